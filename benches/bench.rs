@@ -1,37 +1,65 @@
 use aoc2023 as aoc_lib;
-use criterion::{criterion_group, criterion_main, Criterion};
+use aoc_lib::{NAMES, REGISTRY};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-macro_rules! benchmarks_internal {
-  ( $($day:ident),* ) => {
-    paste::paste!{
-      $(fn [<$day _benchmark>](c: &mut Criterion) {
-          use aoc_lib::$day;
-          let input_data = aoc_lib::utils::read_inputs("input", &vec![stringify!($day)])
-            .expect("can't read input");
-          let input = $day::generator(&input_data[0]);
-          c.bench_function(concat!(stringify!($day), " gen"), |b| {
-            b.iter(|| $day::generator(&input_data[0]))
-          });
-          c.bench_function(concat!(stringify!($day), " part 1"), |b| {
-            b.iter(|| $day::part1(&input))
-          });
-          c.bench_function(concat!(stringify!($day), " part 2"), |b| {
-            b.iter(|| $day::part2(&input))
-          });
-        }
-        criterion_group!($day, [<$day _benchmark>]);
-      )*
+/// Find the input sets, which are the directories with an answers.yml
+fn input_sets() -> Vec<PathBuf> {
+  let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+  let mut sets: Vec<PathBuf> = fs::read_dir(root).expect("can't read crate dir")
+    .filter_map(|entry| entry.ok().map(|e| e.path()))
+    .filter(|path| path.join("answers.yml").is_file())
+    .collect();
+  sets.sort();
+  sets
+}
 
-      criterion_main!($($day),*);
-    }
-  };
+/// Read the days of an input set that have stored answers, so that only the
+/// days that are known to solve the set are measured.
+fn solved_days(set: &Path) -> Vec<String> {
+  let answers: serde_yaml::Value = fs::read_to_string(set.join("answers.yml")).ok()
+    .and_then(|text| serde_yaml::from_str(&text).ok())
+    .unwrap_or_default();
+  NAMES.iter()
+    .filter(|day| answers["days"][**day].is_sequence())
+    .map(|day| day.to_string())
+    .collect()
 }
 
-#[macro_export]
-macro_rules! benchmarks {
-  ( $($day:literal),* ) => {
-    paste::paste!{ benchmarks_internal!{$( [<day $day>] ),*} }
+fn benchmarks(c: &mut Criterion) {
+  let sets: Vec<(String, Vec<String>, PathBuf)> = input_sets().into_iter()
+    .map(|path| {
+      let name = path.file_name().unwrap().to_string_lossy().to_string();
+      (name, solved_days(&path), path)
+    })
+    .collect();
+  for solution in REGISTRY {
+    let day = solution.name;
+    let mut group = c.benchmark_group(day);
+    // Some days take seconds, so keep to the minimum number of samples
+    group.sample_size(10);
+    for (name, solved, path) in &sets {
+      if !solved.iter().any(|s| s == day) {
+        continue;
+      }
+      let Ok(data) = fs::read_to_string(path.join(format!("{day}.txt"))) else {
+        continue;
+      };
+      let input = (solution.generator)(&data).expect("can't parse input");
+      group.bench_with_input(BenchmarkId::new("gen", name), &data, |b, data| {
+        b.iter(|| (solution.generator)(data))
+      });
+      group.bench_with_input(BenchmarkId::new("part 1", name), &input, |b, input| {
+        b.iter(|| input.part1())
+      });
+      group.bench_with_input(BenchmarkId::new("part 2", name), &input, |b, input| {
+        b.iter(|| input.part2())
+      });
+    }
+    group.finish();
   }
 }
 
-benchmarks!(1);
\ No newline at end of file
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -1,4 +1,8 @@
 use std::iter::Iterator;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::random::Random;
 
 pub fn part1(lines: &[Vec<char>]) -> u32 {
   lines.iter().map(|line| {
@@ -65,15 +69,59 @@ pub fn part2(lines: &[Vec<char>]) -> usize {
   }).sum()
 }
 
-pub fn generator(input: &str) -> Vec<Vec<char>> {
-  input.lines()
+pub fn generator(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+  Ok(input.lines()
     .map(|l| l.chars().collect())
-    .collect()
+    .collect())
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet",
+    part1: Some("142"),
+    part2: None,
+  },
+  Example {
+    input: "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen",
+    part1: None,
+    part2: Some("281"),
+  },
+];
+
+/// Lines of letters, digits and spelled out digits, each with at least one digit
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+  (0..size).map(|_| {
+    let mut line = String::new();
+    for _ in 0..rng.gen_range(1..6) {
+      match rng.gen_range(0..3) {
+        0 => line.push(char::from(b'1' + rng.gen_range(0..9))),
+        1 => line.push_str(WORDS.choose(rng).unwrap()),
+        _ => line.extend((0..rng.gen_range(1..4)).map(|_| char::from(b'a' + rng.gen_range(0..26)))),
+      }
+    }
+    if !line.contains(|c: char| c.is_ascii_digit()) {
+      line.insert(rng.gen_range(0..=line.len()), char::from(b'1' + rng.gen_range(0..9)));
+    }
+    line
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day1, Vec<Vec<char>>, u32, usize);
+
 #[cfg(test)]
 mod tests {
-  use crate::day1::{generator, make_numbers, part1, part2, to_digit};
+  use crate::day1::{EXAMPLES, generator, make_numbers, part1, part2, to_digit};
 
   fn str_to_digit(input: &str, first: bool, numbers: &[Vec<char>]) -> Option<usize> {
     let chars: Vec<char> = input.chars().collect();
@@ -81,29 +129,16 @@ mod tests {
   }
 
   fn input() -> String {
-    [
-      "1abc2",
-      "pqr3stu8vwx",
-      "a1b2c3d4e5f",
-      "treb7uchet",
-    ].join("\n")
+    EXAMPLES[0].input.to_string()
   }
 
   fn input2() -> String {
-    [
-      "two1nine",
-      "eightwothree",
-      "abcone2threexyz",
-      "xtwone3four",
-      "4nineeightseven2",
-      "zoneight234",
-      "7pqrstsixteen",
-    ].join("\n")
+    EXAMPLES[1].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let values = generator(&input());
+    let values = generator(&input()).unwrap();
     assert_eq!(values.len(), 4);
     assert_eq!(values[0].len(), 5);
     assert_eq!(values[1].len(), 11);
@@ -113,7 +148,7 @@ mod tests {
 
   #[test]
   fn test_part_1() {
-    let values = generator(&input());
+    let values = generator(&input()).unwrap();
     assert_eq!(142, part1(&values));
   }
 
@@ -130,7 +165,7 @@ mod tests {
 
   #[test]
   fn test_part2() {
-    let lines = generator(&input2());
+    let lines = generator(&input2()).unwrap();
     assert_eq!(281, part2(&lines));
   }
 }
\ No newline at end of file
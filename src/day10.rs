@@ -1,172 +1,108 @@
-use crate::day10::Direction::{EAST, NORTH, SOUTH, WEST};
-
-#[derive(Eq, PartialEq, Copy, Clone)]
-enum Direction {
-  NORTH,
-  SOUTH,
-  EAST,
-  WEST,
-}
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::geom::Dir4::{East, North, South, West};
+use crate::utils::grid::Grid;
+use crate::utils::random::{closed_loop, Random};
 
-const DIRECTIONS: [Direction; 4] = [NORTH, SOUTH, EAST, WEST];
 const GROUND: [bool; 4] = [false; 4];
-const EAST_WEST: [bool; 4] = [false, false, true, true];
-const NORTH_SOUTH: [bool; 4] = [true, true, false, false];
+const EAST_WEST: [bool; 4] = [false, true, false, true];
+const NORTH_SOUTH: [bool; 4] = [true, false, true, false];
 
+/// Whether a tile connects in each direction, indexed by `Dir4`
 type Connects = [bool; 4];
 
 pub struct Field {
-  start: XY,
-  map: Vec<Vec<Connects>>,
+  start: Point2<usize>,
+  map: Grid<Connects>,
 }
 
 impl Field {
   fn connects(&self, x: &usize, y: &usize) -> &Connects {
-    &self.map[*y][*x]
-  }
-  fn width(&self) -> usize {
-    self.map.first().unwrap().len()
-  }
-  fn connect_north(&self, xy: &XY) -> Option<(XY, Direction)> {
-    if xy.y > 0 {
-      match &self.map[xy.y - 1][xy.x] {
-        [_, true, _, _] => Some((XY { x: xy.x, y: xy.y - 1 }, SOUTH)),
-        _ => None,
-      }
-    } else {
-      None
-    }
+    &self.map[(*x, *y)]
   }
 
-  fn connect_south(&self, xy: &XY) -> Option<(XY, Direction)> {
-    if xy.y + 1 < self.map.len() {
-      match &self.map[xy.y + 1][xy.x] {
-        [true, _, _, _] => {
-          Some((XY { x: xy.x, y: xy.y + 1 }, NORTH))
-        }
-        _ => None,
-      }
-    } else {
-      None
-    }
+  /// The neighbor in the direction if it connects back, with the direction it was entered from
+  fn connect(&self, xy: &Point2<usize>, dir: Dir4) -> Option<(Point2<usize>, Dir4)> {
+    let next = (xy.signed() + dir.delta()).unsigned()?;
+    let connects = self.map.get(next.x as i64, next.y as i64)?;
+    connects[dir.reverse() as usize].then_some((next, dir.reverse()))
   }
 
-  fn connect_east(&self, xy: &XY) -> Option<(XY, Direction)> {
-    if xy.x + 1 < self.width() {
-      match &self.map[xy.y][xy.x + 1] {
-        [_, _, _, true] => {
-          Some((XY { x: xy.x + 1, y: xy.y }, WEST))
-        }
-        _ => None,
-      }
-    } else {
-      None
-    }
-  }
-
-  fn connect_west(&self, xy: &XY) -> Option<(XY, Direction)> {
-    if xy.x > 0 {
-      match &self.map[xy.y][xy.x - 1] {
-        [_, _, true, _] => Some((XY { x: xy.x - 1, y: xy.y }, EAST)),
-        _ => None,
-      }
-    } else {
-      None
-    }
-  }
-
-  fn compute_perimeter(&self) -> Vec<XY> {
-    let mut perimeter: Vec<XY> = Vec::new();
+  fn compute_perimeter(&self) -> Option<Vec<Point2<usize>>> {
+    let mut perimeter: Vec<Point2<usize>> = Vec::new();
     perimeter.push(self.start);
-    let (mut curr, mut skip_dir) = match self.connect_north(&self.start) {
-      Some(xy) => xy,
-      _ => match self.connect_south(&self.start) {
-        Some(xy) => xy,
-        _ => match self.connect_east(&self.start) {
-          Some(xy) => xy,
-          _ => panic!("Could not determine a valid connection")
-        }
-      }
-    };
+    let (mut curr, mut skip_dir) = [North, South, East].into_iter()
+      .find_map(|dir| self.connect(&self.start, dir))?;
 
     while curr != self.start {
       perimeter.push(curr);
-      for (connects, to_dir) in self.connects(&curr.x, &curr.y).iter().zip(DIRECTIONS) {
+      let prev = curr;
+      for (connects, to_dir) in self.connects(&curr.x, &curr.y).iter().zip(Dir4::ALL) {
         // Skip if there is no connection or incoming direction
         if !connects || to_dir == skip_dir { continue; }
-        let v = match to_dir {
-          NORTH => self.connect_north(&curr),
-          SOUTH => self.connect_south(&curr),
-          WEST => self.connect_west(&curr),
-          EAST => self.connect_east(&curr),
-        };
-        if let Some((c, dir)) = v {
+        if let Some((c, dir)) = self.connect(&curr, to_dir) {
           curr = c;
           skip_dir = dir;
           break;
         }
       }
+      // A dead end means the loop through the start is broken
+      if curr == prev { return None; }
     }
-    perimeter
+    Some(perimeter)
   }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-pub struct XY {
-  x: usize,
-  y: usize,
-}
-
-pub fn generator(input: &str) -> (Field, Vec<XY>) {
-  let mut start: Option<XY> = None;
-  let map: Vec<Vec<Connects>> = input.lines().enumerate().map(|(y, line)| {
-    line.chars().enumerate().map(|(x, c)| {
-      match c {
-        '|' => [true, true, false, false], // North South
-        '-' => [false, false, true, true], // East West
-        'L' => [true, false, true, false], // North East
-        'J' => [true, false, false, true], // North West
-        '7' => [false, true, false, true], // South West
-        'F' => [false, true, true, false], // South East
-        '.' => [false; 4],
-        'S' => {
-          start = Some(XY { x, y });
-          [true; 4]
-        }
-        _ => panic!("Unexpected character {c}"),
+pub fn generator(input: &str) -> Result<(Field, Vec<Point2<usize>>), ParseError> {
+  let mut start: Option<Point2<usize>> = None;
+  let map = Grid::parse(input, "field", |(x, y), c| {
+    match c {
+      '|' => Ok(NORTH_SOUTH),
+      '-' => Ok(EAST_WEST),
+      'L' => Ok([true, true, false, false]), // North East
+      'J' => Ok([true, false, false, true]), // North West
+      '7' => Ok([false, false, true, true]), // South West
+      'F' => Ok([false, true, true, false]), // South East
+      '.' => Ok([false; 4]),
+      'S' => {
+        start = Some(Point2::new(x, y));
+        Ok([true; 4])
       }
-    }).collect()
-  }).collect();
+      _ => Err(format!("Unexpected character {c}")),
+    }
+  })?;
 
-  if start.is_none() {
-    panic!("Could not determine start");
-  }
+  let Some(start) = start else {
+    return Err(ParseError::at(input, input, "Could not determine start"));
+  };
 
   let mut f = Field {
-    start: start.unwrap(),
+    start,
     map,
   };
 
 
-  f.map[f.start.y][f.start.x][NORTH as usize] = f.connect_north(&f.start).is_some();
-  f.map[f.start.y][f.start.x][SOUTH as usize] = f.connect_south(&f.start).is_some();
-  f.map[f.start.y][f.start.x][EAST as usize] = f.connect_east(&f.start).is_some();
-  f.map[f.start.y][f.start.x][WEST as usize] = f.connect_west(&f.start).is_some();
-  let p = f.compute_perimeter();
-  (f, p)
+  for dir in [North, South, East, West] {
+    f.map[(start.x, start.y)][dir as usize] = f.connect(&f.start, dir).is_some();
+  }
+  let p = f.compute_perimeter()
+    .ok_or_else(|| ParseError::new(start.y + 1, start.x + 1, "Could not find a loop through the start"))?;
+  Ok((f, p))
 }
 
-pub fn part1(puzzle: &(Field, Vec<XY>)) -> u32 {
-  ((puzzle.1.len() + 1) / 2) as u32
+pub fn part1(puzzle: &(Field, Vec<Point2<usize>>)) -> u32 {
+  puzzle.1.len().div_ceil(2) as u32
 }
 
-pub fn part2(puzzle: &(Field, Vec<XY>)) -> u32 {
+pub fn part2(puzzle: &(Field, Vec<Point2<usize>>)) -> u32 {
   // compute area
   let field = &puzzle.0;
-  let mut perimeter: Vec<Vec<bool>> = vec![vec![false; field.width()]; field.map.len()];
-  let mut x_min = field.width();
+  let mut perimeter = Grid::filled(field.map.width(), field.map.height(), false);
+  let mut x_min = field.map.width();
   let mut x_max = 0;
-  let mut y_min = field.map.len();
+  let mut y_min = field.map.height();
   let mut y_max = 0;
 
   for xy in &puzzle.1 {
@@ -174,7 +110,7 @@ pub fn part2(puzzle: &(Field, Vec<XY>)) -> u32 {
     x_max = x_max.max(xy.x);
     y_min = y_min.min(xy.y);
     y_max = y_max.max(xy.y);
-    perimeter[xy.y][xy.x] = true;
+    perimeter[(xy.x, xy.y)] = true;
   }
 
   let mut area = 0;
@@ -186,43 +122,179 @@ pub fn part2(puzzle: &(Field, Vec<XY>)) -> u32 {
     include = false;
     for x in x_min..=x_max {
       curr = field.connects(&x, &y);
-      if perimeter[y][x] {
+      if perimeter[(x, y)] {
         match curr {
           &NORTH_SOUTH => {
             include = !include;
             prev = &NORTH_SOUTH;
           }
           &EAST_WEST => {}
-          c if c[NORTH as usize] == prev[NORTH as usize] => {
+          c if c[North as usize] == prev[North as usize] => {
             include = !include;
             prev = c;
           }
           _ => {}
         }
-      } else {
-        if include { area += 1 }
+      } else if include {
+        area += 1
       }
     }
   }
   area
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...",
+    part1: Some("8"),
+    part2: None,
+  },
+  Example {
+    input: ".....
+.S-7.
+.|.|.
+.L-J.
+.....",
+    part1: None,
+    part2: Some("1"),
+  },
+  Example {
+    input: "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+    part1: None,
+    part2: Some("4"),
+  },
+  Example {
+    input: "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........",
+    part1: None,
+    part2: Some("4"),
+  },
+  Example {
+    input: ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+    part1: None,
+    part2: Some("8"),
+  },
+  Example {
+    input: "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L",
+    part1: None,
+    part2: Some("10"),
+  },
+];
+
+/// The pipe that connects in the directions, indexed by `Dir4`
+fn pipe(connects: Connects) -> char {
+  match connects {
+    NORTH_SOUTH => '|',
+    EAST_WEST => '-',
+    [true, true, false, false] => 'L',
+    [true, false, false, true] => 'J',
+    [false, false, true, true] => '7',
+    [false, true, true, false] => 'F',
+    _ => '.',
+  }
+}
+
+/// A loop through the start on a square field of about the side, with pipes that are not part of
+/// the loop all around it
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  const PIPES: &[u8] = b"|-LJ7F....";
+  let block = rng.gen_range(4..=6);
+  let (side, tiles) = closed_loop(rng, size / block, block);
+  let mut map = Grid::filled(side, side, '.');
+  for y in 0..side {
+    for x in 0..side {
+      map[(x, y)] = char::from(*PIPES.choose(rng).unwrap());
+    }
+  }
+  let mut on_loop = Grid::filled(side, side, false);
+  for (i, tile) in tiles.iter().enumerate() {
+    let mut connects = GROUND;
+    for other in [tiles[(i + tiles.len() - 1) % tiles.len()], tiles[(i + 1) % tiles.len()]] {
+      let dir = Dir4::ALL.into_iter().find(|d| tile.signed() + d.delta() == other.signed()).unwrap();
+      connects[dir as usize] = true;
+    }
+    map[(tile.x, tile.y)] = pipe(connects);
+    on_loop[(tile.x, tile.y)] = true;
+  }
+  let start = tiles[rng.gen_range(0..tiles.len())];
+  map[(start.x, start.y)] = 'S';
+  // Only the loop may lead into the start
+  for dir in Dir4::ALL {
+    if let Some(next) = (start.signed() + dir.delta()).unsigned().filter(|n| n.x < side && n.y < side) {
+      if !on_loop[(next.x, next.y)] {
+        map[(next.x, next.y)] = '.';
+      }
+    }
+  }
+  map.to_string()
+}
+
+solution!(Day10, (Field, Vec<Point2<usize>>), u32, u32);
+
 #[cfg(test)]
 mod tests {
-  use crate::day10::{generator, part1, part2, XY};
+  use crate::day10::{EXAMPLES, generator, part1, part2};
+  use crate::utils::geom::Dir4::{East, North};
+  use crate::utils::geom::Point2;
 
   fn input() -> String {
-    ".....
-.S-7.
-.|.|.
-.L-J.
-.....".to_string()
+    EXAMPLES[1].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let (f, _) = generator(&input());
-    assert_eq!(5, f.map.first().unwrap().len());
+    let (f, _) = generator(&input()).unwrap();
+    assert_eq!(5, f.map.width());
+  }
+
+  #[test]
+  fn test_generator_errors() {
+    let e = generator(".....\n.S-7.\n.|x|.").err().unwrap();
+    assert_eq!((3, 3), (e.line, e.column));
+
+    let e = generator("...\n.-.").err().unwrap();
+    assert_eq!("Could not determine start", e.message);
+
+    let e = generator("S-.\n...").err().unwrap();
+    assert_eq!((1, 1), (e.line, e.column));
   }
 
   #[test]
@@ -231,9 +303,10 @@ mod tests {
 .FJ|.
 SJ.L7
 |F--J
-LJ...");
-    let n = f.connect_north(&XY { x: 3, y: 1 });
+LJ...").unwrap();
+    let n = f.connect(&Point2::new(3, 1), North);
     assert!(n.is_some());
+    assert!(f.connect(&Point2::new(3, 1), East).is_none());
   }
 
   #[test]
@@ -245,7 +318,7 @@ LJ...");
 .FJ|.
 SJ.L7
 |F--J
-LJ...");
+LJ...").unwrap();
     assert_eq!(8, part1(&f));
   }
 
@@ -255,7 +328,7 @@ LJ...");
 .S-7.
 .|.|.
 .L-J.
-.....");
+.....").unwrap();
     assert_eq!(1, part2(&f));
 
     let f = generator("...........
@@ -266,7 +339,7 @@ LJ...");
 .|L-7.F-J|.
 .|..|.|..|.
 .L--J.L--J.
-...........");
+...........").unwrap();
     assert_eq!(4, part2(&f));
 
     let f = generator("..........
@@ -277,7 +350,7 @@ LJ...");
 .|L-7F-J|.
 .|..||..|.
 .L--JL--J.
-..........");
+..........").unwrap();
     assert_eq!(4, part2(&f));
 
     let f = generator(".F----7F7F7F7F-7....
@@ -289,7 +362,7 @@ L--J.L7...LJS7F-7L7.
 ....L7.F7||L7|.L7L7|
 .....|FJLJ|FJ|F7|.LJ
 ....FJL-7.||.||||...
-....L---J.LJ.LJLJ...");
+....L---J.LJ.LJLJ...").unwrap();
     assert_eq!(8, part2(&f));
 
     let f = generator("FF7FSF7F7F7F7F7F---7
@@ -301,7 +374,7 @@ L---JF-JLJ.||-FJLJJ7
 |FFJF7L7F-JF7|JL---7
 7-L-JL7||F7|L7F-7F7|
 L.L7LFJ|||||FJL7||LJ
-L7JLJL-JLJLJL--JLJ.L");
+L7JLJL-JLJLJL--JLJ.L").unwrap();
     assert_eq!(10, part2(&f));
   }
 }
\ No newline at end of file
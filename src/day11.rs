@@ -1,13 +1,13 @@
-const EMPTY_SPACE: char = '.';
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::Point2;
+use crate::utils::grid::Grid;
+use crate::utils::random::{grid, Random};
 
-#[derive(Eq, PartialEq, Debug)]
-struct XY {
-  x: usize,
-  y: usize,
-}
+const EMPTY_SPACE: char = '.';
 
 pub struct Image {
-  galaxies: Vec<XY>,
+  galaxies: Vec<Point2<usize>>,
   empty_rows: Vec<bool>,
   empty_cols: Vec<bool>,
 }
@@ -32,7 +32,7 @@ impl Image {
     let mut normal_moves = 0;
     let mut empty_moves = 0;
 
-    for i in 0..self.galaxies.len() - 1 {
+    for i in 0..self.galaxies.len() {
       for j in i + 1..self.galaxies.len() {
         let (n, e) = self.distance_between(&self.galaxies[i], &self.galaxies[j]);
         normal_moves += n;
@@ -41,7 +41,7 @@ impl Image {
     }
     (normal_moves, empty_moves)
   }
-  fn distance_between(&self, g1: &XY, g2: &XY) -> (u64, u64) {
+  fn distance_between(&self, g1: &Point2<usize>, g2: &Point2<usize>) -> (u64, u64) {
     let (normal_moves, empty_moves) = if g1.x < g2.x {
       distance_between(g1.x, g2.x, &self.empty_cols)
     } else {
@@ -56,43 +56,36 @@ impl Image {
     (normal_moves + n, empty_moves + e)
   }
 
-  fn _distance_between_w_expansion(&self, g1: &XY, g2: &XY, empty_as: u64) -> u64 {
+  fn _distance_between_w_expansion(&self, g1: &Point2<usize>, g2: &Point2<usize>, empty_as: u64) -> u64 {
     let (n, e) = self.distance_between(g1, g2);
     n + (e * empty_as)
   }
 
-  fn from(input: &str) -> Image {
-    let chars: Vec<Vec<char>> = input.lines()
-      .map(|line| line.chars().collect())
+  fn from(input: &str) -> Result<Image, ParseError> {
+    let chars = Grid::parse(input, "image", |_, c| Ok(c))?;
+
+    let empty_rows: Vec<bool> = chars.rows()
+      .map(|row| row.iter().all(|c| c == &EMPTY_SPACE))
+      .collect();
+
+    let empty_cols: Vec<bool> = chars.columns()
+      .map(|mut column| column.all(|c| c == &EMPTY_SPACE))
+      .collect();
+    let galaxies = chars.iter()
+      .filter(|(_, c)| *c != &EMPTY_SPACE)
+      .map(|((x, y), _)| Point2::new(x, y))
       .collect();
 
-    let empty_rows: Vec<bool> = chars.iter()
-      .map(|line| {
-        line.iter().all(|c| c == &EMPTY_SPACE)
-      }).collect();
-
-    let empty_cols: Vec<bool> = (0..chars.first().unwrap().len()).map(|c| {
-      chars.iter().all(|row| row[c] == EMPTY_SPACE)
-    }).collect();
-    let mut galaxies = Vec::new();
-    chars.iter().enumerate().for_each(|(y, r)| {
-      r.iter().enumerate().for_each(|(x, c)| {
-        if c != &EMPTY_SPACE {
-          galaxies.push(XY { x, y });
-        }
-      })
-    });
-
-    Image {
+    Ok(Image {
       galaxies,
       empty_rows,
       empty_cols,
-    }
+    })
   }
 }
 
-pub fn generator(input: &str) -> (u64, u64) {
-  Image::from(input).compute_total_distances()
+pub fn generator(input: &str) -> Result<(u64, u64), ParseError> {
+  Ok(Image::from(input)?.compute_total_distances())
 }
 
 pub fn part1(distances: &(u64, u64)) -> u64 {
@@ -105,12 +98,10 @@ pub fn part2(distances: &(u64, u64)) -> u64 {
   n + (e * 1000000)
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day11::{generator, Image, part1, XY};
-
-  fn input() -> String {
-    "...#......
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "...#......
 .......#..
 #.........
 ..........
@@ -119,15 +110,38 @@ mod tests {
 .........#
 ..........
 .......#..
-#...#.....".to_string()
+#...#.....",
+    part1: Some("374"),
+    part2: None,
+  },
+];
+
+/// A square image of the side with a few galaxies and some rows and columns without any
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let empty_rows: Vec<bool> = (0..size).map(|_| rng.gen_bool(0.1)).collect();
+  let empty_cols: Vec<bool> = (0..size).map(|_| rng.gen_bool(0.1)).collect();
+  grid(size, size, |x, y| {
+    if !empty_rows[y] && !empty_cols[x] && rng.gen_bool(0.03) { '#' } else { EMPTY_SPACE }
+  })
+}
+
+solution!(Day11, (u64, u64), u64, u64);
+
+#[cfg(test)]
+mod tests {
+  use crate::day11::{EXAMPLES, generator, Image, part1};
+  use crate::utils::geom::Point2;
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let i = Image::from(&input());
+    let i = Image::from(&input()).unwrap();
     assert_eq!(9, i.galaxies.len());
-    assert_eq!(XY { x: 3, y: 0 }, i.galaxies[0]);
-    assert_eq!(XY { x: 4, y: 9 }, i.galaxies[8]);
+    assert_eq!(Point2::new(3, 0), i.galaxies[0]);
+    assert_eq!(Point2::new(4, 9), i.galaxies[8]);
     assert!(!i.empty_rows[0]);
     assert!(i.empty_rows[3]);
     assert!(i.empty_rows[7]);
@@ -138,7 +152,7 @@ mod tests {
 
   #[test]
   fn test_moves() {
-    let i = Image::from(&input());
+    let i = Image::from(&input()).unwrap();
     assert_eq!(9, i._distance_between_w_expansion(&i.galaxies[4], &i.galaxies[8], 2));
     assert_eq!(15, i._distance_between_w_expansion(&i.galaxies[0], &i.galaxies[6], 2));
     assert_eq!(5, i._distance_between_w_expansion(&i.galaxies[7], &i.galaxies[8], 2));
@@ -146,13 +160,13 @@ mod tests {
 
   #[test]
   fn test_part1() {
-    let i = generator(&input());
+    let i = generator(&input()).unwrap();
     assert_eq!(374, part1(&i));
   }
 
   #[test]
   fn test_part2() {
-    let i = Image::from(&input());
+    let i = Image::from(&input()).unwrap();
     assert_eq!(1030, i._compute_total_distances_w_expansion(10));
     assert_eq!(8410, i._compute_total_distances_w_expansion(100));
   }
@@ -1,20 +1,25 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use crate::day12::Status::{DAMAGED, OPERATIONAL, UNKNOWN};
+use rand::Rng;
+use crate::day12::Status::{Damaged, Operational, Unknown};
+use crate::utils::{parse_at, solution, Example, ParseError};
+use crate::utils::random::Random;
+
+pub mod reference;
 
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
 enum Status {
-  UNKNOWN,
-  OPERATIONAL,
-  DAMAGED,
+  Unknown,
+  Operational,
+  Damaged,
 }
 
 impl Status {
-  fn to_char(&self) -> char {
+  fn to_char(self) -> char {
     match self {
-      UNKNOWN => { '?' }
-      OPERATIONAL => { '.' }
-      DAMAGED => { '#' }
+      Unknown => { '?' }
+      Operational => { '.' }
+      Damaged => { '#' }
     }
   }
 }
@@ -42,22 +47,28 @@ impl Display for PumpRow {
 }
 
 impl PumpRow {
-  fn from(input: &str) -> PumpRow {
-    let (pumps, groups) = input.split_once(' ').unwrap();
-    let pumps: Vec<Status> = pumps.chars().map(|c| match c {
-      '#' => DAMAGED,
-      '.' => OPERATIONAL,
-      '?' => UNKNOWN,
-      _ => panic!("Unexpected character for pump status {c}"),
-    }).collect();
-    let groups = groups.split(',').map(|v| v.parse().unwrap()).collect();
-    let last_damaged = pumps.iter().rposition(|s| s == &DAMAGED);
-
-    PumpRow {
+  fn from(input: &str) -> Result<PumpRow, ParseError> {
+    let (pumps, groups) = input.split_once(' ')
+      .ok_or_else(|| ParseError::at(input, input, "Expected '<pumps> <groups>'"))?;
+    let pumps: Vec<Status> = pumps.char_indices().map(|(i, c)| match c {
+      '#' => Ok(Damaged),
+      '.' => Ok(Operational),
+      '?' => Ok(Unknown),
+      _ => Err(ParseError::at(input, &pumps[i..], format!("Unexpected character for pump status {c}"))),
+    }).collect::<Result<_, _>>()?;
+    let groups: Vec<usize> = groups.split(',')
+      .map(|v| match parse_at(input, v)? {
+        0 => Err(ParseError::at(input, v, "Groups must not be empty")),
+        g => Ok(g),
+      })
+      .collect::<Result<_, _>>()?;
+    let last_damaged = pumps.iter().rposition(|s| s == &Damaged);
+
+    Ok(PumpRow {
       pumps,
       groups,
       last_damaged,
-    }
+    })
   }
 
   fn group_starts(&self, group_idx: usize, start: usize) -> Vec<usize> {
@@ -68,13 +79,13 @@ impl PumpRow {
         u if u > self.pumps.len() => {
           continue;
         }
-        u if u < self.pumps.len() && &self.pumps[u] == &DAMAGED => {
+        u if u < self.pumps.len() && self.pumps[u] == Damaged => {
           continue;
         }
-        u if self.pumps[idx..u].iter().any(|s| s == &OPERATIONAL) => {
+        u if self.pumps[idx..u].iter().any(|s| s == &Operational) => {
           continue;
         }
-        _ if idx > 0 && &self.pumps[idx - 1] == &DAMAGED => {
+        _ if idx > 0 && self.pumps[idx - 1] == Damaged => {
           continue;
         }
         _ => starts.push(idx),
@@ -87,7 +98,7 @@ impl PumpRow {
   fn count_possibilities(&self) -> u64 {
     let group_starts = self.compute_group_starts();
     let mut cache: HashMap<(usize, usize), u64> = HashMap::new();
-    count_possibilities_w_cache(&self, &group_starts, 0, 0, &mut cache)
+    count_possibilities_w_cache(self, &group_starts, 0, 0, &mut cache)
   }
 
   fn compute_group_starts(&self) -> Vec<Vec<usize>> {
@@ -104,12 +115,12 @@ impl PumpRow {
     let mut groups = self.groups.clone();
 
     for _ in 0..4 {
-      pumps.push(UNKNOWN);
+      pumps.push(Unknown);
       pumps.extend(&self.pumps);
 
       groups.extend(&self.groups);
     }
-    let last_damaged = pumps.iter().rposition(|s| s == &DAMAGED);
+    let last_damaged = pumps.iter().rposition(|s| s == &Damaged);
 
     PumpRow {
       pumps,
@@ -120,7 +131,7 @@ impl PumpRow {
 }
 
 
-fn count_possibilities_w_cache(pr: &PumpRow, group_starts: &Vec<Vec<usize>>, level: usize, min_value: usize,
+fn count_possibilities_w_cache(pr: &PumpRow, group_starts: &[Vec<usize>], level: usize, min_value: usize,
                                cache: &mut HashMap<(usize, usize), u64>) -> u64 {
   if let Some(sum) = cache.get(&(level, min_value)) {
     *sum
@@ -131,7 +142,7 @@ fn count_possibilities_w_cache(pr: &PumpRow, group_starts: &Vec<Vec<usize>>, lev
   }
 }
 
-fn count_possibilities(pr: &PumpRow, group_starts: &Vec<Vec<usize>>, level: usize, min_value: usize,
+fn count_possibilities(pr: &PumpRow, group_starts: &[Vec<usize>], level: usize, min_value: usize,
                        cache: &mut HashMap<(usize, usize), u64>) -> u64 {
   let start = group_starts[level].binary_search(&min_value).unwrap_or_else(|i| i);
 
@@ -142,10 +153,9 @@ fn count_possibilities(pr: &PumpRow, group_starts: &Vec<Vec<usize>>, level: usiz
   if level + 1 == group_starts.len() {
     let mut sum = 0;
     // compute last damaged position
-    for i in start..group_starts[level].len() {
-      let mv = &group_starts[level][i];
+    for mv in &group_starts[level][start..] {
       // If we left a damaged pipe then the reset of the options are not valid.
-      if pr.pumps[min_value..*mv].iter().any(|s| s == &DAMAGED) { break; }
+      if pr.pumps[min_value..*mv].iter().any(|s| s == &Damaged) { break; }
       // If we have a trailing damaged that is not part of this then this is not a valid choice
       if pr.last_damaged.is_some_and(|v| v > *mv + pr.groups[level]) { continue; }
       if pr.last_damaged.unwrap_or(*mv) < *mv + pr.groups[level] {
@@ -155,77 +165,105 @@ fn count_possibilities(pr: &PumpRow, group_starts: &Vec<Vec<usize>>, level: usiz
     sum
   } else {
     let mut sum = 0;
-    for i in start..group_starts[level].len() {
-      let mv = &group_starts[level][i];
+    for mv in &group_starts[level][start..] {
       // If we left a damaged pipe then the reset of the options are not valid.
-      if pr.pumps[min_value..*mv].iter().any(|s| s == &DAMAGED) { break; }
+      if pr.pumps[min_value..*mv].iter().any(|s| s == &Damaged) { break; }
       sum += count_possibilities_w_cache(pr, group_starts, level + 1, mv + 1 + pr.groups[level], cache);
     }
     sum
   }
 }
 
-pub fn generator(input: &str) -> Vec<PumpRow> {
+pub fn generator(input: &str) -> Result<Vec<PumpRow>, ParseError> {
   input.lines()
-    .map(|line| PumpRow::from(line))
+    .map(|line| PumpRow::from(line).map_err(|e| e.within(input, line)))
     .collect()
 }
 
 pub fn part1(rows: &[PumpRow]) -> u64 {
-  rows.iter().map(|pr| {
-    let p = pr.count_possibilities();
-    p
-  }).sum()
+  rows.iter().map(|pr| pr.count_possibilities()).sum()
 }
 
 pub fn part2(rows: &[PumpRow]) -> u64 {
   rows.iter().map(|pr| pr.unfold().count_possibilities()).sum()
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day12::{generator, part1, part2, PumpRow};
-  use crate::day12::Status::{DAMAGED, OPERATIONAL, UNKNOWN};
-
-  fn input() -> String {
-    "???.### 1,1,3
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "???.### 1,1,3
 .??..??...?##. 1,1,3
 ?#?#?#?#?#?#?#? 1,3,1,6
 ????.#...#... 4,1,1
 ????.######..#####. 1,6,5
-?###???????? 3,2,1".to_string()
+?###???????? 3,2,1",
+    part1: Some("21"),
+    part2: Some("525152"),
+  },
+];
+
+/// Rows of up to six groups with an arrangement that fits, where some of the pumps are unknown
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  (0..size).map(|_| {
+    let groups: Vec<usize> = (0..rng.gen_range(1..=6)).map(|_| rng.gen_range(1..=5)).collect();
+    let mut pumps = ".".repeat(rng.gen_range(0..=3));
+    for (i, group) in groups.iter().enumerate() {
+      if i > 0 {
+        pumps.push_str(&".".repeat(rng.gen_range(1..=3)));
+      }
+      pumps.push_str(&"#".repeat(*group));
+    }
+    pumps.push_str(&".".repeat(rng.gen_range(0..=3)));
+    let pumps: String = pumps.chars().map(|c| if rng.gen_bool(0.4) { '?' } else { c }).collect();
+    let groups: Vec<String> = groups.iter().map(|g| g.to_string()).collect();
+    format!("{pumps} {}", groups.join(","))
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day12, Vec<PumpRow>, u64, u64);
+
+#[cfg(test)]
+mod tests {
+  use proptest::collection::vec;
+  use proptest::prelude::*;
+  use crate::day12::{EXAMPLES, generator, part1, part2, PumpRow, random_input, reference};
+  use crate::day12::Status::{Damaged, Operational, Unknown};
+  use crate::utils::differential::check;
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let rows = generator(&input());
+    let rows = generator(&input()).unwrap();
     assert_eq!(6, rows.len());
   }
 
   #[test]
   fn test_pump_row() {
-    let pr = PumpRow::from("???.### 1,1,3");
-    assert_eq!(&pr.pumps, &vec![UNKNOWN, UNKNOWN, UNKNOWN, OPERATIONAL, DAMAGED, DAMAGED, DAMAGED]);
+    let pr = PumpRow::from("???.### 1,1,3").unwrap();
+    assert_eq!(&pr.pumps, &vec![Unknown, Unknown, Unknown, Operational, Damaged, Damaged, Damaged]);
     assert_eq!(&pr.groups, &vec![1, 1, 3]);
     let group_starts = pr.compute_group_starts();
     assert_eq!(group_starts[0], vec![0, 1, 2]);
     assert_eq!(group_starts[1], vec![2]);
     assert_eq!(group_starts[2], vec![4]);
 
-    let pr = PumpRow::from(".??..??...?##. 1,1,3");
+    let pr = PumpRow::from(".??..??...?##. 1,1,3").unwrap();
     let group_starts = pr.compute_group_starts();
     assert_eq!(group_starts[0], vec![1, 2, 5, 6]);
     assert_eq!(group_starts[1], vec![5, 6]);
     assert_eq!(group_starts[2], vec![10]);
 
-    let pr = PumpRow::from("?#?#?#?#?#?#?#? 1,3,1,6");
+    let pr = PumpRow::from("?#?#?#?#?#?#?#? 1,3,1,6").unwrap();
     let group_starts = pr.compute_group_starts();
     assert_eq!(group_starts[0], vec![1, 3, 5, 7, 9, 11, 13]);
     assert_eq!(group_starts[1], vec![3, 5, 7, 9, 11]);
     assert_eq!(group_starts[2], vec![7, 9, 11, 13]);
     assert_eq!(group_starts[3], vec![9]);
 
-    let pr = PumpRow::from("????.######..#####. 1,6,5");
+    let pr = PumpRow::from("????.######..#####. 1,6,5").unwrap();
     let group_starts = pr.compute_group_starts();
     assert_eq!(group_starts[0], vec![0, 1, 2, 3]);
     assert_eq!(group_starts[1], vec![5]);
@@ -234,52 +272,76 @@ mod tests {
 
   #[test]
   fn test_possibilities() {
-    let pr = PumpRow::from("???.### 1,1,3");
+    let pr = PumpRow::from("???.### 1,1,3").unwrap();
     assert_eq!(1, pr.count_possibilities());
     assert_eq!(1, pr.unfold().count_possibilities());
 
-    let pr = PumpRow::from(".??..??...?##. 1,1,3");
+    let pr = PumpRow::from(".??..??...?##. 1,1,3").unwrap();
     assert_eq!(4, pr.count_possibilities());
     assert_eq!(16384, pr.unfold().count_possibilities());
 
-    let pr = PumpRow::from("?#?#?#?#?#?#?#? 1,3,1,6");
+    let pr = PumpRow::from("?#?#?#?#?#?#?#? 1,3,1,6").unwrap();
     assert_eq!(1, pr.count_possibilities());
     assert_eq!(1, pr.unfold().count_possibilities());
 
-    let pr = PumpRow::from("????.#...#... 4,1,1");
+    let pr = PumpRow::from("????.#...#... 4,1,1").unwrap();
     assert_eq!(1, pr.count_possibilities());
     assert_eq!(16, pr.unfold().count_possibilities());
 
-    let pr = PumpRow::from("????.######..#####. 1,6,5");
+    let pr = PumpRow::from("????.######..#####. 1,6,5").unwrap();
     assert_eq!(4, pr.count_possibilities());
     assert_eq!(2500, pr.unfold().count_possibilities());
 
-    let pr = PumpRow::from("?###???????? 3,2,1");
+    let pr = PumpRow::from("?###???????? 3,2,1").unwrap();
     assert_eq!(10, pr.count_possibilities());
     //assert_eq!(506250, pr.unfold().count_possibilities());
   }
 
   #[test]
   fn test_failures() {
-    let pr = PumpRow::from("###???#??#??????? 4,4,1");
+    let pr = PumpRow::from("###???#??#??????? 4,4,1").unwrap();
     assert_eq!(6, pr.count_possibilities());
 
-    let pr = PumpRow::from("##.?.????#??.?#?## 2,1,1,1,1,5");
+    let pr = PumpRow::from("##.?.????#??.?#?## 2,1,1,1,1,5").unwrap();
     assert_eq!(5, pr.count_possibilities());
 
-    let pr = PumpRow::from("##.???#??.??..# 2,1,1,1,1");
+    let pr = PumpRow::from("##.???#??.??..# 2,1,1,1,1").unwrap();
     assert_eq!(8, pr.count_possibilities());
   }
 
   #[test]
   fn test_part1() {
-    let rows = generator(&input());
+    let rows = generator(&input()).unwrap();
     assert_eq!(21, part1(&rows));
   }
 
   #[test]
   fn test_part2() {
-    let rows = generator(&input());
+    let rows = generator(&input()).unwrap();
     assert_eq!(525152, part2(&rows));
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_reference() {
+    assert_eq!(21, reference::part1(&generator(EXAMPLES[0].input).unwrap()));
+    check(random_input, generator, 10, 0..10, |rows| part1(rows), |rows| reference::part1(rows));
+  }
+
+  proptest! {
+    #[test]
+    fn test_unfold_properties(pumps in "[?.#]{1,20}", groups in vec(1..6_usize, 1..6)) {
+      let groups: Vec<String> = groups.iter().map(|g| g.to_string()).collect();
+      let pr = PumpRow::from(&format!("{pumps} {}", groups.join(","))).unwrap();
+      let unfolded = pr.unfold();
+      // Five copies of the groups, and of the pumps with an unknown between each
+      prop_assert_eq!(5 * pr.groups.len(), unfolded.groups.len());
+      prop_assert!(unfolded.groups.chunks(pr.groups.len()).all(|c| c == pr.groups));
+      prop_assert_eq!(5 * pr.pumps.len() + 4, unfolded.pumps.len());
+      for (i, copy) in unfolded.pumps.chunks(pr.pumps.len() + 1).enumerate() {
+        prop_assert_eq!(&pr.pumps[..], &copy[..pr.pumps.len()]);
+        prop_assert!(i == 4 || copy[pr.pumps.len()] == Unknown);
+      }
+      prop_assert_eq!(pr.last_damaged.map(|d| d + 4 * (pr.pumps.len() + 1)), unfolded.last_damaged);
+    }
+  }
+}
@@ -0,0 +1,32 @@
+//! Try every way to fill in the unknown pumps, keeping those whose groups match
+
+use crate::day12::{PumpRow, Status};
+use crate::day12::Status::{Damaged, Operational, Unknown};
+
+pub fn part1(rows: &[PumpRow]) -> u64 {
+  rows.iter().map(|row| arrangements(row, &mut row.pumps.clone(), 0)).sum()
+}
+
+/// The arrangements of the row with the unknown pumps before the index already filled in
+fn arrangements(row: &PumpRow, pumps: &mut Vec<Status>, idx: usize) -> u64 {
+  match pumps[idx..].iter().position(|p| *p == Unknown) {
+    None => u64::from(groups(pumps) == row.groups),
+    Some(pos) => {
+      let mut count = 0;
+      for status in [Operational, Damaged] {
+        pumps[idx + pos] = status;
+        count += arrangements(row, pumps, idx + pos + 1);
+      }
+      pumps[idx + pos] = Unknown;
+      count
+    }
+  }
+}
+
+/// The lengths of the runs of damaged pumps
+fn groups(pumps: &[Status]) -> Vec<usize> {
+  pumps.split(|p| *p != Damaged)
+    .map(|run| run.len())
+    .filter(|len| *len > 0)
+    .collect()
+}
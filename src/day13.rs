@@ -1,37 +1,40 @@
-use crate::day13::Loc::{ASH, ROCK};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::day13::Loc::{Ash, Rock};
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::grid::{Cell, Grid};
+use crate::utils::parse::blocks;
+use crate::utils::random::Random;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum Loc {
-  ASH,
-  ROCK,
+  Ash,
+  Rock,
 }
 
-impl Loc {
-  fn from(c: char) -> Loc {
+impl Cell for Loc {
+  fn from_char(c: char) -> Option<Loc> {
     match c {
-      '.' => ASH,
-      '#' => ROCK,
-      _ => panic!("Unexpected space {c}"),
+      '.' => Some(Ash),
+      '#' => Some(Rock),
+      _ => None,
     }
   }
 }
 
 pub struct Pattern {
-  layout: Vec<Vec<Loc>>,
+  layout: Grid<Loc>,
+  // the layout with the columns as rows, so both mirrors are found the same way
+  transposed: Grid<Loc>,
 }
 
 impl Pattern {
-  fn from(input: &str) -> Pattern {
-    let map = input.lines().map(|line| line.chars().map(Loc::from).collect()).collect();
-    Pattern { layout: map }
-  }
-
-  fn height(&self) -> u32 {
-    self.layout.len() as u32
-  }
-
-  fn width(&self) -> u32 {
-    self.layout[0].len() as u32
+  fn from(input: &str) -> Result<Pattern, ParseError> {
+    let layout = Grid::parse(input, "pattern", |_, c| {
+      Loc::from_char(c).ok_or_else(|| format!("Unexpected space {c}"))
+    })?;
+    let transposed = layout.transposed();
+    Ok(Pattern { layout, transposed })
   }
 
   fn score(&self, smudges: u32) -> u32 {
@@ -41,79 +44,58 @@ impl Pattern {
     }
   }
 
-  fn row_equal_count(&self, r1: u32, r2: u32) -> u32 {
-    (0..self.layout[0].len()).filter(|c| {
-      self.layout[r1 as usize][*c] == self.layout[r2 as usize][*c]
-    }).count() as u32
+  fn check_for_vertical_mirror(&self, smudges: u32) -> Option<u32> {
+    find_mirror(&self.transposed, smudges)
   }
 
-  fn col_equal_count(&self, c1: u32, c2: u32) -> u32 {
-    self.layout.iter().filter(|row| row[c1 as usize] == row[c2 as usize])
-      .count() as u32
+  fn check_for_horizontal_mirror(&self, smudges: u32) -> Option<u32> {
+    find_mirror(&self.layout, smudges)
   }
+}
 
-  fn check_for_vertical_mirror(&self, smudges: u32) -> Option<u32> {
-    // Find the adjacent columns that are identical
-    for c1 in 0..self.width() - 1 {
-      let mut smudges = smudges;
-      match self.height() - self.col_equal_count(c1, c1 + 1) {
-        v if v > smudges => continue,
-        v => smudges -= v,
+/// The number of cells that are the same in two rows
+fn equal_count(layout: &Grid<Loc>, r1: u32, r2: u32) -> u32 {
+  layout.row(r1 as usize).iter().zip(layout.row(r2 as usize))
+    .filter(|(l1, l2)| l1 == l2)
+    .count() as u32
+}
+
+/// Find the number of rows above the mirror, where the rows differ in exactly `smudges` cells
+fn find_mirror(layout: &Grid<Loc>, smudges: u32) -> Option<u32> {
+  let (width, height) = (layout.width() as u32, layout.height() as u32);
+  // Find the adjacent rows that are identical
+  for r1 in 0..height - 1 {
+    let mut smudges = smudges;
+    match width - equal_count(layout, r1, r1 + 1) {
+      v if v > smudges => {
+        continue;
       }
-      let mut x1 = c1;
-      let mut x2 = c1 + 1;
-      let mut matched = true;
-      while x1 > 0 && x2 + 1 < self.width() {
-        x1 -= 1;
-        x2 += 1;
-        match self.height() - self.col_equal_count(x1, x2) {
-          v if v > smudges => {
-            matched = false;
-            break;
-          }
-          v => smudges -= v,
-        }
+      v => {
+        smudges -= v
       }
-      if matched && smudges == 0 { return Some(c1 + 1); }
     }
-    None
-  }
-
-  fn check_for_horizontal_mirror(&self, smudges: u32) -> Option<u32> {
-    // Find the adjacent rows that are identical
-    for r1 in 0..self.height() - 1 {
-      let mut smudges = smudges;
-      match self.width() - self.row_equal_count(r1, r1 + 1) {
+    let mut y1 = r1;
+    let mut y2 = r1 + 1;
+    let mut matched = true;
+    while y1 > 0 && y2 + 1 < height {
+      y1 -= 1;
+      y2 += 1;
+      match width - equal_count(layout, y1, y2) {
         v if v > smudges => {
-          continue;
-        }
-        v => {
-          smudges -= v
-        }
-      }
-      let mut y1 = r1;
-      let mut y2 = r1 + 1;
-      let mut matched = true;
-      while y1 > 0 && y2 + 1 < self.height() {
-        y1 -= 1;
-        y2 += 1;
-        match self.width() - self.row_equal_count(y1, y2) {
-          v if v > smudges => {
-            matched = false;
-            break;
-          }
-          v => smudges -= v,
+          matched = false;
+          break;
         }
+        v => smudges -= v,
       }
-      if matched && smudges == 0 { return Some(r1 + 1); }
     }
-    None
+    if matched && smudges == 0 { return Some(r1 + 1); }
   }
+  None
 }
 
-pub fn generator(input: &str) -> Vec<Pattern> {
-  input.split("\n\n")
-    .map(Pattern::from)
+pub fn generator(input: &str) -> Result<Vec<Pattern>, ParseError> {
+  blocks(input)
+    .map(|p| Pattern::from(p).map_err(|e| e.within(input, p)))
     .collect()
 }
 
@@ -129,12 +111,10 @@ pub fn part2(patterns: &[Pattern]) -> u32 {
   }).sum()
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day13::{generator, part1, part2, Pattern};
-
-  fn input() -> String {
-    "#.##..##.
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "#.##..##.
 ..#.##.#.
 ##......#
 ##......#
@@ -148,12 +128,65 @@ mod tests {
 #####.##.
 #####.##.
 ..##..###
-#....#..#".to_string()
+#....#..#",
+    part1: Some("405"),
+    part2: Some("400"),
+  },
+];
+
+/// Patterns with a mirror between rows and a mirror with a smudge between columns, or the other way
+/// round. The rows mirror leaves some rows out, and the smudge is in one of them.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  (0..size).map(|_| {
+    let (width, height) = (rng.gen_range(5..=17_usize), rng.gen_range(5..=17_usize));
+    let rows_mirror = loop {
+      let r = rng.gen_range(1..height);
+      if 2 * r.min(height - r) < height { break r; }
+    };
+    let cols_mirror = rng.gen_range(1..width);
+    let reflect = |v: usize, mirror: usize, len: usize| (2 * mirror).checked_sub(v + 1).filter(|r| *r < len);
+
+    // The cells that the mirrors reflect onto each other all have the same value
+    let mut cells: Vec<Vec<Option<char>>> = vec![vec![None; width]; height];
+    for y in 0..height {
+      for x in 0..width {
+        if cells[y][x].is_some() { continue; }
+        let c = if rng.gen_bool(0.5) { '#' } else { '.' };
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+          if cells[y][x].replace(c).is_some() { continue; }
+          stack.extend(reflect(y, rows_mirror, height).map(|r| (x, r)));
+          stack.extend(reflect(x, cols_mirror, width).map(|r| (r, y)));
+        }
+      }
+    }
+    let mut pattern: Vec<Vec<char>> = cells.into_iter().map(|row| row.into_iter().flatten().collect()).collect();
+    let y = *(0..height).filter(|y| reflect(*y, rows_mirror, height).is_none()).collect::<Vec<_>>()
+      .choose(rng).unwrap();
+    let x = *(0..width).filter(|x| reflect(*x, cols_mirror, width).is_some()).collect::<Vec<_>>()
+      .choose(rng).unwrap();
+    pattern[y][x] = if pattern[y][x] == '#' { '.' } else { '#' };
+
+    if rng.gen_bool(0.5) {
+      pattern = (0..width).map(|x| pattern.iter().map(|row| row[x]).collect()).collect();
+    }
+    pattern.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+  }).collect::<Vec<_>>().join("\n\n")
+}
+
+solution!(Day13, Vec<Pattern>, u32, u32);
+
+#[cfg(test)]
+mod tests {
+  use crate::day13::{equal_count, EXAMPLES, generator, part1, part2, Pattern};
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let patterns = generator(&input());
+    let patterns = generator(&input()).unwrap();
     assert_eq!(2, patterns.len());
   }
 
@@ -165,7 +198,7 @@ mod tests {
 ##......#
 ..#.##.#.
 ..##..##.
-#.#.##.#.");
+#.#.##.#.").unwrap();
     assert_eq!(5, pattern.check_for_vertical_mirror(0).unwrap());
     assert_eq!(None, pattern.check_for_horizontal_mirror(0));
   }
@@ -186,7 +219,7 @@ mod tests {
 #.##..#####
 .#...#.##.#
 ..##.#.###.
-...#.#.###.");
+...#.#.###.").unwrap();
     assert_eq!(None, pattern.check_for_vertical_mirror(0));
     assert_eq!(Some(11), pattern.check_for_horizontal_mirror(0));
 
@@ -196,7 +229,7 @@ mod tests {
 #####......
 .#######..#
 ######..##.
-..##.######");
+..##.######").unwrap();
     assert_eq!(Some(9), pattern.check_for_vertical_mirror(0));
     assert_eq!(None, pattern.check_for_horizontal_mirror(0));
 
@@ -216,20 +249,20 @@ mod tests {
 #####.##..#
 ...##.##..#
 ...##.#...#
-#####.##..#");
-    assert_eq!(10, pattern.row_equal_count(14, 15));
+#####.##..#").unwrap();
+    assert_eq!(10, equal_count(&pattern.layout, 14, 15));
     assert_eq!(Some(15), pattern.check_for_horizontal_mirror(1));
   }
 
   #[test]
   fn test_part1() {
-    let patterns = generator(&input());
+    let patterns = generator(&input()).unwrap();
     assert_eq!(405, part1(&patterns));
   }
 
   #[test]
   fn test_part2() {
-    let patterns = generator(&input());
+    let patterns = generator(&input()).unwrap();
     assert_eq!(400, part2(&patterns));
   }
 }
\ No newline at end of file
@@ -1,29 +1,37 @@
-use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use crate::day14::Space::{CUBE, EMPTY, ROUND};
+use rand::Rng;
+use crate::day14::Space::{Cube, Empty, Round};
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::cycle::{hashed_by, Cycle};
+use crate::utils::grid::{Cell, Grid};
+use crate::utils::random::{grid, Random};
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+pub mod reference;
+
+#[derive(Debug, Eq, Hash, PartialEq, Clone)]
 enum Space {
-  EMPTY,
-  ROUND,
-  CUBE,
+  Empty,
+  Round,
+  Cube,
 }
 
-impl Space {
-  fn from(c: char) -> Space {
+impl Cell for Space {
+  fn from_char(c: char) -> Option<Space> {
     match c {
-      '.' => EMPTY,
-      'O' => ROUND,
-      '#' => CUBE,
-      _ => panic!("Unexpected space {c}"),
+      '.' => Some(Empty),
+      'O' => Some(Round),
+      '#' => Some(Cube),
+      _ => None,
     }
   }
+}
 
+impl Space {
   fn to_char(&self) -> char {
     match self {
-      EMPTY => '.',
-      ROUND => 'O',
-      CUBE => '#',
+      Empty => '.',
+      Round => 'O',
+      Cube => '#',
     }
   }
 }
@@ -34,34 +42,36 @@ impl Display for Space {
   }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Eq, Hash, PartialEq)]
 pub struct Platform {
-  layout: Vec<Vec<Space>>,
+  layout: Grid<Space>,
 }
 
 impl Platform {
-  fn from(input: &str) -> Platform {
-    let layout = input.lines()
-      .map(|line| line.chars().map(Space::from).collect()).collect();
+  fn from(input: &str) -> Result<Platform, ParseError> {
+    let layout = Grid::parse(input, "platform", |_, c| {
+      Space::from_char(c).ok_or_else(|| format!("Unexpected space {c}"))
+    })?;
 
-    Platform {
+    Ok(Platform {
       layout,
-    }
+    })
+  }
+
+  /// Spin the platform until its layout repeats, with the north load after each number of cycles
+  fn find_cycle(&self) -> (Cycle, Vec<usize>) {
+    hashed_by(self.clone(), |p| { p.cycle(); }, Platform::rounds, |p| north_weight(&p.north_weights()))
   }
 
-  fn find_cycle(&mut self) -> (HashMap<Vec<(usize, usize)>, usize>, (usize, usize)) {
-    let mut repeats: HashMap<Vec<(usize, usize)>, usize> = HashMap::new();
-    let mut i = 0;
-    loop {
-      let north_weights = self.north_weights();
-      if repeats.contains_key(&north_weights) {
-        let prev = *repeats.get(&north_weights).unwrap();
-        return (repeats, (prev, i));
+  /// The round rocks as a bitset of the cells, which tells the layouts apart as the cubes never move
+  fn rounds(&self) -> Vec<u64> {
+    let mut rounds = vec![0; (self.width() * self.height()).div_ceil(64)];
+    for (i, (_, space)) in self.layout.iter().enumerate() {
+      if *space == Round {
+        rounds[i / 64] |= 1 << (i % 64);
       }
-      repeats.insert(north_weights, i);
-      self.cycle();
-      i += 1;
     }
+    rounds
   }
 
   fn cycle(&mut self) -> Vec<usize> {
@@ -72,11 +82,11 @@ impl Platform {
   }
 
   fn north_weights(&self) -> Vec<(usize, usize)> {
-    (0..self.width()).map(|c| {
-      self.layout.iter().enumerate()
-        .fold((0, 0), |(count, weight), (r, row)| {
-          if row[c] == ROUND {
-            (count + 1, weight + self.layout.len() - r)
+    self.layout.columns().map(|column| {
+      column.enumerate()
+        .fold((0, 0), |(count, weight), (r, space)| {
+          if space == &Round {
+            (count + 1, weight + self.height() - r)
           } else {
             (count, weight)
           }
@@ -92,18 +102,19 @@ impl Platform {
     self.tilt_north_south(true)
   }
 
+  #[allow(clippy::needless_range_loop)]
   fn tilt_north_south(&mut self, reverse: bool) -> Vec<usize> {
     let (start, change) = if reverse { (self.height() - 1, -1_i32) } else { (0, 1) };
     let mut sweep_edge: Vec<usize> = vec![start; self.width()];
 
     let mut process_row = |r: usize, p: &mut Platform| {
       for c in 0..p.width() {
-        match &p.layout[r][c] {
-          EMPTY => {}
-          CUBE => sweep_edge[c] = (r as i32 + change) as usize,
-          ROUND => {
-            p.layout[r][c] = EMPTY;
-            p.layout[sweep_edge[c]][c] = ROUND;
+        match &p.layout[(c, r)] {
+          Empty => {}
+          Cube => sweep_edge[c] = (r as i32 + change) as usize,
+          Round => {
+            p.layout[(c, r)] = Empty;
+            p.layout[(c, sweep_edge[c])] = Round;
             sweep_edge[c] = (sweep_edge[c] as i32 + change) as usize;
           }
         }
@@ -126,38 +137,39 @@ impl Platform {
     self.tilt_west_east(false)
   }
 
+  #[allow(clippy::needless_range_loop)]
   fn tilt_west_east(&mut self, reverse: bool) -> Vec<usize> {
     let (start, change) = if reverse { (self.width() - 1, -1_i32) } else { (0, 1) };
     let mut sweep_edge: Vec<usize> = vec![start; self.height()];
 
     let mut process_col = |c: usize, p: &mut Platform| {
       for r in 0..p.height() {
-        match &p.layout[r][c] {
-          EMPTY => {}
-          CUBE => sweep_edge[r] = (c as i32 + change) as usize,
-          ROUND => {
-            p.layout[r][c] = EMPTY;
-            p.layout[r][sweep_edge[r]] = ROUND;
+        match &p.layout[(c, r)] {
+          Empty => {}
+          Cube => sweep_edge[r] = (c as i32 + change) as usize,
+          Round => {
+            p.layout[(c, r)] = Empty;
+            p.layout[(sweep_edge[r], r)] = Round;
             sweep_edge[r] = (sweep_edge[r] as i32 + change) as usize;
           }
         }
       }
     };
     if reverse {
-      (0..self.height()).rev().for_each(|r| process_col(r, self))
+      (0..self.width()).rev().for_each(|c| process_col(c, self))
     } else {
-      (0..self.height()).for_each(|r| process_col(r, self))
+      (0..self.width()).for_each(|c| process_col(c, self))
     }
 
     sweep_edge
   }
 
   fn width(&self) -> usize {
-    self.layout[0].len()
+    self.layout.width()
   }
 
   fn height(&self) -> usize {
-    self.layout.len()
+    self.layout.height()
   }
 }
 
@@ -167,32 +179,24 @@ fn north_weight(north_weights: &[(usize, usize)]) -> usize {
 
 impl Display for Platform {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    let mut output = String::new();
-    for row in &self.layout {
-      for s in row {
-        output.push(s.to_char());
-      }
-      output.push('\n');
-    }
-    output.pop();
-    write!(f, "{output}")
+    write!(f, "{}", self.layout)
   }
 }
 
-pub fn generator(input: &str) -> Platform {
+pub fn generator(input: &str) -> Result<Platform, ParseError> {
   Platform::from(input)
 }
 
 pub fn part1(platform: &Platform) -> usize {
   // Tilt north
-  let mut sweep_edge: Vec<usize> = vec![platform.layout.len(); platform.width()];
+  let mut sweep_edge: Vec<usize> = vec![platform.height(); platform.width()];
   let mut score = 0_u32;
-  for (r, row) in platform.layout.iter().enumerate() {
+  for (r, row) in platform.layout.rows().enumerate() {
     for c in 0..platform.width() {
       match &row[c] {
-        EMPTY => {}
-        CUBE => sweep_edge[c] = platform.height() - 1 - r,
-        ROUND => {
+        Empty => {}
+        Cube => sweep_edge[c] = platform.height() - 1 - r,
+        Round => {
           score += sweep_edge[c] as u32;
           sweep_edge[c] -= 1;
         }
@@ -203,21 +207,19 @@ pub fn part1(platform: &Platform) -> usize {
 }
 
 pub fn part2(platform: &Platform) -> usize {
-  let (map, (start, repeat)) = platform.clone().find_cycle();
-  let final_state: usize = start + (1000000000_usize - start) % (repeat - start);
-  for (k, v) in map {
-    if v == final_state { return north_weight(&k); }
-  }
-  panic!("Could not find the matching ")
+  load_after(platform, 1000000000)
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day14::{generator, north_weight, part1, part2};
-  use crate::day14::Space::{EMPTY, ROUND};
+/// The load on the north beams after the spin cycles, skipping the repeats of the layout
+fn load_after(platform: &Platform, cycles: usize) -> usize {
+  let (cycle, loads) = platform.find_cycle();
+  loads[cycle.index(cycles)]
+}
 
-  fn input() -> String {
-    "O....#....
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "O....#....
 O.OO#....#
 .....##...
 OO.#O....O
@@ -226,19 +228,47 @@ O.#..O.#.#
 ..O..#O..O
 .......O..
 #....###..
-#OO..#....".to_string()
+#OO..#....",
+    part1: Some("136"),
+    part2: Some("64"),
+  },
+];
+
+/// A square platform of the side with round rocks on a fifth and cube rocks on a sixth of it
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  grid(size, size, |_, _| match rng.gen_range(0..30) {
+    0..=5 => 'O',
+    6..=10 => '#',
+    _ => '.',
+  })
+}
+
+solution!(Day14, Platform, usize, usize);
+
+#[cfg(test)]
+mod tests {
+  use proptest::collection::vec;
+  use proptest::prelude::*;
+  use crate::day14::{EXAMPLES, generator, load_after, north_weight, Platform, part1, part2, random_input,
+                     reference};
+  use crate::day14::Space::{Cube, Empty, Round};
+  use crate::utils::cycle::Cycle;
+  use crate::utils::differential::check;
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let p = generator(&input());
-    assert_eq!(ROUND, p.layout[0][0]);
-    assert_eq!(EMPTY, p.layout[9][9]);
+    let p = generator(&input()).unwrap();
+    assert_eq!(Round, p.layout[(0, 0)]);
+    assert_eq!(Empty, p.layout[(9, 9)]);
   }
 
   #[test]
   fn test_part1() {
-    let mut p = generator(&input());
+    let mut p = generator(&input()).unwrap();
     assert_eq!(136, part1(&p));
     p.tilt_north_south(false);
     assert_eq!(136, north_weight(&p.north_weights()));
@@ -246,7 +276,7 @@ O.#..O.#.#
 
   #[test]
   fn test_cycle() {
-    let mut p = generator(&input());
+    let mut p = generator(&input()).unwrap();
     p.tilt_north();
     assert_eq!(p.to_string(), "OOOO.#.O..
 OO..#....#
@@ -319,10 +349,10 @@ OO....OO..
 
   #[test]
   fn test_find_cycle() {
-    let mut p = generator(&input());
-    assert_eq!((3, 10), p.find_cycle().1);
+    let p = generator(&input()).unwrap();
+    assert_eq!(Cycle { start: 3, len: 7 }, p.find_cycle().0);
 
-    let mut p = generator(&input());
+    let mut p = generator(&input()).unwrap();
     for _ in 0..11 {
       p.cycle();
     }
@@ -330,7 +360,48 @@ OO....OO..
 
   #[test]
   fn test_part2() {
-    let p = generator(&input());
+    let p = generator(&input()).unwrap();
     assert_eq!(64, part2(&p));
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_reference() {
+    assert_eq!(64, reference::load_after(&generator(EXAMPLES[0].input).unwrap(), 1000));
+    for cycles in [1, 2, 100] {
+      check(random_input, generator, 10, 0..10,
+            |p| load_after(p, cycles), |p| reference::load_after(p, cycles));
+    }
+  }
+
+  /// The text of a platform of up to 11 by 11 spaces
+  fn platform() -> impl Strategy<Value = String> {
+    (1..12_usize, 1..12_usize)
+      .prop_flat_map(|(width, height)| vec(vec(prop_oneof![Just('.'), Just('O'), Just('#')], width), height))
+      .prop_map(|rows| {
+        let rows: Vec<String> = rows.into_iter().map(|row| row.into_iter().collect()).collect();
+        rows.join("\n")
+      })
+  }
+
+  proptest! {
+    #[test]
+    fn test_tilt_properties(text in platform(), dir in 0..4) {
+      let platform = generator(&text).unwrap();
+      let mut tilted = platform.clone();
+      let (dx, dy) = match dir {
+        0 => { tilted.tilt_north(); (0, -1) }
+        1 => { tilted.tilt_west(); (-1, 0) }
+        2 => { tilted.tilt_south(); (0, 1) }
+        _ => { tilted.tilt_east(); (1, 0) }
+      };
+      // The cube rocks stay put, and every round rock still on the platform has rolled as far as it can
+      let count = |p: &Platform| p.layout.iter().filter(|(_, s)| **s == Round).count();
+      prop_assert_eq!(count(&platform), count(&tilted));
+      for ((x, y), space) in platform.layout.iter() {
+        prop_assert_eq!(*space == Cube, tilted.layout[(x, y)] == Cube);
+        let next = tilted.layout.get(x as i64 + dx, y as i64 + dy);
+        prop_assert!(tilted.layout[(x, y)] != Round || next != Some(&Empty));
+      }
+    }
+  }
+}
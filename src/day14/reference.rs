@@ -0,0 +1,40 @@
+//! Spin the platform by rolling the round rocks a space at a time, without skipping any cycles
+
+use crate::day14::{Platform, Space};
+use crate::day14::Space::{Empty, Round};
+use crate::utils::geom::Dir4;
+use crate::utils::geom::Dir4::{East, North, South, West};
+use crate::utils::grid::Grid;
+
+/// The load on the north beams after the spin cycles
+pub fn load_after(platform: &Platform, cycles: usize) -> usize {
+  let mut layout = platform.layout.clone();
+  for _ in 0..cycles {
+    for dir in [North, West, South, East] {
+      tilt(&mut layout, dir);
+    }
+  }
+  layout.iter()
+    .filter(|(_, space)| **space == Round)
+    .map(|((_, y), _)| layout.height() - y)
+    .sum()
+}
+
+/// Move the round rocks a space in the direction until none of them can move
+fn tilt(layout: &mut Grid<Space>, dir: Dir4) {
+  let delta = dir.delta();
+  let mut moved = true;
+  while moved {
+    moved = false;
+    for y in 0..layout.height() {
+      for x in 0..layout.width() {
+        let (nx, ny) = (x as i64 + delta.x, y as i64 + delta.y);
+        if layout[(x, y)] == Round && layout.get(nx, ny) == Some(&Empty) {
+          layout[(x, y)] = Empty;
+          layout[(nx as usize, ny as usize)] = Round;
+          moved = true;
+        }
+      }
+    }
+  }
+}
@@ -1,9 +1,24 @@
 use std::collections::HashMap;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::random::Random;
 
-pub fn generator(input: &str) -> Vec<Vec<char>> {
-  input.lines().next().unwrap()
-    .split(',')
-    .map(|line| line.chars().collect())
+pub fn generator(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+  let line = input.lines().next().unwrap_or_default();
+  line.split(',')
+    .map(|step| {
+      // Each step is either "<label>-" or "<label>=<focal length>"
+      let valid = match step.split_once('=') {
+        Some((label, fl)) => !label.is_empty() && fl.len() == 1
+          && fl.chars().all(|c| c.is_ascii_digit()),
+        None => step.len() > 1 && step.ends_with('-'),
+      };
+      if valid {
+        Ok(step.chars().collect())
+      } else {
+        Err(ParseError::at(input, step, format!("Unexpected step '{step}'")))
+      }
+    })
     .collect()
 }
 
@@ -52,9 +67,35 @@ pub fn part2(codes: &[Vec<char>]) -> u32 {
   focus_power
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7",
+    part1: Some("1320"),
+    part2: Some("145"),
+  },
+];
+
+/// The steps of the size on a few labels, each removing a lens or adding one with its focal length
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let labels: Vec<String> = (0..size / 4 + 1)
+    .map(|_| (0..rng.gen_range(2..=6)).map(|_| char::from(b'a' + rng.gen_range(0..26))).collect())
+    .collect();
+  (0..size).map(|_| {
+    let label = &labels[rng.gen_range(0..labels.len())];
+    if rng.gen_bool(0.3) {
+      format!("{label}-")
+    } else {
+      format!("{label}={}", rng.gen_range(1..=9))
+    }
+  }).collect::<Vec<_>>().join(",")
+}
+
+solution!(Day15, Vec<Vec<char>>, u32, u32);
+
 #[cfg(test)]
 mod tests {
-  use crate::day15::{chars_to_hash, generator, part1, part2};
+  use crate::day15::{chars_to_hash, EXAMPLES, generator, part1, part2};
 
   #[test]
   fn test_ascii_codes() {
@@ -70,18 +111,18 @@ mod tests {
   }
 
   fn input() -> String {
-    "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7".to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_part1() {
-    let codes = generator(&input());
+    let codes = generator(&input()).unwrap();
     assert_eq!(1320, part1(&codes));
   }
 
   #[test]
   fn test_part2() {
-    let codes = generator(&input());
+    let codes = generator(&input()).unwrap();
     assert_eq!(145, part2(&codes));
   }
 }
@@ -1,11 +1,11 @@
 use std::fmt::{Display, Formatter};
-use std::mem::swap;
+use rand::Rng;
 use crate::day16::Space::{Empty, Mirror, Splitter};
-
-const LEFT_ENTRY: u8 = 1;
-const RIGHT_ENTRY: u8 = 2;
-const UP_ENTRY: u8 = 4;
-const DOWN_ENTRY: u8 = 8;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::geom::Dir4::{East, North, South, West};
+use crate::utils::grid::{Cell, Grid};
+use crate::utils::random::{grid, Random};
 
 #[derive(Debug, Eq, PartialEq)]
 enum Space {
@@ -14,18 +14,20 @@ enum Space {
   Splitter(i8, i8),
 }
 
-impl Space {
-  fn from(input: char) -> Space {
+impl Cell for Space {
+  fn from_char(input: char) -> Option<Space> {
     match input {
-      '.' => Empty,
-      '/' => Mirror(-1),
-      '\\' => Mirror(1),
-      '-' => Splitter(1, 0),
-      '|' => Splitter(0, 1),
-      _ => panic!("Unexpected character {input}")
+      '.' => Some(Empty),
+      '/' => Some(Mirror(-1)),
+      '\\' => Some(Mirror(1)),
+      '-' => Some(Splitter(1, 0)),
+      '|' => Some(Splitter(0, 1)),
+      _ => None,
     }
   }
+}
 
+impl Space {
   fn to_char(&self) -> char {
     match self {
       Empty => '.',
@@ -45,131 +47,114 @@ impl Display for Space {
 }
 
 
-pub fn generator(input: &str) -> Contraption {
+pub fn generator(input: &str) -> Result<Contraption, ParseError> {
   Contraption::from(input)
 }
 
 pub fn part1(contraption: &Contraption) -> u32 {
-  let start = Position { x: 0, y: 0, delta_x: 1, delta_y: 0 };
+  let start = Position { pos: Point2::new(0, 0), dir: East };
   count_energized(contraption, start)
 }
 
 fn count_energized(contraption: &Contraption, start: Position) -> u32 {
   let energized = contraption.simulate(start);
-  energized.iter().map(|row| row.iter().filter(|&v| v > &0).count() as u32)
-    .sum()
+  energized.iter().filter(|(_, v)| **v > 0).count() as u32
 }
 
 pub fn part2(contraption: &Contraption) -> u32 {
   let mut max_energized = 0;
   for x in 0..contraption.width() {
     max_energized = max_energized.max(count_energized(contraption,
-                                                      Position { x, y: 0, delta_x: 0, delta_y: 1 }));
+                                                      Position { pos: Point2::new(x, 0), dir: South }));
     max_energized = max_energized.max(count_energized(contraption,
-                                                      Position { x, y: contraption.height() - 1, delta_x: 0, delta_y: -1 }));
+                                                      Position { pos: Point2::new(x, contraption.height() - 1), dir: North }));
   }
   for y in 0..contraption.height() {
     max_energized = max_energized.max(count_energized(contraption,
-                                                      Position { x: 0, y, delta_x: 1, delta_y: 0 }));
+                                                      Position { pos: Point2::new(0, y), dir: East }));
     max_energized = max_energized.max(count_energized(contraption,
-                                                      Position { x: contraption.width() - 1, y, delta_x: -1, delta_y: 0 }));
+                                                      Position { pos: Point2::new(contraption.width() - 1, y), dir: West }));
   }
   max_energized
 }
 
 #[derive(Clone)]
 struct Position {
-  x: i32,
-  y: i32,
-  delta_x: i8,
-  delta_y: i8,
+  pos: Point2<i64>,
+  dir: Dir4,
 }
 
 impl Position {
   fn forward(&mut self) {
-    self.x += self.delta_x as i32;
-    self.y += self.delta_y as i32;
+    self.pos += self.dir.delta();
   }
 
+  /// A bit for each direction the beam can pass through a space in
   fn entry(&self) -> u8 {
-    match (self.delta_x, self.delta_y) {
-      (1, _) => LEFT_ENTRY,
-      (-1, _) => RIGHT_ENTRY,
-      (_, 1) => UP_ENTRY,
-      (_, -1) => DOWN_ENTRY,
-      _ => panic!("Unexpected direction ({}, {})", self.delta_x, self.delta_y),
-    }
+    1 << self.dir as u8
   }
 
   fn reflect(&mut self, mirror: &i8) {
-    swap(&mut self.delta_x, &mut self.delta_y);
-    self.delta_y *= mirror;
-    self.delta_x *= mirror;
+    // A / turns a horizontal beam left and a \ turns it right, and the other way round for a vertical beam
+    self.dir = match (mirror, self.dir.is_horizontal()) {
+      (-1, true) | (1, false) => self.dir.turn_left(),
+      _ => self.dir.turn_right(),
+    };
   }
 
   fn split(&mut self, x: &i8, y: &i8) -> Option<Position> {
-    if self.delta_x.abs() == *x || self.delta_y.abs() == *y {
+    let (dir, other) = match (x, y) {
+      (1, 0) => (West, East),
+      (0, 1) => (North, South),
+      _ => panic!("Unexpected state, cannot split ({x},{y})"),
+    };
+    if self.dir.is_horizontal() == dir.is_horizontal() {
       // Split aligned with direction, no change
       return None;
     }
 
-    match (x, y) {
-      (1, 0) => {
-        self.delta_y = 0;
-        self.delta_x = -1;
-        Some(Position { x: self.x, y: self.y, delta_x: 1, delta_y: 0 })
-      }
-      (0, 1) => {
-        self.delta_y = -1;
-        self.delta_x = 0;
-        Some(Position { x: self.x, y: self.y, delta_x: 0, delta_y: 1 })
-      }
-      _ => {
-        panic!("Unexpected state, cannot split ({x},{y})")
-      }
-    }
+    self.dir = dir;
+    Some(Position { pos: self.pos, dir: other })
   }
 }
 
 pub struct Contraption {
-  grid: Vec<Vec<Space>>,
+  grid: Grid<Space>,
 }
 
 impl Contraption {
-  fn from(input: &str) -> Contraption {
-    let grid = input.lines()
-      .map(|line| line.chars().map(Space::from).collect())
-      .collect();
-    Contraption { grid }
+  fn from(input: &str) -> Result<Contraption, ParseError> {
+    let grid = Grid::parse(input, "contraption", |_, c| {
+      Space::from_char(c).ok_or_else(|| format!("Unexpected character {c}"))
+    })?;
+    Ok(Contraption { grid })
   }
 
-  fn height(&self) -> i32 {
-    self.grid.len() as i32
+  fn height(&self) -> i64 {
+    self.grid.height() as i64
   }
 
-  fn width(&self) -> i32 {
-    self.grid[0].len() as i32
+  fn width(&self) -> i64 {
+    self.grid.width() as i64
   }
 
-  fn is_valid(&self, pos: &Position) -> bool {
-    pos.x >= 0 && pos.x < self.width() && pos.y >= 0 && pos.y < self.height()
-  }
-
-  fn simulate(&self, start: Position) -> Vec<Vec<u8>> {
-    let mut energized: Vec<Vec<u8>> = vec![vec![0; self.width() as usize]; self.height() as usize];
+  fn simulate(&self, start: Position) -> Grid<u8> {
+    let mut energized = Grid::filled(self.grid.width(), self.grid.height(), 0_u8);
     let mut stack: Vec<Position> = Vec::new();
     stack.push(start);
 
-    while !stack.is_empty() {
-      let mut p = stack.pop().unwrap();
+    while let Some(mut p) = stack.pop() {
 
-      if !self.is_valid(&p)
-        || energized[p.y as usize][p.x as usize] & p.entry() == p.entry() {
-        // If entry is invalid or already seen then skip processing
+      let Some(entries) = energized.get_mut(p.pos.x, p.pos.y) else {
+        // The entry is outside the contraption
+        continue;
+      };
+      if *entries & p.entry() == p.entry() {
+        // If entry is already seen then skip processing
         continue;
       }
-      energized[p.y as usize][p.x as usize] |= p.entry();
-      let space = &self.grid[p.y as usize][p.x as usize];
+      *entries |= p.entry();
+      let space = &self.grid[(p.pos.x as usize, p.pos.y as usize)];
       match space {
         Empty => {}
         Mirror(sgn) => {
@@ -190,12 +175,10 @@ impl Contraption {
   }
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day16::{generator, part1};
-
-  fn input() -> String {
-    ".|...\\....
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: ".|...\\....
 |.-.\\.....
 .....|-...
 ........|.
@@ -204,19 +187,43 @@ mod tests {
 ..../.\\\\..
 .-.-/..|..
 .|....-|.\\
-..//.|....".to_string()
+..//.|....",
+    part1: Some("46"),
+    part2: Some("51"),
+  },
+];
+
+/// A square contraption of the side where a tenth of the spaces are mirrors or splitters
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  grid(size, size, |_, _| match rng.gen_range(0..40) {
+    0 => '/',
+    1 => '\\',
+    2 => '-',
+    3 => '|',
+    _ => '.',
+  })
+}
+
+solution!(Day16, Contraption, u32, u32);
+
+#[cfg(test)]
+mod tests {
+  use crate::day16::{EXAMPLES, generator, part1};
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let c = generator(&input());
+    let c = generator(&input()).unwrap();
     assert_eq!(c.height(), 10);
     assert_eq!(c.width(), 10);
   }
 
   #[test]
   fn test_part1() {
-    let c = generator(&input());
+    let c = generator(&input()).unwrap();
     assert_eq!(46, part1(&c))
   }
 }
@@ -1,48 +1,66 @@
 use std::cmp::Ordering;
 use std::mem::swap;
+use rand::Rng;
+use crate::utils::{parse_at, solution, Example, ParseError};
+use crate::utils::geom::Dir4;
+use crate::utils::geom::Dir4::{East, North, South, West};
+use crate::utils::random::{closed_loop, Random};
+
+pub fn generator(input: &str) -> Result<DigInstructions, ParseError> {
+  let steps = to_direction_steps(input)?;
+  let color_steps = input.lines()
+    .map(|line| {
+      let (_, code) = line.rsplit_once(' ')
+        .ok_or_else(|| ParseError::at(input, line, "Expected '<direction> <steps> <color>'"))?;
+      hex_to_step(code).map_err(|e| e.within(input, code))
+    }).collect::<Result<_, _>>()?;
+  Ok(DigInstructions { steps, color_steps })
+}
 
-pub fn generator(input: &str) -> &str {
-  input
+/// The dig plan read both as plain steps and as steps encoded in the colors
+pub struct DigInstructions {
+  steps: Vec<(Dir4, u64)>,
+  color_steps: Vec<(Dir4, u64)>,
 }
 
-fn to_direction_steps(input: &str) -> Vec<(char, u64)> {
+fn to_direction_steps(input: &str) -> Result<Vec<(Dir4, u64)>, ParseError> {
   input.lines()
     .map(|line| {
       let mut splits = line.split_whitespace();
-      (splits.next().unwrap().chars().next().unwrap(), splits.next().unwrap().parse().unwrap())
+      let (Some(direction), Some(steps)) = (splits.next(), splits.next()) else {
+        return Err(ParseError::at(input, line, "Expected '<direction> <steps> <color>'"));
+      };
+      let direction = direction.parse().map_err(|e: String| ParseError::at(input, direction, e))?;
+      Ok((direction, parse_at(input, steps)?))
     }).collect()
 }
 
-pub fn part1(input: &str) -> u64 {
-  let inputs: Vec<(char, u64)> = to_direction_steps(input);
-
-  let plan = DigPlan::from(&inputs);
+pub fn part1(instructions: &DigInstructions) -> u64 {
+  let plan = DigPlan::from(&instructions.steps);
   plan.compute_area()
 }
 
-pub fn part2(input: &str) -> u64 {
-  let inputs: Vec<(char, u64)> = input.lines().map(|line| {
-    let (_, code) = line.rsplit_once(' ').unwrap();
-    hex_to_step(code)
-  }).collect();
-
-  let plan = DigPlan::from(&inputs);
+pub fn part2(instructions: &DigInstructions) -> u64 {
+  let plan = DigPlan::from(&instructions.color_steps);
   plan.compute_area()
 }
 
 
-fn hex_to_step(input: &str) -> (char, u64) {
-  let moves = u64::from_str_radix(&input[2..input.len() - 2], 16).unwrap();
-  let mut chars = input.chars();
-  chars.next_back();
-  let direction = match chars.next_back().unwrap() {
-    '0' => 'R',
-    '1' => 'D',
-    '2' => 'L',
-    '3' => 'U',
-    c => panic!("Unexpected direction {c}"),
+fn hex_to_step(input: &str) -> Result<(Dir4, u64), ParseError> {
+  let hex = input.strip_prefix("(#")
+    .and_then(|h| h.strip_suffix(')'))
+    .filter(|h| h.len() == 6 && h.is_ascii())
+    .ok_or_else(|| ParseError::at(input, input, format!("Expected a color like (#70c710) but found {input}")))?;
+  let moves = u64::from_str_radix(&hex[..5], 16)
+    .map_err(|_| ParseError::at(input, hex, format!("Invalid hex distance {}", &hex[..5])))?;
+  let direction = match &hex[5..] {
+    "0" => East,
+    "1" => South,
+    "2" => West,
+    "3" => North,
+    c => return Err(ParseError::at(input, &hex[5..], format!("Unexpected direction {c}"))),
   };
-  (direction, moves)
+  Ok((direction, moves))
 }
 
 pub struct DigPlan {
@@ -50,17 +68,18 @@ pub struct DigPlan {
 }
 
 impl DigPlan {
-  fn from(input: &[(char, u64)]) -> DigPlan {
+  fn from(input: &[(Dir4, u64)]) -> DigPlan {
     let mut x = 0;
     let mut y = 0;
     let mut min_x = 0;
     let mut min_y = 0;
     let mut edges = Vec::new();
-    for (c, m) in input {
-      let (delta_x, delta_y) = char_to_deltas(c);
-      edges.push(Edge { x1: x, y1: y, x2: x + (*m as i32 * delta_x), y2: y + (*m as i32 * delta_y) });
-      x += *m as i32 * delta_x;
-      y += *m as i32 * delta_y;
+    for (dir, m) in input {
+      let delta = dir.delta() * *m as i64;
+      let (delta_x, delta_y) = (delta.x as i32, delta.y as i32);
+      edges.push(Edge { x1: x, y1: y, x2: x + delta_x, y2: y + delta_y });
+      x += delta_x;
+      y += delta_y;
       min_x = min_x.min(x);
       min_y = min_y.min(y);
     }
@@ -182,22 +201,10 @@ impl Edge {
   }
 }
 
-fn char_to_deltas(c: &char) -> (i32, i32) {
-  match c {
-    'R' => (1, 0),
-    'L' => (-1, 0),
-    'U' => (0, -1),
-    'D' => (0, 1),
-    _ => panic!("Unexpected direction {c}"),
-  }
-}
-
-#[cfg(test)]
-mod tests {
-  use crate::day18::{DigPlan, Edge, generator, hex_to_step, part1, part2, to_direction_steps};
-
-  fn input() -> String {
-    "R 6 (#70c710)
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "R 6 (#70c710)
 D 5 (#0dc571)
 L 2 (#5713f0)
 D 2 (#d2c081)
@@ -210,12 +217,80 @@ U 2 (#caa171)
 R 2 (#7807d2)
 U 3 (#a77fa3)
 L 2 (#015232)
-U 2 (#7a21e3)".to_string()
+U 2 (#7a21e3)",
+    part1: Some("62"),
+    part2: Some("952408144115"),
+  },
+];
+
+/// A loop of trenches on a square of about the side. The colors dig the same loop with the
+/// distances between its corners stretched to up to five hex digits.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let block = rng.gen_range(4..=6);
+  let (_, tiles) = closed_loop(rng, size / block, block);
+  let dir = |i: usize| {
+    let (a, b) = (tiles[i % tiles.len()].signed(), tiles[(i + 1) % tiles.len()].signed());
+    Dir4::ALL.into_iter().find(|d| a + d.delta() == b).unwrap()
+  };
+  // Start from a corner so the first and the last steps don't go the same way
+  let first = (1..=tiles.len()).find(|i| dir(*i) != dir(i - 1)).unwrap();
+  let mut corners = vec![tiles[first % tiles.len()]];
+  let mut steps: Vec<(Dir4, usize)> = Vec::new();
+  for i in first..first + tiles.len() {
+    match steps.last_mut() {
+      Some((d, len)) if *d == dir(i) => *len += 1,
+      _ => {
+        if !steps.is_empty() {
+          corners.push(tiles[i % tiles.len()]);
+        }
+        steps.push((dir(i), 1));
+      }
+    }
+  }
+
+  // Stretch the gaps between the columns and the rows of the corners
+  let mut stretch = |values: Vec<usize>| {
+    let mut values = values;
+    values.sort();
+    values.dedup();
+    let gap = (0xfffff / values.len()).max(1);
+    let mut stretched = vec![0; values.last().map_or(0, |v| v + 1)];
+    let mut at = 0;
+    for v in values {
+      at += rng.gen_range(1..=gap);
+      stretched[v] = at;
+    }
+    stretched
+  };
+  let xs = stretch(corners.iter().map(|c| c.x).collect());
+  let ys = stretch(corners.iter().map(|c| c.y).collect());
+  steps.iter().enumerate().map(|(i, (d, len))| {
+    let (a, b) = (corners[i], corners[(i + 1) % corners.len()]);
+    let distance = xs[a.x].abs_diff(xs[b.x]) + ys[a.y].abs_diff(ys[b.y]);
+    let (name, code) = match d {
+      East => ('R', 0),
+      South => ('D', 1),
+      West => ('L', 2),
+      North => ('U', 3),
+    };
+    format!("{name} {len} (#{distance:05x}{code})")
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day18, DigInstructions, u64, u64);
+
+#[cfg(test)]
+mod tests {
+  use crate::day18::{DigPlan, Edge, EXAMPLES, generator, hex_to_step, part1, part2, to_direction_steps};
+  use crate::utils::geom::Dir4::{East, South};
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let dp = DigPlan::from(&to_direction_steps(&input()));
+    let dp = DigPlan::from(&to_direction_steps(&input()).unwrap());
     assert_eq!(dp.edges.len(), 14);
     assert_eq!(dp.horizontal_edges().len(), 7);
     assert_eq!(Edge::x_values(&dp.horizontal_edges()), vec![0, 1, 2, 4, 6]);
@@ -235,15 +310,15 @@ U 2 (#7a21e3)".to_string()
   #[test]
   fn test_part1() {
     let input = input();
-    let dp = generator(&input);
-    assert_eq!(62, part1(dp));
+    let dp = generator(&input).unwrap();
+    assert_eq!(62, part1(&dp));
   }
 
   #[test]
   fn test_color_to_moves() {
-    assert_eq!(('R', 461937), hex_to_step("(#70c710)"));
-    assert_eq!(('D', 56407), hex_to_step("(#0dc571)"));
-    assert_eq!(('R', 356671), hex_to_step("(#5713f0)"));
+    assert_eq!((East, 461937), hex_to_step("(#70c710)").unwrap());
+    assert_eq!((South, 56407), hex_to_step("(#0dc571)").unwrap());
+    assert_eq!((East, 356671), hex_to_step("(#5713f0)").unwrap());
 
     let (_, code) = "U 2 (#7a21e3)".rsplit_once(' ').unwrap();
     assert_eq!(code, "(#7a21e3)");
@@ -252,7 +327,7 @@ U 2 (#7a21e3)".to_string()
   #[test]
   fn test_part2() {
     let input = input();
-    let dp = generator(&input);
-    assert_eq!(952408144115, part2(dp));
+    let dp = generator(&input).unwrap();
+    assert_eq!(952408144115, part2(&dp));
   }
 }
\ No newline at end of file
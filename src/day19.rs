@@ -1,8 +1,13 @@
-use std::collections::HashMap;
+use rand::Rng;
 use crate::day19::Condition::{Else, GreaterThan, LessThan};
 use crate::day19::Outcome::{Accepted, Goto, Rejected};
+use crate::utils::{parse_at, solution, Example, ParseError};
+use crate::utils::intern::Interner;
+use crate::utils::interval::{Interval, IntervalBox};
+use crate::utils::parse::split_once;
+use crate::utils::random::{names, Random};
 
-pub fn generator(input: &str) -> Puzzle {
+pub fn generator(input: &str) -> Result<Puzzle, ParseError> {
   Puzzle::from(input)
 }
 
@@ -18,10 +23,7 @@ pub fn part1(puzzle: &Puzzle) -> u64 {
 
 pub fn part2(puzzle: &Puzzle) -> u64 {
   let rr = Rating {
-    x: Range { low: 1, high: 4000 },
-    m: Range { low: 1, high: 4000 },
-    a: Range { low: 1, high: 4000 },
-    s: Range { low: 1, high: 4000 },
+    categories: IntervalBox::new([Interval::new(1, 4001); 4]),
     exhausted: false,
   };
 
@@ -36,19 +38,31 @@ pub struct Puzzle {
 }
 
 impl Puzzle {
-  fn from(input: &str) -> Puzzle {
-    let (workflows, ratings) = input.split_once("\n\n").unwrap();
-    let ratings = ratings.lines().map(Rating::from).collect();
-
-    let id_map = workflows.lines().enumerate().map(|(id, line)| {
-      (&line[..line.find(|c| c == '{').unwrap()], id)
-    }).collect();
+  fn from(input: &str) -> Result<Puzzle, ParseError> {
+    let (workflows, ratings) = split_once(input, input, "\n\n",
+                                          "workflows and ratings separated by a blank line")?;
+    let ratings = ratings.lines()
+      .map(|line| Rating::from(line).map_err(|e| e.within(input, line)))
+      .collect::<Result<_, _>>()?;
+
+    // The ids follow the lines so they index the workflows
+    let mut names = Interner::new();
+    for line in workflows.lines() {
+      let name = line.find('{').map(|pos| &line[..pos])
+        .ok_or_else(|| ParseError::at(input, line, "Expected '<name>{<rules>}'"))?;
+      if names.get(name).is_some() {
+        return Err(ParseError::at(input, name, format!("Duplicate workflow {name}")));
+      }
+      names.intern(name);
+    }
     let flows = workflows.lines().map(|w| {
-      Workflow::from(w, &id_map)
-    }).collect();
+      Workflow::from(w, &names).map_err(|e| e.within(input, w))
+    }).collect::<Result<_, _>>()?;
 
-    let workflows = Workflows { start: *id_map.get("in").unwrap(), flows };
-    Puzzle { ratings, workflows }
+    let start = names.get("in")
+      .ok_or_else(|| ParseError::at(input, workflows, "Missing the 'in' workflow"))? as usize;
+    let workflows = Workflows { start, flows };
+    Ok(Puzzle { ratings, workflows })
   }
 }
 
@@ -100,13 +114,15 @@ struct Workflow {
 }
 
 impl Workflow {
-  fn from(input: &str, id_map: &HashMap<&str, usize>) -> Workflow {
-    let start = input.chars().position(|c| c == '{').unwrap();
-    let len = input.len();
-    let branches = input[start + 1..len - 1].split(',')
-      .map(|b| Branch::from(b, id_map))
-      .collect();
-    Workflow { branches }
+  fn from(input: &str, names: &Interner) -> Result<Workflow, ParseError> {
+    let start = input.find('{')
+      .ok_or_else(|| ParseError::at(input, input, "Expected '<name>{<rules>}'"))?;
+    let rules = input[start + 1..].strip_suffix('}')
+      .ok_or_else(|| ParseError::at(input, &input[start..], "Expected the rules to end with '}'"))?;
+    let branches = rules.split(',')
+      .map(|b| Branch::from(b, names).map_err(|e| e.within(input, b)))
+      .collect::<Result<_, _>>()?;
+    Ok(Workflow { branches })
   }
 
   fn take_evaluate<'a>(&'a self, rr: &mut Rating, stack: &mut Vec<(Rating, &'a Outcome)>) {
@@ -127,21 +143,21 @@ struct Branch {
 }
 
 impl Branch {
-  fn from(input: &str, id_map: &HashMap<&str, usize>) -> Branch {
+  fn from(input: &str, names: &Interner) -> Result<Branch, ParseError> {
     let cond;
     let outcome;
     match input.split_once(':') {
       Some((c, o)) => {
-        cond = Condition::from(c);
-        outcome = Outcome::from(o, id_map);
+        cond = Condition::from(c)?;
+        outcome = Outcome::from(o, names).map_err(|e| e.within(input, o))?;
       }
       None => {
         cond = Else;
-        outcome = Outcome::from(input, id_map);
+        outcome = Outcome::from(input, names)?;
       }
     }
 
-    Branch { cond, outcome }
+    Ok(Branch { cond, outcome })
   }
 
   fn take_evaluate(&self, rr: &mut Rating) -> Option<(Rating, &Outcome)> {
@@ -157,11 +173,12 @@ enum Outcome {
 }
 
 impl Outcome {
-  fn from(input: &str, id_map: &HashMap<&str, usize>) -> Outcome {
+  fn from(input: &str, names: &Interner) -> Result<Outcome, ParseError> {
     match input {
-      "A" => Accepted,
-      "R" => Rejected,
-      g => Goto(*id_map.get(g).unwrap()),
+      "A" => Ok(Accepted),
+      "R" => Ok(Rejected),
+      g => names.get(g).map(|id| Goto(id as usize))
+        .ok_or_else(|| ParseError::at(input, input, format!("Unknown workflow {g}"))),
     }
   }
 
@@ -173,163 +190,101 @@ impl Outcome {
   }
 }
 
+/// The rating categories, in the order of the dimensions of a rating
+const CATEGORIES: [&str; 4] = ["x", "m", "a", "s"];
+
+/// The dimension of the category with the name
+fn category(name: &str) -> Option<usize> {
+  CATEGORIES.iter().position(|c| *c == name)
+}
+
+/// A comparison of the category with the index against a value
+#[derive(Clone, Copy, Debug)]
 enum Condition {
-  LessThan(Box<dyn Fn(&mut Rating) -> &mut Range>, u32),
-  GreaterThan(Box<dyn Fn(&mut Rating) -> &mut Range>, u32),
+  LessThan(usize, u32),
+  GreaterThan(usize, u32),
   Else,
 }
 
 impl Condition {
-  fn from(input: &str) -> Condition {
-    match (input.chars().nth(0).unwrap(), input.chars().nth(1).unwrap()) {
-      (c, '<') => LessThan(Rating::get_fn(&c),
-                           input[2..].parse().unwrap()),
-      (c, '>') => GreaterThan(Rating::get_fn(&c),
-                              input[2..].parse().unwrap()),
-      _ => Else,
+  fn from(input: &str) -> Result<Condition, ParseError> {
+    let mut chars = input.chars();
+    let (Some(c), Some(op)) = (chars.next(), chars.next()) else {
+      return Err(ParseError::at(input, input, format!("Expected '<category><op><value>' but found {input}")));
+    };
+    let f = category(&input[..c.len_utf8()])
+      .ok_or_else(|| ParseError::at(input, input, format!("Unexpected category {c}")))?;
+    match op {
+      '<' => Ok(LessThan(f, parse_at(input, &input[2..])?)),
+      '>' => Ok(GreaterThan(f, parse_at(input, &input[2..])?)),
+      _ => Err(ParseError::at(input, &input[1..], format!("Unexpected comparison {op}"))),
     }
   }
 
+  /// Split off the part of the ratings that meets the condition, leaving the rest behind. When
+  /// nothing is left the ratings are exhausted.
   fn take_accept(&self, rr: &mut Rating) -> Option<Rating> {
-    let mut r = *rr;
-    match self {
-      LessThan(f, v) => {
-        let category = f(rr);
-        let accept_category = f(&mut r);
-        if &category.low >= v {
-          None
-        } else if &category.high < v {
-          rr.exhaust();
-          Some(r)
-        } else {
-          accept_category.high = v - 1;
-          category.low = *v;
-          Some(r)
-        }
-      }
-      GreaterThan(f, v) => {
-        let category = f(rr);
-        let accept_category = f(&mut r);
-        if &category.high <= v {
-          None
-        } else if &category.low > v {
-          rr.exhaust();
-          Some(r)
-        } else {
-          accept_category.low = v + 1;
-          category.high = *v;
-          Some(r)
-        }
-      }
-      Else => {
-        let r = Some(*rr);
-        rr.exhaust();
-        r
+    let (accepted, rest) = match self {
+      LessThan(c, v) => rr.categories.split_at(*c, *v),
+      GreaterThan(c, v) => {
+        let (below, above) = rr.categories.split_at(*c, v + 1);
+        (above, below)
       }
+      Else => (Some(rr.categories), None),
+    };
+    let accepted = accepted?;
+    match rest {
+      Some(rest) => rr.categories = rest,
+      None => rr.exhaust(),
     }
+    Some(Rating { categories: accepted, exhausted: false })
   }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
-struct Range {
-  low: u32,
-  high: u32,
-}
-
-impl Range {
-  fn create(low: u32, high: u32) -> Range {
-    Range { low, high }
-  }
-
-  fn len(&self) -> u64 {
-    (self.high - self.low + 1) as u64
-  }
-}
-
+/// The ratings in the box of the four categories
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 struct Rating {
-  x: Range,
-  m: Range,
-  a: Range,
-  s: Range,
+  categories: IntervalBox<u32, 4>,
   exhausted: bool,
 }
 
 impl Rating {
-  fn from(input: &str) -> Rating {
-    let mut x = 0;
-    let mut m = 0;
-    let mut a = 0;
-    let mut s = 0;
+  fn from(input: &str) -> Result<Rating, ParseError> {
+    let mut values = [0; 4];
     let p: &[_] = &['{', '}'];
-    input.trim_matches(p)
-      .split(',')
-      .for_each(|v| match v.split_once('=').unwrap() {
-        ("x", v) => x = v.parse().unwrap(),
-        ("m", v) => m = v.parse().unwrap(),
-        ("a", v) => a = v.parse().unwrap(),
-        ("s", v) => s = v.parse().unwrap(),
-        (r, _) => panic!("Unexpected rating {}", r),
-      });
-    Rating::create(x, m, a, s)
+    for v in input.trim_matches(p).split(',') {
+      match v.split_once('=').and_then(|(c, v)| Some((category(c)?, v))) {
+        Some((c, v)) => values[c] = parse_at(input, v)?,
+        None => return Err(ParseError::at(input, v, format!("Unexpected rating {}", v))),
+      }
+    }
+    Ok(Rating::create(values))
   }
 
-  fn create(x: u32, m: u32, a: u32, s: u32) -> Rating {
+  fn create(values: [u32; 4]) -> Rating {
     Rating {
-      x: Range::create(x, x),
-      m: Range::create(m, m),
-      a: Range::create(a, a),
-      s: Range::create(s, s),
+      categories: IntervalBox::new(values.map(|v| Interval::new(v, v + 1))),
       exhausted: false,
     }
   }
+
   fn exhaust(&mut self) {
     self.exhausted = true;
   }
 
-  fn get_mut_x(&mut self) -> &mut Range {
-    &mut self.x
-  }
-
-  fn get_mut_m(&mut self) -> &mut Range {
-    &mut self.m
-  }
-
-  fn get_mut_a(&mut self) -> &mut Range {
-    &mut self.a
-  }
-
-  fn get_mut_s(&mut self) -> &mut Range {
-    &mut self.s
-  }
-
-  fn get_fn(c: &char) -> Box<dyn Fn(&mut Rating) -> &mut Range> {
-    match c {
-      'x' => Box::from(Rating::get_mut_x),
-      'm' => Box::from(Rating::get_mut_m),
-      'a' => Box::from(Rating::get_mut_a),
-      's' => Box::from(Rating::get_mut_s),
-      _ => panic!(""),
-    }
-  }
-
   fn total(&self) -> u64 {
-    (self.x.low + self.m.low + self.a.low + self.s.low) as u64
+    self.categories.dims.iter().map(|c| c.start as u64).sum()
   }
 
   fn combinations(&self) -> u64 {
-    self.x.len() * self.m.len() * self.a.len() * self.s.len()
+    self.categories.volume()
   }
 }
 
-#[cfg(test)]
-mod tests {
-  use std::collections::HashMap;
-  use crate::day19::{Condition, generator, Outcome, part1, part2, Range, Rating, Workflow};
-  use crate::day19::Outcome::Accepted;
-
-  fn input() -> String {
-    "px{a<2006:qkq,m>2090:A,rfg}
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "px{a<2006:qkq,m>2090:A,rfg}
 pv{a>1716:R,A}
 lnx{m>1548:A,A}
 rfg{s<537:gd,x>2440:R,A}
@@ -345,21 +300,71 @@ hdj{m>838:A,pv}
 {x=1679,m=44,a=2067,s=496}
 {x=2036,m=264,a=79,s=2244}
 {x=2461,m=1339,a=466,s=291}
-{x=2127,m=1623,a=2188,s=1013}".to_string()
+{x=2127,m=1623,a=2188,s=1013}",
+    part1: Some("19114"),
+    part2: Some("167409079868000"),
+  },
+];
+
+/// Workflows of the size and as many ratings. The workflows only send parts to the ones after them,
+/// and each one after "in" is sent to by one before it.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let count = size.max(1);
+  let mut flows = vec![String::from("in")];
+  let len = count.ilog(26) as usize + 2;
+  flows.extend(names(rng, count - 1, len, "abcdefghijklmnopqrstuvwxyz").into_iter().filter(|n| n != "in"));
+  let mut targets: Vec<Vec<String>> = vec![Vec::new(); flows.len()];
+  for (i, flow) in flows.iter().enumerate().skip(1) {
+    targets[rng.gen_range(0..i)].push(flow.clone());
+  }
+  let lines: Vec<String> = flows.iter().zip(targets).map(|(flow, mut targets)| {
+    while targets.len() < 2 || rng.gen_bool(0.3) {
+      targets.push(String::from(if rng.gen_bool(0.5) { "A" } else { "R" }));
+    }
+    let last = targets.swap_remove(rng.gen_range(0..targets.len()));
+    let mut rules: Vec<String> = targets.iter().map(|target| {
+      let category = CATEGORIES[rng.gen_range(0..4)];
+      let op = if rng.gen_bool(0.5) { '<' } else { '>' };
+      format!("{category}{op}{}:{target}", rng.gen_range(1..=4000))
+    }).collect();
+    rules.push(last);
+    format!("{flow}{{{}}}", rules.join(","))
+  }).collect();
+  let ratings: Vec<String> = (0..size).map(|_| {
+    let values: Vec<String> = CATEGORIES.iter().map(|c| format!("{c}={}", rng.gen_range(1..=4000))).collect();
+    format!("{{{}}}", values.join(","))
+  }).collect();
+  format!("{}\n\n{}", lines.join("\n"), ratings.join("\n"))
+}
+
+solution!(Day19, Puzzle, u64, u64);
+
+#[cfg(test)]
+mod tests {
+  use proptest::array::uniform4;
+  use proptest::prelude::*;
+  use crate::day19::{Condition, EXAMPLES, generator, Outcome, part1, part2, Rating, Workflow};
+  use crate::day19::Condition::{Else, GreaterThan, LessThan};
+  use crate::day19::Outcome::Accepted;
+  use crate::utils::intern::Interner;
+  use crate::utils::interval::{Interval, IntervalBox};
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_rating() {
-    let r = Rating::from("{x=787,m=2655,a=1222,s=2876}");
-    assert_eq!(787, r.x.low);
-    assert_eq!(2655, r.m.low);
-    assert_eq!(1222, r.a.low);
-    assert_eq!(2876, r.s.low);
+    let r = Rating::from("{x=787,m=2655,a=1222,s=2876}").unwrap();
+    assert_eq!(787, r.categories.dims[0].start);
+    assert_eq!(2655, r.categories.dims[1].start);
+    assert_eq!(1222, r.categories.dims[2].start);
+    assert_eq!(2876, r.categories.dims[3].start);
   }
 
   #[test]
   fn test_generator() {
-    let p = generator(&input());
+    let p = generator(&input()).unwrap();
     assert_eq!(p.ratings.len(), 5);
     assert_eq!(p.workflows.flows.len(), 11);
     assert_eq!(p.workflows.start, 7);
@@ -371,22 +376,19 @@ hdj{m>838:A,pv}
 
   #[test]
   fn test_part1() {
-    let p = generator(&input());
+    let p = generator(&input()).unwrap();
     assert_eq!(19114, part1(&p));
   }
 
   #[test]
   fn test_part2() {
-    let p = generator(&input());
+    let p = generator(&input()).unwrap();
     assert_eq!(167409079868000, part2(&p));
   }
 
   fn create_range() -> Rating {
     Rating {
-      x: Range { low: 1, high: 10 },
-      m: Range { low: 1, high: 10 },
-      a: Range { low: 1, high: 10 },
-      s: Range { low: 1, high: 10 },
+      categories: IntervalBox::new([Interval::new(1, 11); 4]),
       exhausted: false,
     }
   }
@@ -394,57 +396,58 @@ hdj{m>838:A,pv}
   #[test]
   fn test_rating_ranges() {
     let mut rr = create_range();
-    let c = Condition::from("x>5");
+    let c = Condition::from("x>5").unwrap();
     let accepted = c.take_accept(&mut rr);
-    assert_eq!(accepted.unwrap().x.low, 6);
-    assert_eq!(accepted.unwrap().x.high, 10);
-    assert_eq!(rr.x.low, 1);
-    assert_eq!(rr.x.high, 5);
+    assert_eq!(accepted.unwrap().categories.dims[0].start, 6);
+    assert_eq!(accepted.unwrap().categories.dims[0].end, 11);
+    assert_eq!(rr.categories.dims[0].start, 1);
+    assert_eq!(rr.categories.dims[0].end, 6);
 
     rr = create_range();
-    let c = Condition::from("x<5");
+    let c = Condition::from("x<5").unwrap();
     let accepted = c.take_accept(&mut rr);
-    assert_eq!(accepted.unwrap().x.low, 1);
-    assert_eq!(accepted.unwrap().x.high, 4);
-    assert_eq!(rr.x.low, 5);
-    assert_eq!(rr.x.high, 10);
+    assert_eq!(accepted.unwrap().categories.dims[0].start, 1);
+    assert_eq!(accepted.unwrap().categories.dims[0].end, 5);
+    assert_eq!(rr.categories.dims[0].start, 5);
+    assert_eq!(rr.categories.dims[0].end, 11);
 
     rr = create_range();
-    let c = Condition::from("x<11");
+    let c = Condition::from("x<11").unwrap();
     let accepted = c.take_accept(&mut rr);
-    assert_eq!(accepted.unwrap().x.low, 1);
-    assert_eq!(accepted.unwrap().x.high, 10);
+    assert_eq!(accepted.unwrap().categories.dims[0].start, 1);
+    assert_eq!(accepted.unwrap().categories.dims[0].end, 11);
     assert!(rr.exhausted);
 
     rr = create_range();
-    let c = Condition::from("x>0");
+    let c = Condition::from("x>0").unwrap();
     let accepted = c.take_accept(&mut rr);
-    assert_eq!(accepted.unwrap().x.low, 1);
-    assert_eq!(accepted.unwrap().x.high, 10);
+    assert_eq!(accepted.unwrap().categories.dims[0].start, 1);
+    assert_eq!(accepted.unwrap().categories.dims[0].end, 11);
     assert!(rr.exhausted);
 
     rr = create_range();
-    let c = Condition::from("x>10");
+    let c = Condition::from("x>10").unwrap();
     let accepted = c.take_accept(&mut rr);
     assert_eq!(accepted, None);
-    assert_eq!(rr.x.low, 1);
-    assert_eq!(rr.x.high, 10);
+    assert_eq!(rr.categories.dims[0].start, 1);
+    assert_eq!(rr.categories.dims[0].end, 11);
 
     rr = create_range();
-    let c = Condition::from("x<1");
+    let c = Condition::from("x<1").unwrap();
     let accepted = c.take_accept(&mut rr);
     assert_eq!(accepted, None);
-    assert_eq!(rr.x.low, 1);
-    assert_eq!(rr.x.high, 10);
+    assert_eq!(rr.categories.dims[0].start, 1);
+    assert_eq!(rr.categories.dims[0].end, 11);
   }
 
   #[test]
   fn test_range_workflow() {
-    let id_map = HashMap::from([
-      ("px", 1), ("qkq", 2), ("rfg", 3), ("pv", 4)
-    ]);
+    let mut names = Interner::new();
+    for name in ["in", "px", "qkq", "rfg", "pv"] {
+      names.intern(name);
+    }
 
-    let w = Workflow::from("px{a<2006:qkq,m>2090:A,rfg}", &id_map);
+    let w = Workflow::from("px{a<2006:qkq,m>2090:A,rfg}", &names).unwrap();
     let mut stack: Vec<(Rating, &Outcome)> = Vec::new();
     let mut rr = create_range();
     w.take_evaluate(&mut rr, &mut stack);
@@ -453,7 +456,7 @@ hdj{m>838:A,pv}
     assert_eq!(stack[0].1.goto_id(), &2);
     assert_eq!(stack[0].0, create_range());
 
-    let w = Workflow::from("px{a<5:qkq,m>8:A,rfg}", &id_map);
+    let w = Workflow::from("px{a<5:qkq,m>8:A,rfg}", &names).unwrap();
     stack.clear();
     let mut rr = create_range();
     w.take_evaluate(&mut rr, &mut stack);
@@ -462,30 +465,64 @@ hdj{m>838:A,pv}
     assert_eq!(stack[0].1.goto_id(), &2);
     assert_eq!(stack[0].0,
                Rating {
-                 x: Range { low: 1, high: 10 },
-                 m: Range { low: 1, high: 10 },
-                 a: Range { low: 1, high: 4 },
-                 s: Range { low: 1, high: 10 },
+                 categories: IntervalBox::new([Interval::new(1, 11), Interval::new(1, 11),
+                                               Interval::new(1, 5), Interval::new(1, 11)]),
                  exhausted: false,
                });
     assert_eq!(stack[1].1, &Accepted);
     assert_eq!(stack[1].0,
                Rating {
-                 x: Range { low: 1, high: 10 },
-                 m: Range { low: 9, high: 10 },
-                 a: Range { low: 5, high: 10 },
-                 s: Range { low: 1, high: 10 },
+                 categories: IntervalBox::new([Interval::new(1, 11), Interval::new(9, 11),
+                                               Interval::new(5, 11), Interval::new(1, 11)]),
                  exhausted: false,
                });
     assert_eq!(stack[2].1.goto_id(), &3);
     assert_eq!(stack[2].0,
                Rating {
-                 x: Range { low: 1, high: 10 },
-                 m: Range { low: 1, high: 8 },
-                 a: Range { low: 5, high: 10 },
-                 s: Range { low: 1, high: 10 },
+                 categories: IntervalBox::new([Interval::new(1, 11), Interval::new(1, 9),
+                                               Interval::new(5, 11), Interval::new(1, 11)]),
                  exhausted: false,
                });
   }
-}
 
+  fn condition() -> impl Strategy<Value = Condition> {
+    prop_oneof![
+      (0..4_usize, 0..4002_u32).prop_map(|(c, v)| LessThan(c, v)),
+      (0..4_usize, 0..4002_u32).prop_map(|(c, v)| GreaterThan(c, v)),
+      Just(Else),
+    ]
+  }
+
+  proptest! {
+    #[test]
+    fn test_take_accept_properties(bounds in uniform4((1..=4000_u32, 1..=4000_u32)), condition in condition()) {
+      let dims = bounds.map(|(a, b)| Interval::new(a.min(b), a.max(b) + 1));
+      let original = Rating { categories: IntervalBox::new(dims), exhausted: false };
+      let mut rest = original;
+      let accepted = condition.take_accept(&mut rest);
+      // The accepted ratings and the rest split the ratings between them
+      let accepted_volume = accepted.map_or(0, |a| a.combinations());
+      let rest_volume = if rest.exhausted { 0 } else { rest.combinations() };
+      prop_assert_eq!(original.combinations(), accepted_volume + rest_volume);
+      if let (Some(a), false) = (accepted, rest.exhausted) {
+        prop_assert!(!a.categories.overlaps(&rest.categories));
+      }
+      let meets = |c: usize, v: u32| match condition {
+        LessThan(category, value) => c != category || v < value,
+        GreaterThan(category, value) => c != category || v > value,
+        Else => true,
+      };
+      // Only the condition's category is split, with the values that meet it accepted
+      for (c, dim) in dims.iter().enumerate() {
+        if let Some(a) = accepted {
+          let a = a.categories.dims[c];
+          prop_assert!(a == *dim || meets(c, a.start) && meets(c, a.end - 1));
+        }
+        if !rest.exhausted {
+          let r = rest.categories.dims[c];
+          prop_assert!(r == *dim || !meets(c, r.start) && !meets(c, r.end - 1));
+        }
+      }
+    }
+  }
+}
@@ -1,3 +1,9 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{parse_at, solution, Example, ParseError};
+use crate::utils::parse::split_once;
+use crate::utils::random::Random;
+
 const RED: &str = "red";
 const BLUE: &str = "blue";
 const GREEN: &str = "green";
@@ -10,26 +16,26 @@ pub struct Reveal {
 }
 
 impl Reveal {
-  fn create(line: &str) -> Reveal {
+  fn create(line: &str) -> Result<Reveal, ParseError> {
     let mut red = 0;
     let mut blue = 0;
     let mut green = 0;
 
     for v in line.split(", ") {
-      let mut splits = v.split(' ');
-      match (splits.next().unwrap().parse().unwrap(), splits.next().unwrap()) {
+      let (n, color) = split_once(line, v, " ", "'<count> <color>'")?;
+      match (parse_at(line, n)?, color) {
         (n, RED) => red = n,
         (n, BLUE) => blue = n,
         (n, GREEN) => green = n,
-        (_, c) => panic!("Unexpected color {}", c),
+        (_, c) => return Err(ParseError::at(line, c, format!("Unexpected color {c}"))),
       }
     }
 
-    Reveal {
+    Ok(Reveal {
       red,
       blue,
       green,
-    }
+    })
   }
 
   fn exceeds(&self, other: &Reveal) -> bool {
@@ -48,20 +54,17 @@ pub struct Game {
 }
 
 impl Game {
-  fn create(line: &str) -> Game {
-    let mut splits = line.split(": ");
-    let id: u32 = {
-      let mut game_splits = splits.next().unwrap().split(' ');
-      game_splits.next();
-      game_splits.next().unwrap().parse().unwrap()
-    };
-    let reveals = splits.next().unwrap().split("; ")
-      .map(Reveal::create)
-      .collect();
-    Game {
+  fn create(line: &str) -> Result<Game, ParseError> {
+    let (game, reveals) = split_once(line, line, ": ", "'Game <id>: <reveals>'")?;
+    let (_, id) = split_once(line, game, " ", "'Game <id>'")?;
+    let id = parse_at(line, id)?;
+    let reveals = reveals.split("; ")
+      .map(|r| Reveal::create(r).map_err(|e| e.within(line, r)))
+      .collect::<Result<_, _>>()?;
+    Ok(Game {
       id,
       reveals,
-    }
+    })
   }
 
   fn impossible(&self, max_reveal: &Reveal) -> bool {
@@ -77,9 +80,9 @@ impl Game {
   }
 }
 
-pub fn generator(input: &str) -> Vec<Game> {
+pub fn generator(input: &str) -> Result<Vec<Game>, ParseError> {
   input.lines()
-    .map(Game::create)
+    .map(|line| Game::create(line).map_err(|e| e.within(input, line)))
     .collect()
 }
 
@@ -101,24 +104,47 @@ pub fn part2(games: &[Game]) -> u32 {
   games.iter().map(|g| g.min_cubes().power()).sum()
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+    part1: Some("8"),
+    part2: Some("2286"),
+  },
+];
+
+/// Games with up to six reveals of up to 20 cubes of each color
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  (1..=size).map(|id| {
+    let reveals: Vec<String> = (0..rng.gen_range(1..=6)).map(|_| {
+      let mut colors = [RED, GREEN, BLUE];
+      colors.shuffle(rng);
+      colors[..rng.gen_range(1..=3)].iter()
+        .map(|color| format!("{} {color}", rng.gen_range(1..=20)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }).collect();
+    format!("Game {id}: {}", reveals.join("; "))
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day2, Vec<Game>, u32, u32);
+
 #[cfg(test)]
 mod tests {
-  use crate::day2::{generator, part1, part2};
+  use crate::day2::{EXAMPLES, generator, part1, part2};
 
   fn input() -> String {
-    [
-      "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
-      "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
-      "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
-      "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
-      "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
-    ].join("\n")
-      .to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let games = generator(&input());
+    let games = generator(&input()).unwrap();
     assert_eq!(5, games.len());
     assert_eq!(3, games[0].reveals[0].blue);
     assert_eq!(4, games[0].reveals[0].red);
@@ -128,15 +154,25 @@ mod tests {
     assert_eq!(2, games[4].reveals[1].green);
   }
 
+  #[test]
+  fn test_generator_errors() {
+    let e = generator("Game 1: 3 blue\nGame 2: 1 blue, 2 purple").err().unwrap();
+    assert_eq!((2, 19), (e.line, e.column));
+    assert_eq!("Unexpected color purple", e.message);
+
+    let e = generator("Game x: 3 blue").err().unwrap();
+    assert_eq!((1, 6), (e.line, e.column));
+  }
+
   #[test]
   fn test_part1() {
-    let games = generator(&input());
+    let games = generator(&input()).unwrap();
     assert_eq!(8, part1(&games));
   }
 
   #[test]
   fn test_part2() {
-    let games = generator(&input());
+    let games = generator(&input()).unwrap();
     assert_eq!(2286, part2(&games));
   }
 }
\ No newline at end of file
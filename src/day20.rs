@@ -1,9 +1,17 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use num::Integer;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use crate::day20::ModType::{Broadcaster, Conjunction, UnTyped, FlipFlop};
 use crate::day20::Pulse::{High, Low};
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::cycle::{hashed_by, Cycle};
+use crate::utils::intern::Interner;
+use crate::utils::random::{names, Random};
 
-pub fn generator(input: &str) -> Relays {
+pub mod reference;
+
+pub fn generator(input: &str) -> Result<Relays, ParseError> {
   Relays::from(input)
 }
 
@@ -40,15 +48,14 @@ enum ModType {
 }
 
 #[derive(Clone)]
-struct Module<'a> {
+struct Module {
   id: usize,
-  name: &'a str,
   state: u64,
   mod_type: ModType,
   dests: Vec<usize>,
 }
 
-impl Module<'_> {
+impl Module {
   fn receive(&mut self, message: Message, queue: &mut VecDeque<Message>) {
     let pulse: Option<Pulse> = match self.mod_type {
       Broadcaster => Some(message.pulse),
@@ -91,31 +98,34 @@ impl Module<'_> {
     }
   }
 
-  fn create_conjunction(id: usize, name: &str, srcs: Vec<usize>, dests: Vec<usize>) -> Module {
+  fn create_conjunction(id: usize, srcs: Vec<usize>, dests: Vec<usize>) -> Module {
     let mut expected = 0_u64;
     for src in srcs {
       assert!(src < 64);
       expected |= 1 << src;
     }
-    Module { id, name, state: 0, mod_type: Conjunction(expected), dests }
+    Module { id, state: 0, mod_type: Conjunction(expected), dests }
   }
 
-  fn create_flip_flop(id: usize, name: &str, dests: Vec<usize>) -> Module {
-    Module { id, name, dests, state: 0, mod_type: FlipFlop }
+  fn create_flip_flop(id: usize, dests: Vec<usize>) -> Module {
+    Module { id, dests, state: 0, mod_type: FlipFlop }
   }
 }
 
 #[derive(Clone)]
-pub struct Relays<'a> {
+pub struct Relays {
   broadcaster: usize,
   rx: Option<usize>,
-  modules: Vec<Module<'a>>,
+  names: Interner,
+  modules: Vec<Module>,
 }
 
-impl Relays<'_> {
-  fn make_id_map(input: &str) -> HashMap<&str, usize> {
-    let mut id_map: HashMap<&str, usize> = HashMap::new();
-    let mut id_value = 0;
+impl Relays {
+  /// The largest number of modules whose state fits in a conjunction's bitmask
+  const MAX_MODULES: usize = 64;
+
+  fn intern_names(input: &str) -> Interner {
+    let mut names = Interner::new();
 
     for line in input.lines() {
       let (src, dests) = line.split_once(" -> ").unwrap();
@@ -123,20 +133,14 @@ impl Relays<'_> {
         '%' | '&' => &src[1..],
         _ => src
       };
-      if !id_map.contains_key(name) {
-        id_map.insert(name, id_value);
-        id_value += 1;
-      }
+      names.intern(name);
 
-      // Check destination names
+      // Destinations without a line of their own are untyped modules
       for dest in dests.split(", ") {
-        if !id_map.contains_key(dest) {
-          id_map.insert(dest, id_value);
-          id_value += 1;
-        }
+        names.intern(dest);
       }
     }
-    id_map
+    names
   }
 
   fn find_srcs(input: &str) -> (HashMap<&str, Vec<&str>>, HashSet<&str>) {
@@ -159,8 +163,20 @@ impl Relays<'_> {
     (conjunction_srcs, srcs)
   }
 
-  fn from(input: &str) -> Relays {
-    let id_map = Relays::make_id_map(input);
+  fn from(input: &str) -> Result<Relays, ParseError> {
+    for line in input.lines() {
+      match line.split_once(" -> ") {
+        Some((src, _)) if !src.is_empty() => {}
+        _ => return Err(ParseError::at(input, line, "Expected '<module> -> <destinations>'")),
+      }
+    }
+    let names = Relays::intern_names(input);
+    if names.len() > Relays::MAX_MODULES {
+      return Err(ParseError::at(input, input, format!("Found {} modules but at most {} are supported",
+                                                      names.len(), Relays::MAX_MODULES)));
+    }
+    let broadcaster = names.get("broadcaster")
+      .ok_or_else(|| ParseError::at(input, input, "Missing the broadcaster module"))? as usize;
     let (mut conjunction_srcs, srcs) = Relays::find_srcs(input);
 
     // Handle conjunction sources
@@ -179,11 +195,11 @@ impl Relays<'_> {
     }
 
     let get_id = |v: &str| -> usize {
-      *id_map.get(v).unwrap()
+      names.get(v).unwrap() as usize
     };
     let get_dests = |dests: &str| -> Vec<usize> {
       dests.split(", ")
-        .map(|v| *id_map.get(v).unwrap())
+        .map(get_id)
         .collect()
     };
 
@@ -195,37 +211,36 @@ impl Relays<'_> {
         '%' => {
           let id = get_id(&src[1..]);
           let dests = get_dests(dests);
-          modules.push(Module::create_flip_flop(id, &src[1..], dests));
+          modules.push(Module::create_flip_flop(id, dests));
         }
         '&' => {
           let id = get_id(&src[1..]);
           let dests = get_dests(dests);
           let srcs = conjunction_srcs.get(&src[1..]).unwrap()
-            .iter().map(|&v| *id_map.get(v).unwrap())
+            .iter().map(|&v| get_id(v))
             .collect();
-          modules.push(Module::create_conjunction(id, &src[1..], srcs, dests));
+          modules.push(Module::create_conjunction(id, srcs, dests));
         }
         _ if src == "broadcaster" => {
           let id = get_id(src);
           let dests = get_dests(dests);
-          modules.push(Module { id, name: src, dests, state: 0, mod_type: Broadcaster });
+          modules.push(Module { id, dests, state: 0, mod_type: Broadcaster });
         }
-        _ => panic!("Unexpected value {src}"),
+        _ => return Err(ParseError::at(input, src, format!("Unexpected value {src}"))),
       }
     }
     // Handle untyped modules
-    for (name, id) in &id_map {
+    for (id, name) in names.iter() {
       if !srcs.contains(name) {
-        modules.push(Module { id: *id, name, dests: vec![], state: 0, mod_type: UnTyped });
+        modules.push(Module { id: id as usize, dests: vec![], state: 0, mod_type: UnTyped });
       }
     }
     modules.sort_by_key(|m| m.id);
-    Relays::create(*id_map.get("broadcaster").unwrap(),
-                   id_map.get("rx").copied(),
-                   modules)
+    let rx = names.get("rx").map(|id| id as usize);
+    Ok(Relays::create(broadcaster, rx, names, modules))
   }
-  fn create(broadcaster: usize, rx: Option<usize>, modules: Vec<Module>) -> Relays {
-    Relays { broadcaster, rx, modules }
+  fn create(broadcaster: usize, rx: Option<usize>, names: Interner, modules: Vec<Module>) -> Relays {
+    Relays { broadcaster, rx, names, modules }
   }
   fn push_button(&mut self, times: u32) -> (u64, u64) {
     let mut messages = VecDeque::new();
@@ -280,11 +295,11 @@ impl Relays<'_> {
     // Each of these flows should output a high for us to get the desired output
     sub_flows.iter()
       .map(|(s, e)| {
-        let (from, repeat, highs) = self.find_repeat(s, e);
-        assert_eq!(from, 0);
-        assert_eq!(highs.len(), 1);
-        assert_eq!(highs[0] + 1, repeat);
-        highs[0] as u64 + 1
+        let (cycle, highs) = self.find_repeat(*s, *e);
+        // The counter starts over right away, and its end sends a high pulse once per cycle
+        assert_eq!(cycle.start, 0);
+        assert_eq!(highs, vec![cycle.len]);
+        cycle.len as u64
       }).fold(1, |a, v| a.lcm(&v))
   }
 
@@ -295,8 +310,7 @@ impl Relays<'_> {
 
     for (idx, start) in starts.iter().enumerate() {
       stack.push(start);
-      while !stack.is_empty() {
-        let m = stack.pop().unwrap();
+      while let Some(m) = stack.pop() {
         let v = 1_u64 << *m;
         if scopes[idx] & v == 0 {
           scopes[idx] |= v;
@@ -315,8 +329,8 @@ impl Relays<'_> {
       for j in i + 1..scopes.len() {
         if scopes[i] & scopes[j] != 0 {
           panic!("Overlap detected between {} and {}",
-                 self.modules[starts[i]].name,
-                 self.modules[starts[j]].name)
+                 self.names.name(starts[i] as u32),
+                 self.names.name(starts[j] as u32))
         }
       }
     }
@@ -324,53 +338,106 @@ impl Relays<'_> {
     output
   }
 
-  fn find_repeat(&self, start: &usize, end: &usize) -> (usize, usize, Vec<usize>) {
-    let mut repeats: HashMap<Vec<u64>, usize> = HashMap::new();
-    let mut times = 0;
-    let mut modules = self.modules.clone();
-    let mut messages: VecDeque<Message> = VecDeque::new();
-    let mut high_times = Vec::new();
-
-    loop {
-      let mut high_pulses = 0_u32;
-      let message = Message { src: *start, dest: *start, pulse: Low };
-      messages.push_back(message);
-
-      while !messages.is_empty() {
-        let message = messages.pop_front().unwrap();
-        if message.src == *end && message.pulse == High {
-          high_pulses += 1;
-        }
-        modules[message.dest].receive(message, &mut messages);
+  /// Send a low pulse to the start of a flow, returning whether its end sent a high pulse
+  fn press(modules: &mut [Module], start: usize, end: usize) -> bool {
+    let mut messages = VecDeque::from([Message { src: start, dest: start, pulse: Low }]);
+    let mut high = false;
+    while let Some(message) = messages.pop_front() {
+      if message.src == end && message.pulse == High {
+        high = true;
       }
+      modules[message.dest].receive(message, &mut messages);
+    }
+    high
+  }
 
-      if high_pulses > 0 {
-        high_times.push(times);
-      }
+  /// The cycle of the module states when pressing the start of a flow, counted from the first
+  /// press, with the presses up to the end of the first cycle at which its end sent a high pulse
+  fn find_repeat(&self, start: usize, end: usize) -> (Cycle, Vec<usize>) {
+    // The modules are pressed in place and only their states are remembered
+    let mut modules = self.modules.clone();
+    let high = Relays::press(&mut modules, start, end);
+    let (cycle, sent) = hashed_by((modules, high),
+                                  |(modules, high)| *high = Relays::press(modules, start, end),
+                                  |(modules, _)| modules.iter().map(|m| m.state).collect::<Vec<u64>>(),
+                                  |(_, high)| *high);
+    let highs = sent.iter().enumerate().filter(|(_, high)| **high).map(|(i, _)| i + 1).collect();
+    (cycle, highs)
+  }
+}
 
-      let state: Vec<u64> = modules.iter().map(|m| m.state).collect();
-      match repeats.get(&state) {
-        None => {
-          repeats.insert(state, times);
-        }
-        Some(v) => {
-          return (*v, times, high_times);
-        }
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a",
+    part1: Some("32000000"),
+    part2: None,
+  },
+  Example {
+    input: "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output",
+    part1: Some("11687500"),
+    part2: None,
+  },
+];
+
+/// Like the puzzle, the broadcaster presses up to four counters that each count to a random number
+/// with flip-flops, where a conjunction resets the counter and pulses an inverter. The inverters
+/// feed the conjunction in front of rx. The counters have as many bits as the size, up to 12.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let counters = rng.gen_range(1..=4);
+  let bits = size.clamp(2, 12);
+  let mut names = names(rng, counters * (bits + 2) + 2, 2, "abcdefghijklmnopqrstuvwxyz").into_iter()
+    .filter(|n| n != "rx");
+  let last = names.next().unwrap();
+  let mut lines = vec![format!("&{last} -> rx")];
+  let mut starts = Vec::new();
+  for _ in 0..counters {
+    let flip_flops: Vec<String> = names.by_ref().take(bits).collect();
+    let (conjunction, inverter) = (names.next().unwrap(), names.next().unwrap());
+    // The number has its lowest and highest bits set
+    let target = rng.gen_range(0..1 << (bits - 1)) | 1 | 1 << (bits - 1);
+    for (i, flip_flop) in flip_flops.iter().enumerate() {
+      let mut dests: Vec<&str> = flip_flops.get(i + 1).into_iter().map(|n| n.as_str()).collect();
+      if target >> i & 1 == 1 {
+        dests.push(&conjunction);
       }
-      times += 1;
+      lines.push(format!("%{flip_flop} -> {}", dests.join(", ")));
     }
+    // Setting the bits that are off and the lowest bit resets the counter
+    let mut dests: Vec<&str> = flip_flops.iter().enumerate()
+      .filter(|(i, _)| target >> i & 1 == 0 || *i == 0)
+      .map(|(_, n)| n.as_str())
+      .collect();
+    dests.push(&inverter);
+    lines.push(format!("&{conjunction} -> {}", dests.join(", ")));
+    lines.push(format!("&{inverter} -> {last}"));
+    starts.push(flip_flops[0].clone());
   }
+  lines.push(format!("broadcaster -> {}", starts.join(", ")));
+  lines.shuffle(rng);
+  lines.join("\n")
 }
 
+solution!(Day20, Relays, u64, u64);
+
 #[cfg(test)]
 mod tests {
   use std::collections::VecDeque;
-  use crate::day20::{Conjunction, generator, Message, Module, part1, Relays};
+  use crate::day20::{Conjunction, Cycle, generator, Message, Module, part1, part2, random_input, reference, Relays};
   use crate::day20::Pulse::{High, Low};
+  use crate::utils::differential::check;
 
   #[test]
   fn test_flipflop() {
-    let mut m = Module::create_flip_flop(1, "a", vec![0, 5, 6]);
+    let mut m = Module::create_flip_flop(1, vec![0, 5, 6]);
     let mut queue = VecDeque::new();
     m.receive(Message { pulse: High, src: 2, dest: 1 }, &mut queue);
     assert_eq!(0, m.state);
@@ -383,7 +450,7 @@ mod tests {
 
   #[test]
   fn test_conjunction() {
-    let mut m = Module::create_conjunction(1, "c", vec![2, 3], vec![5, 9]);
+    let mut m = Module::create_conjunction(1, vec![2, 3], vec![5, 9]);
     assert_eq!(m.mod_type, Conjunction(12));
     assert_eq!(m.state, 0);
 
@@ -416,7 +483,7 @@ mod tests {
 %b -> c
 %c -> inv
 &inv -> a";
-    let mut relays = Relays::from(input);
+    let mut relays = Relays::from(input).unwrap();
     let (low, high) = relays.push_button(1);
     assert_eq!(8, low);
     assert_eq!(4, high);
@@ -429,11 +496,12 @@ mod tests {
 %b -> c
 %c -> inv
 &inv -> a";
-    let relays = generator(input);
+    let relays = generator(input).unwrap();
     assert_eq!(0, relays.broadcaster);
     assert_eq!(5, relays.modules.len());
     assert_eq!(0, relays.modules[0].id);
     assert_eq!(4, relays.modules[4].id);
+    assert_eq!("inv", relays.names.name(4));
   }
 
   #[test]
@@ -442,14 +510,46 @@ mod tests {
 %a -> b
 %b -> c
 %c -> inv
-&inv -> a");
+&inv -> a").unwrap();
     assert_eq!(32000000, part1(&relays));
 
     let relays = generator("broadcaster -> a
 %a -> inv, con
 &inv -> b
 %b -> con
-&con -> output");
+&con -> output").unwrap();
     assert_eq!(11687500, part1(&relays));
   }
-}
\ No newline at end of file
+
+  /// Two counters, to 3 and to 5, that rx waits for
+  const COUNTERS: &str = "broadcaster -> a0, b0
+%a0 -> a1, ac
+%a1 -> ac
+&ac -> a0, ai
+&ai -> last
+%b0 -> b1, bc
+%b1 -> b2
+%b2 -> bc
+&bc -> b0, b1, bi
+&bi -> last
+&last -> rx";
+
+  #[test]
+  fn test_part2() {
+    let relays = generator(COUNTERS).unwrap();
+    let id = |name| relays.names.get(name).unwrap() as usize;
+    let (cycle, highs) = relays.find_repeat(id("a0"), id("ai"));
+    assert_eq!(Cycle { start: 0, len: 3 }, cycle);
+    assert_eq!(vec![3], highs);
+    let (cycle, highs) = relays.find_repeat(id("b0"), id("bi"));
+    assert_eq!(Cycle { start: 0, len: 5 }, cycle);
+    assert_eq!(vec![5], highs);
+    assert_eq!(15, part2(&relays));
+    assert_eq!(15, reference::part2(&relays));
+  }
+
+  #[test]
+  fn test_reference() {
+    check(random_input, generator, 4, 0..10, part2, reference::part2);
+  }
+}
@@ -0,0 +1,21 @@
+//! Press the button until rx receives a low pulse, without looking for the counters
+
+use std::collections::VecDeque;
+use crate::day20::{Message, Relays};
+use crate::day20::Pulse::Low;
+
+pub fn part2(relays: &Relays) -> u64 {
+  let rx = relays.rx.expect("There is no rx module");
+  let mut modules = relays.modules.clone();
+  let mut presses = 0;
+  loop {
+    presses += 1;
+    let mut messages = VecDeque::from([Message { src: relays.broadcaster, dest: relays.broadcaster, pulse: Low }]);
+    while let Some(message) = messages.pop_front() {
+      if message.dest == rx && message.pulse == Low {
+        return presses;
+      }
+      modules[message.dest].receive(message, &mut messages);
+    }
+  }
+}
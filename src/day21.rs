@@ -1,10 +1,14 @@
-use std::cmp::{Ordering, Reverse};
-use std::collections::{BinaryHeap, HashMap};
+use rand::Rng;
 use crate::day21::Plot::{Garden, Rock};
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::graph::bfs;
+use crate::utils::grid::Grid;
+use crate::utils::random::{grid, Random};
 
-const NEIGHBORS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+pub mod reference;
 
-pub fn generator(input: &str) -> Puzzle {
+pub fn generator(input: &str) -> Result<Puzzle, ParseError> {
   Puzzle::from(input)
 }
 
@@ -22,85 +26,52 @@ enum Plot {
   Rock,
 }
 
-#[derive(Default, Eq, PartialEq, Debug, Clone, Hash)]
-struct XY {
-  x: i32,
-  y: i32,
-}
-
-impl XY {
-  fn create(x: i32, y: i32) -> XY {
-    XY { x, y }
-  }
-}
-
 pub struct Puzzle {
-  start: XY,
-  map: Vec<Vec<Plot>>,
+  start: Point2<i64>,
+  map: Grid<Plot>,
 }
 
 impl Puzzle {
   fn height(&self) -> u32 {
-    self.map.len() as u32
+    self.map.height() as u32
   }
 
   fn width(&self) -> u32 {
-    self.map[0].len() as u32
-  }
-
-  fn from(input: &str) -> Puzzle {
-    let mut start = XY::default();
-
-    let map = input.lines().enumerate()
-      .map(|(y, line)| {
-        line.chars().enumerate().map(|(x, c)| {
-          match c {
-            '#' => Rock,
-            '.' => Garden,
-            'S' => {
-              start.x = x as i32;
-              start.y = y as i32;
-              Garden
-            }
-            _ => panic!("Unexpected plot {c}")
-          }
-        }).collect()
-      }).collect();
-    let p = Puzzle { start, map };
-    assert_eq!(p.height(), p.width());
-    p
+    self.map.width() as u32
   }
 
-  fn walk(&self, steps: u32, start: &XY, limit: bool) -> Vec<u32> {
-    let mut visited: HashMap<XY, u32> = HashMap::new();
-    let mut heap: BinaryHeap<Reverse<Entry>> = BinaryHeap::new();
-    heap.push(Reverse(Entry::from(start.x, start.y, 0)));
-
-    while !heap.is_empty() {
-      let entry = heap.pop().unwrap().0;
-      if entry.steps > steps || visited.contains_key(&entry.xy) { continue; }
-      visited.insert(entry.xy.clone(), entry.steps);
-
-      for (dx, dy) in &NEIGHBORS {
-        let x = entry.xy.x + dx;
-        let y = entry.xy.y + dy;
-        if limit
-          && (x < 0 || x as u32 == self.width()
-          || y < 0 || y as u32 == self.height()) {
-          continue;
-        }
+  fn from(input: &str) -> Result<Puzzle, ParseError> {
+    let mut start: Option<Point2<i64>> = None;
 
-        let y_pos = y.rem_euclid(self.height() as i32) as usize;
-        let x_pos = x.rem_euclid(self.width() as i32) as usize;
-        if self.map[y_pos][x_pos] == Rock {
-          continue;
+    let map = Grid::parse(input, "garden", |(x, y), c| {
+      match c {
+        '#' => Ok(Rock),
+        '.' => Ok(Garden),
+        'S' => {
+          start = Some(Point2::new(x as i64, y as i64));
+          Ok(Garden)
         }
-
-        heap.push(Reverse(Entry::from(x, y, entry.steps + 1)));
+        _ => Err(format!("Unexpected plot {c}")),
       }
+    })?;
+    let start = start.ok_or_else(|| ParseError::at(input, input, "Could not determine start"))?;
+    let p = Puzzle { start, map };
+    if p.height() != p.width() {
+      return Err(ParseError::at(input, input, format!("Expected a square garden but found {}x{}",
+                                                      p.width(), p.height())));
     }
+    Ok(p)
+  }
 
-    visited.values().copied().collect()
+  fn walk(&self, steps: u32, start: &Point2<i64>, limit: bool) -> Vec<u32> {
+    let visited = bfs(*start, steps, |xy| {
+      // Without the limit the garden repeats in every direction
+      Dir4::ALL.map(|dir| *xy + dir.delta())
+        .into_iter()
+        .filter(|next| !limit || self.map.contains(next.x, next.y))
+        .filter(|next| self.map.get_wrapping(next.x, next.y) != &Rock)
+    });
+    visited.into_values().collect()
   }
 
   fn walk_positions(&self, steps: u32) -> ((u32, u32), (u32, u32)) {
@@ -122,13 +93,13 @@ impl Puzzle {
     ((start_pos, max_start_steps), (next_pos, max_next_steps))
   }
 
-  fn walk_to_end_positions(&self, steps: u32, start: &XY, limit: bool) -> u64 {
+  fn walk_to_end_positions(&self, steps: u32, start: &Point2<i64>, limit: bool) -> u64 {
     let req_state = steps % 2;
     let positions = self.walk(steps, start, limit);
     positions.iter().filter(|&v| v % 2 == req_state).count() as u64
   }
 
-  fn compute_positions(&self, steps: u32, start: &XY) -> u64 {
+  fn compute_positions(&self, steps: u32, start: &Point2<i64>) -> u64 {
     let positions = self.walk_to_end_positions(steps,
                                                start,
                                                true);
@@ -141,14 +112,14 @@ impl Puzzle {
 
   fn compute_ends(&self, steps: u32) -> u64 {
     let mut positions: u64 = self.compute_positions(steps,
-                                                    &XY::create(self.start.x,
-                                                                (self.height() - 1) as i32));
+                                                    &Point2::new(self.start.x,
+                                                                 (self.height() - 1) as i64));
     positions += self.compute_positions(steps,
-                                        &XY { x: self.start.x, y: 0 });
+                                        &Point2::new(self.start.x, 0));
     positions += self.compute_positions(steps,
-                                        &XY { x: (self.width() - 1) as i32, y: self.start.y });
+                                        &Point2::new((self.width() - 1) as i64, self.start.y));
     positions += self.compute_positions(steps,
-                                        &XY { x: 0, y: self.start.y }, );
+                                        &Point2::new(0, self.start.y), );
     positions
   }
 
@@ -167,7 +138,7 @@ impl Puzzle {
     assert!(next_max <= self.height());
     let count = steps / self.height();
     if count > 1 {
-      if count % 2 == 0 {
+      if count.is_multiple_of(2) {
         total_positions += (count - 1) as u64 * (count - 1) as u64 * start_pos as u64;
         total_positions += count as u64 * count as u64 * next_pos as u64;
       } else {
@@ -183,32 +154,32 @@ impl Puzzle {
     if remainder >= (self.start.x + 1) as u32 {
       let remaining_side_reminder = remainder - self.start.x as u32 - 1;
       total_positions += self.compute_positions(remaining_side_reminder,
-                                                &XY::create((self.width() - 1) as i32,
-                                                            (self.height() - 1) as i32));
+                                                &Point2::new((self.width() - 1) as i64,
+                                                             (self.height() - 1) as i64));
       total_positions += self.compute_positions(remaining_side_reminder,
-                                                &XY::create(0,
-                                                            (self.height() - 1) as i32));
+                                                &Point2::new(0,
+                                                             (self.height() - 1) as i64));
       total_positions += self.compute_positions(remaining_side_reminder,
-                                                &XY::create((self.width() - 1) as i32,
-                                                            0));
+                                                &Point2::new((self.width() - 1) as i64,
+                                                             0));
       total_positions += self.compute_positions(remaining_side_reminder,
-                                                &XY::create(0,
-                                                            0));
+                                                &Point2::new(0,
+                                                             0));
     }
 
     if count > 0 {
       total_positions += (count - 1) as u64 * self.compute_positions(side_remainder,
-                                                                     &XY::create((self.width() - 1) as i32,
-                                                                                 (self.height() - 1) as i32));
+                                                                     &Point2::new((self.width() - 1) as i64,
+                                                                                  (self.height() - 1) as i64));
       total_positions += (count - 1) as u64 * self.compute_positions(side_remainder,
-                                                                     &XY::create(0,
-                                                                                 (self.height() - 1) as i32));
+                                                                     &Point2::new(0,
+                                                                                  (self.height() - 1) as i64));
       total_positions += (count - 1) as u64 * self.compute_positions(side_remainder,
-                                                                     &XY::create((self.width() - 1) as i32,
-                                                                                 0));
+                                                                     &Point2::new((self.width() - 1) as i64,
+                                                                                  0));
       total_positions += (count - 1) as u64 * self.compute_positions(side_remainder,
-                                                                     &XY::create(0,
-                                                                                 0));
+                                                                     &Point2::new(0,
+                                                                                  0));
     }
 
 
@@ -216,40 +187,33 @@ impl Puzzle {
   }
 }
 
-struct Entry {
-  xy: XY,
-  steps: u32,
-}
-
-impl Entry {
-  fn from(x: i32, y: i32, steps: u32) -> Entry {
-    Entry { xy: XY { x, y }, steps }
-  }
-}
-
-impl Eq for Entry {}
-
-impl Ord for Entry {
-  fn cmp(&self, other: &Self) -> Ordering {
-    self.steps.cmp(&other.steps)
-  }
+/// The examples from the puzzle, whose answers are for fewer steps than the parts take
+pub const EXAMPLES: &[Example] = &[];
+
+/// A square garden with an odd side of about the size and the start in the middle. Like the puzzle
+/// the middle row and column, the edges and a diamond through the middles of the edges are clear.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let half = (size / 2).max(3);
+  let side = 2 * half + 1;
+  grid(side, side, |x, y| {
+    let (dx, dy) = (x.abs_diff(half), y.abs_diff(half));
+    if dx == 0 && dy == 0 {
+      'S'
+    } else if dx == 0 || dy == 0 || dx == half || dy == half || (dx + dy).abs_diff(half) <= 1 || rng.gen_bool(0.85) {
+      '.'
+    } else {
+      '#'
+    }
+  })
 }
 
-impl PartialEq<Self> for Entry {
-  fn eq(&self, other: &Self) -> bool {
-    self.steps == other.steps
-  }
-}
-
-impl PartialOrd for Entry {
-  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-    Some(self.cmp(other))
-  }
-}
+solution!(Day21, Puzzle, u64, u64);
 
 #[cfg(test)]
 mod tests {
-  use crate::day21::{generator, XY};
+  use crate::day21::{generator, part1, random_input, reference};
+  use crate::utils::differential::check;
+  use crate::utils::geom::Point2;
 
   const UNOBSTRUCTED_INPUT: &str = "...........
 ......##.#.
@@ -276,21 +240,21 @@ mod tests {
 
   #[test]
   fn test_generator() {
-    let puzzle = generator(INPUT);
-    assert_eq!(puzzle.start, XY { x: 5, y: 5 });
+    let puzzle = generator(INPUT).unwrap();
+    assert_eq!(puzzle.start, Point2::new(5, 5));
     assert_eq!(puzzle.width(), 11);
     assert_eq!(puzzle.height(), 11);
   }
 
   #[test]
   fn test_part1() {
-    let puzzle = generator(INPUT);
+    let puzzle = generator(INPUT).unwrap();
     assert_eq!(16, puzzle.walk_to_end_positions(6, &puzzle.start, false));
   }
 
   #[test]
   fn test_part2() {
-    let puzzle = generator(INPUT);
+    let puzzle = generator(INPUT).unwrap();
     assert_eq!(50, puzzle.walk_to_end_positions(10, &puzzle.start, false));
     assert_eq!(1594, puzzle.walk_to_end_positions(50, &puzzle.start, false));
     assert_eq!(6536, puzzle.walk_to_end_positions(100, &puzzle.start, false));
@@ -301,7 +265,7 @@ mod tests {
 
   #[test]
   fn test_part2_unobstructed() {
-    let puzzle = generator(UNOBSTRUCTED_INPUT);
+    let puzzle = generator(UNOBSTRUCTED_INPUT).unwrap();
     assert_eq!(90, puzzle.walk_optimized(10));
     assert_eq!(192, puzzle.walk_optimized(15));
     assert_eq!(1501, puzzle.walk_optimized(44));
@@ -316,7 +280,7 @@ mod tests {
 
   #[test]
   fn test_compute_ends() {
-    let p = generator(UNOBSTRUCTED_INPUT);
+    let p = generator(UNOBSTRUCTED_INPUT).unwrap();
     assert_eq!(162, p.compute_ends(11));
 
     let ((start_pos, start_max), (next_pos, next_max)) = p.walk_positions(50);
@@ -325,4 +289,14 @@ mod tests {
     assert_eq!(10, start_max);
     assert_eq!(9, next_max);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_reference() {
+    let puzzle = generator(UNOBSTRUCTED_INPUT).unwrap();
+    assert_eq!(1580, reference::reachable(&puzzle, 45));
+    check(random_input, generator, 10, 0..3, part1, |p| reference::reachable(p, 64));
+    for steps in [3, 11, 16, 27, 38, 50] {
+      check(random_input, generator, 10, 0..5, |p| p.walk_optimized(steps), |p| reference::reachable(p, steps));
+    }
+  }
+}
@@ -0,0 +1,18 @@
+//! Step every reachable plot of the endless garden at once, without using its repeats
+
+use std::collections::HashSet;
+use crate::day21::Plot::Rock;
+use crate::day21::Puzzle;
+use crate::utils::geom::Dir4;
+
+/// The plots the elf can be on after exactly the steps
+pub fn reachable(puzzle: &Puzzle, steps: u32) -> u64 {
+  let mut plots = HashSet::from([puzzle.start]);
+  for _ in 0..steps {
+    plots = plots.iter()
+      .flat_map(|plot| Dir4::ALL.map(|dir| *plot + dir.delta()))
+      .filter(|next| puzzle.map.get_wrapping(next.x, next.y) != &Rock)
+      .collect();
+  }
+  plots.len() as u64
+}
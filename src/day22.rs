@@ -1,4 +1,13 @@
-pub fn generator(input: &str) -> Snapshot {
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::Point3;
+use crate::utils::grid::Grid;
+use crate::utils::interval::{Interval, IntervalBox};
+use crate::utils::parse::{array, split_once};
+use crate::utils::random::Random;
+
+pub fn generator(input: &str) -> Result<Snapshot, ParseError> {
   Snapshot::from(input)
 }
 
@@ -13,69 +22,53 @@ pub fn part2(snapshot: &Snapshot) -> u32 {
 
 #[derive(Debug, Clone)]
 struct Brick {
-  x1: u32,
-  x2: u32,
-  y1: u32,
-  y2: u32,
-  z1: u32,
-  z2: u32,
+  min: Point3<u32>,
+  max: Point3<u32>,
 }
 
 impl Brick {
-  fn from(input: &str) -> Brick {
-    let (coord1, coord2) = input.split_once('~').unwrap();
-    let splits1: Vec<u32> = coord1.split(',').map(|v| v.parse().unwrap())
-      .collect();
-    let splits2: Vec<u32> = coord2.split(',').map(|v| v.parse().unwrap())
-      .collect();
-    let x1 = splits1[0].min(splits2[0]);
-    let x2 = splits1[0].max(splits2[0]);
-    let y1 = splits1[1].min(splits2[1]);
-    let y2 = splits1[1].max(splits2[1]);
-    let z1 = splits1[2].min(splits2[2]);
-    let z2 = splits1[2].max(splits2[2]);
-
-    let mut equals = 0;
-    if x1 == x2 { equals += 1; }
-    if y1 == y2 { equals += 1; }
-    if z1 == z2 { equals += 1; }
-    assert!(equals > 1);
-
-    Brick {
-      x1,
-      x2,
-      y1,
-      y2,
-      z1,
-      z2,
+  fn from(input: &str) -> Result<Brick, ParseError> {
+    let (coord1, coord2) = split_once(input, input, "~", "'<x>,<y>,<z>~<x>,<y>,<z>'")?;
+    let p1: Point3<u32> = array(input, coord1, ",")?.into();
+    let p2: Point3<u32> = array(input, coord2, ",")?.into();
+    let min = Point3::new(p1.x.min(p2.x), p1.y.min(p2.y), p1.z.min(p2.z));
+    let max = Point3::new(p1.x.max(p2.x), p1.y.max(p2.y), p1.z.max(p2.z));
+
+    let equals = [min.x == max.x, min.y == max.y, min.z == max.z].iter().filter(|e| **e).count();
+    if equals < 2 {
+      return Err(ParseError::at(input, input, "Expected a brick that extends in at most one direction"));
     }
+
+    Ok(Brick { min, max })
   }
 
-  fn fall(&mut self, id: usize, xy: &mut [Vec<u32>], brick_xy: &mut [Vec<i32>], rests_on: &mut [Vec<u32>]) -> bool {
-    let mut fall_to = 0;
-    let mut fell = false;
-    for y in self.y1..=self.y2 {
-      for x in self.x1..=self.x2 {
-        fall_to = fall_to.max(xy[y as usize][x as usize]);
-      }
-    }
-    let diff = self.z2 - self.z1;
-    if self.z1 != fall_to + 1 {
-      fell = true;
-    }
-    self.z1 = fall_to + 1;
-    self.z2 = self.z1 + diff;
-    for y in self.y1..=self.y2 {
-      for x in self.x1..=self.x2 {
-        if xy[y as usize][x as usize] == fall_to && brick_xy[y as usize][x as usize] > -1 {
-          rests_on[id].push(brick_xy[y as usize][x as usize] as u32);
-        }
-        brick_xy[y as usize][x as usize] = id as i32;
-        xy[y as usize][x as usize] = self.z2;
-      }
+  /// The cells the brick covers when seen from above
+  fn footprint(&self) -> IntervalBox<u32, 2> {
+    IntervalBox::new([Interval::new(self.min.x, self.max.x + 1), Interval::new(self.min.y, self.max.y + 1)])
+  }
+
+  /// The (x, y) cells of the footprint
+  fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+    let [xs, ys] = self.footprint().dims;
+    ys.range().flat_map(move |y| xs.range().map(move |x| (x as usize, y as usize)))
+  }
+
+  /// Drop the brick onto the settled bricks below it, given by the top brick of each cell, recording
+  /// the ones it rests on and becoming the top of its cells
+  fn fall(&mut self, id: u32, settled: &[Brick], tops: &mut Grid<Option<u32>>, rests_on: &mut Vec<u32>) -> bool {
+    let below: Vec<u32> = self.cells().filter_map(|cell| tops[cell]).collect();
+    let fall_to = below.iter().map(|b| settled[*b as usize].max.z).max().unwrap_or(0);
+    rests_on.extend(below.iter().filter(|b| settled[**b as usize].max.z == fall_to));
+    rests_on.sort();
+    rests_on.dedup();
+
+    let diff = self.max.z - self.min.z;
+    let fell = self.min.z != fall_to + 1;
+    self.min.z = fall_to + 1;
+    self.max.z = self.min.z + diff;
+    for cell in self.cells() {
+      tops[cell] = Some(id);
     }
-    rests_on[id].sort();
-    rests_on[id].dedup();
     fell
   }
 }
@@ -86,26 +79,24 @@ pub struct Snapshot {
 }
 
 impl Snapshot {
-  fn from(input: &str) -> Snapshot {
+  fn from(input: &str) -> Result<Snapshot, ParseError> {
     let mut bricks: Vec<Brick> = input.lines()
-      .map(Brick::from)
-      .collect();
+      .map(|line| Brick::from(line).map_err(|e| e.within(input, line)))
+      .collect::<Result<_, _>>()?;
 
     let rests_on = Snapshot::fall(&mut bricks).0;
-    Snapshot { bricks, rests_on }
+    Ok(Snapshot { bricks, rests_on })
   }
 
-  fn fall(bricks: &mut Vec<Brick>) -> (Vec<Vec<u32>>, u32) {
-    bricks.sort_by_key(|b| b.z1);
-    let (max_x, max_y) = bricks.iter().fold((0, 0), |a, v| {
-      (a.0.max(v.x2), a.1.max(v.y2))
-    });
-    let mut xy = vec![vec![0_u32; (max_x + 1) as usize]; (max_y + 1) as usize];
-    let mut brick_xy = vec![vec![-1; (max_x + 1) as usize]; (max_y + 1) as usize];
+  fn fall(bricks: &mut [Brick]) -> (Vec<Vec<u32>>, u32) {
+    bricks.sort_by_key(|b| b.min.z);
+    let (width, height) = bricks.iter().fold((0, 0), |a, b| (a.0.max(b.max.x + 1), a.1.max(b.max.y + 1)));
+    let mut tops = Grid::filled(width as usize, height as usize, None);
     let mut rests_on = vec![vec![]; bricks.len()];
     let mut fallen_bricks = 0;
-    for (id, brick) in bricks.iter_mut().enumerate() {
-      if brick.fall(id, &mut xy, &mut brick_xy, &mut rests_on) {
+    for (id, rests_on) in rests_on.iter_mut().enumerate() {
+      let (settled, rest) = bricks.split_at_mut(id);
+      if rest[0].fall(id as u32, settled, &mut tops, rests_on) {
         fallen_bricks += 1;
       }
     }
@@ -139,26 +130,59 @@ impl Snapshot {
   }
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day22::{generator, part1, part2};
-
-  const INPUT: &str = "1,0,1~1,2,1
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "1,0,1~1,2,1
 0,0,2~2,0,2
 0,2,3~2,2,3
 0,0,4~0,2,4
 2,0,5~2,2,5
 0,1,6~2,1,6
-1,1,8~1,1,9";
+1,1,8~1,1,9",
+    part1: Some("5"),
+    part2: Some("7"),
+  },
+];
+
+/// Bricks of up to four cubes over a ten by ten area, stacked in the air without overlapping
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let mut tops = [[0_u32; 10]; 10];
+  let mut bricks: Vec<String> = (0..size).map(|_| {
+    let len = rng.gen_range(0..4);
+    let (dx, dy, dz) = match rng.gen_range(0..3) {
+      0 => (len, 0, 0),
+      1 => (0, len, 0),
+      _ => (0, 0, len),
+    };
+    let (x, y) = (rng.gen_range(0..10 - dx), rng.gen_range(0..10 - dy));
+    let top = (x..=x + dx).flat_map(|x| tops[x][y..=y + dy].iter()).max().copied().unwrap();
+    let z = top + 1 + rng.gen_range(0..3);
+    for column in &mut tops[x..=x + dx] {
+      column[y..=y + dy].fill(z + dz as u32);
+    }
+    format!("{x},{y},{z}~{},{},{}", x + dx, y + dy, z + dz as u32)
+  }).collect();
+  bricks.shuffle(rng);
+  bricks.join("\n")
+}
+
+solution!(Day22, Snapshot, u32, u32);
+
+#[cfg(test)]
+mod tests {
+  use crate::day22::{EXAMPLES, generator, part1, part2};
+
+  const INPUT: &str = EXAMPLES[0].input;
 
   #[test]
   fn test_generator() {
-    let s = generator(INPUT);
+    let s = generator(INPUT).unwrap();
     assert_eq!(7, s.bricks.len());
-    assert_eq!(1, s.bricks[0].z1);
-    assert_eq!(1, s.bricks[0].z2);
-    assert_eq!(5, s.bricks[6].z1);
-    assert_eq!(6, s.bricks[6].z2);
+    assert_eq!(1, s.bricks[0].min.z);
+    assert_eq!(1, s.bricks[0].max.z);
+    assert_eq!(5, s.bricks[6].min.z);
+    assert_eq!(6, s.bricks[6].max.z);
     assert!(s.rests_on[0].is_empty());
     assert_eq!(s.rests_on[1], vec![0]);
     assert_eq!(s.rests_on[2], vec![0]);
@@ -170,13 +194,13 @@ mod tests {
 
   #[test]
   fn test_part1() {
-    let s = generator(INPUT);
+    let s = generator(INPUT).unwrap();
     assert_eq!(5, part1(&s));
   }
 
   #[test]
   fn test_part2() {
-    let s = generator(INPUT);
+    let s = generator(INPUT).unwrap();
     assert_eq!(6, s.disintegrate(0));
     assert_eq!(1, s.disintegrate(5));
     assert_eq!(7, part2(&s));
@@ -1,9 +1,14 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
+use rand::Rng;
 use crate::day23::Block::{Forest, Path, Slope};
-
-const NEIGHBORS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-
-pub fn generator(input: &str) -> TrailMap {
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::geom::Dir4::{East, North, South, West};
+use crate::utils::graph::Graph;
+use crate::utils::grid::{Cell, Grid};
+use crate::utils::random::Random;
+
+pub fn generator(input: &str) -> Result<TrailMap, ParseError> {
   TrailMap::from(input)
 }
 
@@ -13,40 +18,35 @@ pub fn part1(map: &TrailMap) -> u32 {
 
 pub fn part2(map: &TrailMap) -> u32 {
   let g = map.make_graph();
-  g.max_distance(&map.start, &map.end)
+  let (Some(start), Some(end)) = (g.id(&map.start), g.id(&map.end)) else { return 0 };
+  g.longest_path(start, end).unwrap_or(0)
 }
 
 #[derive(Debug, Eq, PartialEq)]
 enum Block {
   Path,
   Forest,
-  Slope(i32, i32),
+  Slope(Dir4),
 }
 
-impl Block {
-  fn from(input: char) -> Block {
+impl Cell for Block {
+  fn from_char(input: char) -> Option<Block> {
     match input {
-      '.' => Path,
-      '#' => Forest,
-      '^' => Slope(0, -1),
-      'v' => Slope(0, 1),
-      '<' => Slope(-1, 0),
-      '>' => Slope(1, 0),
-      _ => panic!("Unexpected input {input}"),
+      '.' => Some(Path),
+      '#' => Some(Forest),
+      '^' => Some(Slope(North)),
+      'v' => Some(Slope(South)),
+      '<' => Some(Slope(West)),
+      '>' => Some(Slope(East)),
+      _ => None,
     }
   }
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
-struct XY {
-  x: i32,
-  y: i32,
-}
-
 #[derive(Clone)]
 struct Hike {
-  pos: XY,
-  visited: HashSet<XY>,
+  pos: Point2<i64>,
+  visited: HashSet<Point2<i64>>,
 }
 
 impl Hike {
@@ -54,17 +54,14 @@ impl Hike {
     if self.visited.contains(&self.pos) {
       false
     } else {
-      self.visited.insert(self.pos.clone());
+      self.visited.insert(self.pos);
       true
     }
   }
 
-  fn move_by(&mut self, dx: &i32, dy: &i32, tm: &TrailMap) -> bool {
-    self.pos.x += *dx;
-    self.pos.y += *dy;
-    if self.pos.x < 0 || self.pos.x >= tm.width() as i32
-      || self.pos.y < 0 || self.pos.y >= tm.height() as i32
-      || tm.map[self.pos.y as usize][self.pos.x as usize] == Forest {
+  fn move_by(&mut self, delta: Point2<i64>, tm: &TrailMap) -> bool {
+    self.pos += delta;
+    if tm.is_blocked(&self.pos) {
       return false;
     }
 
@@ -73,9 +70,9 @@ impl Hike {
       return mark;
     }
 
-    match &tm.map[self.pos.y as usize][self.pos.x as usize] {
-      Slope(dx, dy) => {
-        self.move_by(dx, dy, tm)
+    match &tm.map[(self.pos.x as usize, self.pos.y as usize)] {
+      Slope(dir) => {
+        self.move_by(dir.delta(), tm)
       }
       _ => {
         true
@@ -89,44 +86,39 @@ impl Hike {
 }
 
 pub struct TrailMap {
-  map: Vec<Vec<Block>>,
-  start: XY,
-  end: XY,
+  map: Grid<Block>,
+  start: Point2<i64>,
+  end: Point2<i64>,
 }
 
 impl TrailMap {
-  fn from(input: &str) -> TrailMap {
-    let map: Vec<Vec<Block>> = input.lines()
-      .map(|l| l.chars().map(Block::from).collect())
-      .collect();
-    let start = XY { x: map[0].iter().position(|b| b == &Path).unwrap() as i32, y: 0 };
-    let end = XY {
-      x: map[map.len() - 1].iter().position(|b| b == &Path).unwrap() as i32,
-      y: (map.len() - 1) as i32,
-    };
-    TrailMap { map, start, end }
+  fn from(input: &str) -> Result<TrailMap, ParseError> {
+    let map = Grid::parse(input, "trail map", |_, c| {
+      Block::from_char(c).ok_or_else(|| format!("Unexpected input {c}"))
+    })?;
+    let start = map.row(0).iter().position(|b| b == &Path)
+      .ok_or_else(|| ParseError::new(1, 1, "Expected a path in the first row"))?;
+    let start = Point2::new(start as i64, 0);
+    let end = map.row(map.height() - 1).iter().position(|b| b == &Path)
+      .ok_or_else(|| ParseError::new(map.height(), 1, "Expected a path in the last row"))?;
+    let end = Point2::new(end as i64, (map.height() - 1) as i64);
+    Ok(TrailMap { map, start, end })
   }
 
-  fn height(&self) -> usize {
-    self.map.len()
+  /// Is the position outside the map or in the forest
+  fn is_blocked(&self, xy: &Point2<i64>) -> bool {
+    matches!(self.map.get(xy.x, xy.y), None | Some(Forest))
   }
 
-  fn width(&self) -> usize {
-    self.map[0].len()
-  }
-
-  fn single_next(&self, xy: &XY, visited: &HashSet<XY>) -> Option<XY> {
-    let mut result: Option<XY> = None;
+  fn single_next(&self, xy: &Point2<i64>, visited: &HashSet<Point2<i64>>) -> Option<Point2<i64>> {
+    let mut result: Option<Point2<i64>> = None;
 
-    for (x, y) in NEIGHBORS.iter() {
-      let x = xy.x + x;
-      let y = xy.y + y;
-      if x > -1 && x < self.width() as i32
-        && y > -1 && y < self.height() as i32
-        && self.map[y as usize][x as usize] != Forest
-        && !visited.contains(&XY{x, y}) {
+    for dir in Dir4::ALL {
+      let next = *xy + dir.delta();
+      if !self.is_blocked(&next)
+        && !visited.contains(&next) {
         if result.is_none() {
-          result = Some(XY{x, y});
+          result = Some(next);
         } else {
           result = None;
           break;
@@ -140,22 +132,21 @@ impl TrailMap {
   fn walk(&self) -> u32 {
     let mut stack: Vec<Hike> = Vec::new();
     let mut visited = HashSet::new();
-    visited.insert(self.start.clone());
+    visited.insert(self.start);
     stack.push(Hike {
-      pos: self.start.clone(),
+      pos: self.start,
       visited,
     });
     let mut max_len = 0_u32;
 
-    while !stack.is_empty() {
-      let mut curr = stack.pop().unwrap();
+    while let Some(mut curr) = stack.pop() {
 
       // If we have a single path, move forward and avoid cloning
       loop {
         match self.single_next(&curr.pos, &curr.visited) {
           None => break,
           Some(n) => {
-            if !curr.move_by(&(n.x -curr.pos.x), &(n.y - curr.pos.y), self) {
+            if !curr.move_by(n - curr.pos, self) {
               break;
             }
           }
@@ -169,33 +160,32 @@ impl TrailMap {
         continue;
       }
 
-      for (x, y) in NEIGHBORS.iter() {
+      for dir in Dir4::ALL {
         let mut next = curr.clone();
-        if !next.move_by(x, y, self) { continue; }
+        if !next.move_by(dir.delta(), self) { continue; }
         stack.push(next);
       }
     }
     max_len
   }
 
-  fn make_graph(&self) -> Graph {
-    let mut g = Graph::default();
-    g.get_id(&self.start);
+  fn make_graph(&self) -> Graph<Point2<i64>, u32> {
+    let mut g = Graph::new();
+    g.node(self.start);
 
     let mut e = Edge {
-      start: self.start.clone(),
-      curr: self.start.clone(),
-      visited: HashSet::from([self.start.clone()]),
+      start: self.start,
+      curr: self.start,
+      visited: HashSet::from([self.start]),
     };
     // As this is a single trail block
     e.visit(e.nexts(self).pop().unwrap());
 
     let mut stack: Vec<Edge> = vec![e];
 
-    while !stack.is_empty() {
-      let mut e = stack.pop().unwrap();
-      if g.is_vertex(&e.curr) {
-        g.record_edge(&e.start, &e.curr, e.len());
+    while let Some(mut e) = stack.pop() {
+      if g.id(&e.curr).is_some() {
+        record_edge(&mut g, &e.start, &e.curr, e.len());
         continue;
       }
 
@@ -204,7 +194,7 @@ impl TrailMap {
       match possibilities {
         0 => {
           // Complete edge
-          g.record_edge(&e.start, &e.curr, e.len());
+          record_edge(&mut g, &e.start, &e.curr, e.len());
         }
         1 => {
           // Extend the edge
@@ -217,14 +207,14 @@ impl TrailMap {
         }
         _ => {
           // As we have multiple paths, mark the vertex
-          g.get_id(&e.curr);
-          g.record_edge(&e.start, &e.curr, e.len());
+          g.node(e.curr);
+          record_edge(&mut g, &e.start, &e.curr, e.len());
           for next in nexts {
             // Create a new edge for each possibility
             let e = Edge {
-              start: e.curr.clone(),
-              curr: next.clone(),
-              visited: HashSet::from([e.curr.clone(), next]),
+              start: e.curr,
+              curr: next,
+              visited: HashSet::from([e.curr, next]),
             };
             stack.push(e);
           }
@@ -235,69 +225,18 @@ impl TrailMap {
   }
 }
 
-#[derive(Default)]
-struct Graph {
-  vertices: HashMap<XY, usize>,
-  edges: HashMap<(usize, usize), u32>,
-}
-
-impl Graph {
-  fn get_id(&mut self, xy: &XY) -> usize {
-    if !self.vertices.contains_key(xy) {
-      self.vertices.insert(xy.clone(), self.vertices.len());
-    }
-    *self.vertices.get(xy).unwrap()
-  }
-
-  fn record_edge(&mut self, xy1: &XY, xy2: &XY, len: u32) {
-    let id1 = self.get_id(xy1);
-    let id2 = self.get_id(xy2);
-    self.edges.entry((id1.min(id2), id2.max(id1)))
-      .and_modify(|v| *v = (*v).max(len))
-      .or_insert(len);
-  }
-
-  fn is_vertex(&self, xy: &XY) -> bool {
-    self.vertices.contains_key(xy)
-  }
-
-  fn max_distance(&self, start: &XY, end: &XY) -> u32 {
-    let mut paths: Vec<Vec<(usize, u32)>> = vec![vec![]; self.vertices.len()];
-    for ((v1, v2), d) in &self.edges {
-      paths[*v1].push((*v2, *d));
-      paths[*v2].push((*v1, *d));
-    }
-    let start = self.vertices.get(start).unwrap();
-    let end = self.vertices.get(end).unwrap();
-
-    let mut max_dist = 0;
-    let mut stack: Vec<(usize, u32, Vec<bool>)> = Vec::new();
-    let mut visited = vec![false; self.vertices.len()];
-    visited[*start] = true;
-    stack.push((*start, 0, visited));
-
-    while !stack.is_empty() {
-      let (v, d, visited) = stack.pop().unwrap();
-      if &v == end {
-        max_dist = max_dist.max(d);
-        continue;
-      }
-      paths[v].iter().for_each(|(n, d_to_n)| {
-        if !visited[*n] {
-          let mut visited = visited.clone();
-          visited[*n] = true;
-          stack.push((*n, d + d_to_n, visited));
-        }
-      });
-    }
-    max_dist
-  }
+/// Record the edge between two vertices, keeping the longer one when there are several
+fn record_edge(g: &mut Graph<Point2<i64>, u32>, xy1: &Point2<i64>, xy2: &Point2<i64>, len: u32) {
+  let id1 = g.node(*xy1);
+  let id2 = g.node(*xy2);
+  let len = g.edge(id1, id2).map_or(len, |l| l.max(len));
+  g.insert_undirected_edge(id1, id2, len);
 }
 
 struct Edge {
-  start: XY,
-  curr: XY,
-  visited: HashSet<XY>,
+  start: Point2<i64>,
+  curr: Point2<i64>,
+  visited: HashSet<Point2<i64>>,
 }
 
 impl Edge {
@@ -305,38 +244,32 @@ impl Edge {
     (self.visited.len() - 1) as u32
   }
 
-  fn move_by(&self, dx: &i32, dy: &i32, tm: &TrailMap) -> Option<XY> {
-    let mut next = self.curr.clone();
-    next.x += *dx;
-    next.y += *dy;
-    if next.x < 0 || next.x >= tm.width() as i32
-      || next.y < 0 || next.y >= tm.height() as i32
-      || tm.map[next.y as usize][next.x as usize] == Forest
-      || self.visited.contains(&next) {
+  fn move_by(&self, dir: Dir4, tm: &TrailMap) -> Option<Point2<i64>> {
+    let next = self.curr + dir.delta();
+    if tm.is_blocked(&next) || self.visited.contains(&next) {
       None
     } else {
       Some(next)
     }
   }
 
-  fn visit(&mut self, xy: XY) {
+  fn visit(&mut self, xy: Point2<i64>) {
     self.curr = xy;
-    self.visited.insert(self.curr.clone());
+    self.visited.insert(self.curr);
   }
 
-  fn nexts(&self, tm: &TrailMap) -> Vec<XY> {
-    let nexts: Vec<XY> = NEIGHBORS.iter().filter_map(|(x, y)| {
-      self.move_by(x, y, tm)
+  fn nexts(&self, tm: &TrailMap) -> Vec<Point2<i64>> {
+    let nexts: Vec<Point2<i64>> = Dir4::ALL.iter().filter_map(|dir| {
+      self.move_by(*dir, tm)
     }).collect();
     nexts
   }
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day23::{generator, part1, part2, XY};
-
-  const INPUT: &str = "#.#####################
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "#.#####################
 #.......#########...###
 #######.#########.#.###
 ###.....#.>.>.###.#.###
@@ -358,27 +291,85 @@ mod tests {
 #...#...#.#.>.>.#.>.###
 #.###.###.#.###.#.#v###
 #.....###...###...#...#
-#####################.#";
+#####################.#",
+    part1: Some("94"),
+    part2: Some("154"),
+  },
+];
+
+/// Like the puzzle, a trail map with crossings on a grid of up to six by six that are joined by
+/// straight trails, with slopes down and to the right next to each crossing. The size is about
+/// the side of the map.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let crossings = (size / 20).clamp(2, 6);
+  let spacing = (size / crossings).max(4);
+  let mut place = || {
+    let mut at = rng.gen_range(1..=3);
+    let places: Vec<usize> = (0..crossings).map(|_| {
+      let place = at;
+      at += rng.gen_range(4..=spacing);
+      place
+    }).collect();
+    (at - rng.gen_range(0..2), places)
+  };
+  let ((width, xs), (height, ys)) = (place(), place());
+  let (last_x, last_y) = (xs[crossings - 1], ys[crossings - 1]);
+  let mut map = Grid::filled(width, height, '#');
+  for y in 0..ys[0] {
+    map[(xs[0], y)] = '.';
+  }
+  for y in last_y..height {
+    map[(last_x, y)] = '.';
+  }
+  for w in xs.windows(2) {
+    for &y in &ys {
+      for x in w[0]..=w[1] {
+        map[(x, y)] = '.';
+      }
+      map[(w[0] + 1, y)] = '>';
+      map[(w[1] - 1, y)] = '>';
+    }
+  }
+  for w in ys.windows(2) {
+    for &x in &xs {
+      for y in w[0]..=w[1] {
+        map[(x, y)] = '.';
+      }
+      map[(x, w[0] + 1)] = 'v';
+      map[(x, w[1] - 1)] = 'v';
+    }
+  }
+  map.to_string()
+}
+
+solution!(Day23, TrailMap, u32, u32);
+
+#[cfg(test)]
+mod tests {
+  use crate::day23::{EXAMPLES, generator, part1, part2};
+  use crate::utils::geom::Point2;
+
+  const INPUT: &str = EXAMPLES[0].input;
 
   #[test]
   fn test_generator() {
-    let tm = generator(INPUT);
-    assert_eq!(23, tm.width());
-    assert_eq!(XY { x: 1, y: 0 }, tm.start);
-    assert_eq!(XY { x: 21, y: 22 }, tm.end);
-    assert_eq!(23, tm.height());
+    let tm = generator(INPUT).unwrap();
+    assert_eq!(23, tm.map.width());
+    assert_eq!(Point2::new(1, 0), tm.start);
+    assert_eq!(Point2::new(21, 22), tm.end);
+    assert_eq!(23, tm.map.height());
   }
 
   #[test]
   fn test_part1() {
-    let tm = generator(INPUT);
+    let tm = generator(INPUT).unwrap();
     assert_eq!(94, part1(&tm));
     //assert_eq!(94, tm.walk_max(false));
   }
 
   #[test]
   fn test_part2() {
-    let tm = generator(INPUT);
+    let tm = generator(INPUT).unwrap();
     assert_eq!(154, part2(&tm));
     //assert_eq!(154, tm.walk_max(true));
   }
@@ -392,19 +383,20 @@ mod tests {
 
   #[test]
   fn test_graph() {
-    let tm = generator(INPUT_SIMPLE);
+    let tm = generator(INPUT_SIMPLE).unwrap();
     let g = tm.make_graph();
-    assert_eq!(4, g.vertices.len());
-    assert!(g.vertices.contains_key(&XY { x: 1, y: 0 }));
-    assert!(g.vertices.contains_key(&XY { x: 6, y: 5 }));
-    assert!(g.vertices.contains_key(&XY { x: 1, y: 2 }));
-    assert!(g.vertices.contains_key(&XY { x: 6, y: 4 }));
-
-    assert_eq!(g.edges.len(), 3);
-    assert_eq!(g.edges.get(&(0, 1)).unwrap(), &2);
-    assert_eq!(g.edges.get(&(1, 2)).unwrap(), &11);
-    assert_eq!(g.edges.get(&(2, 3)).unwrap(), &1);
-
-    assert_eq!(14, g.max_distance(&tm.start, &tm.end));
+    assert_eq!(4, g.len());
+    assert!(g.id(&Point2::new(1, 0)).is_some());
+    assert!(g.id(&Point2::new(6, 5)).is_some());
+    assert!(g.id(&Point2::new(1, 2)).is_some());
+    assert!(g.id(&Point2::new(6, 4)).is_some());
+
+    assert_eq!((0..g.len()).map(|id| g.edges(id).len()).sum::<usize>(), 2 * 3);
+    assert_eq!(g.edge(0, 1), Some(2));
+    assert_eq!(g.edge(1, 2), Some(11));
+    assert_eq!(g.edge(2, 3), Some(1));
+
+    let (start, end) = (g.id(&tm.start).unwrap(), g.id(&tm.end).unwrap());
+    assert_eq!(Some(14), g.longest_path(start, end));
   }
 }
\ No newline at end of file
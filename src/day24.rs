@@ -1,6 +1,15 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use prime_factorization::Factorization;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use crate::day24::Dimension::{X, Y, Z};
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::checked::{cast, mul, sum};
+use crate::utils::geom::Point3;
+use crate::utils::parse::{array, split_once};
+use crate::utils::random::Random;
+
+pub mod reference;
 
 #[derive(Debug)]
 enum Dimension {
@@ -49,10 +58,11 @@ pub struct Hail {
 }
 
 impl Hail {
-  fn from(input: &str) -> Hail {
-    let stones = input.lines().map(Stone::from)
-      .collect();
-    Hail { stones }
+  fn from(input: &str) -> Result<Hail, ParseError> {
+    let stones = input.lines()
+      .map(|line| Stone::from(line).map_err(|e| e.within(input, line)))
+      .collect::<Result<_, _>>()?;
+    Ok(Hail { stones })
   }
 
   fn count_crossings(&self, min_d: u64, max_d: u64) -> u32 {
@@ -81,7 +91,7 @@ impl Hail {
         .and_modify(|v| v.push(i))
         .or_insert(vec![i]);
     }
-    map.iter().filter_map(|(_, v)|
+    map.values().filter_map(|v|
       if v.len() > 1 {
         let mut combinations = Vec::new();
         for i in 0..v.len() - 1 {
@@ -150,47 +160,53 @@ impl Hail {
     let z_src = dzp.src_stone(self);
     for (dx, tx) in &dxp.delta {
       for (dy, ty) in &dyp.delta {
-        let t_y1 = (y_tgt.x - y_src.x + (y_tgt.dx * ty) - (dx * ty)) / (y_src.dx - y_tgt.dx);
+        let t_y1 = meet_time(y_tgt, y_src, X, *dx, *ty);
         if t_y1 <= 0 {
           // Invalid combination
           continue;
         }
         for (dz, tz) in &dzp.delta {
-          let t_z1 = (z_tgt.x - z_src.x + (z_tgt.dx * tz) - (dx * tz)) / (z_src.dx - z_tgt.dx);
+          let t_z1 = meet_time(z_tgt, z_src, X, *dx, *tz);
           if t_z1 <= 0 { continue; }
-          let t_z2 = (z_tgt.y - z_src.y + (z_tgt.dy * tz) - (dy * tz)) / (z_src.dy - z_tgt.dy);
+          let t_z2 = meet_time(z_tgt, z_src, Y, *dy, *tz);
           if t_z2 != t_z1 { continue; }
 
-          let t_y2 = (y_tgt.z - y_src.z + (y_tgt.dz * ty) - (dz * ty)) / (y_src.dz - y_tgt.dz);
+          let t_y2 = meet_time(y_tgt, y_src, Z, *dz, *ty);
           if t_y2 != t_y1 { continue; }
 
           // Check for x possibilities
-          let t_x1 = (x_tgt.y - x_src.y + (x_tgt.dy * tx) - (dy * tx)) / (x_src.dy - x_tgt.dy);
+          let t_x1 = meet_time(x_tgt, x_src, Y, *dy, *tx);
           if t_x1 <= 0 { continue; }
-          let t_x2 = (x_tgt.z - x_src.z + (x_tgt.dz * tx) - (dz * tx)) / (x_src.dz - x_tgt.dz);
+          let t_x2 = meet_time(x_tgt, x_src, Z, *dz, *tx);
           if t_x2 != t_x1 { continue; }
 
-          let x = z_src.x + z_src.dx * t_z1 - dx * t_z1;
-          let y = z_src.y + z_src.dy * t_z1 - dy * t_z1;
-          let z = z_src.z + z_src.dz * t_z1 - dz * t_z1;
-          possibilities.insert(Stone { x, y, z, dx: *dx, dy: *dy, dz: *dz });
+          let t_z1 = cast(t_z1);
+          let x = z_src.pos.x + mul(z_src.vel.x, t_z1) - mul(*dx, t_z1);
+          let y = z_src.pos.y + mul(z_src.vel.y, t_z1) - mul(*dy, t_z1);
+          let z = z_src.pos.z + mul(z_src.vel.z, t_z1) - mul(*dz, t_z1);
+          possibilities.insert(Stone { pos: Point3::new(x, y, z), vel: Point3::new(*dx, *dy, *dz) });
         }
       }
     }
     assert_eq!(1, possibilities.len());
-    let s = possibilities.drain().next().unwrap();
-    s
+    possibilities.into_iter().next().unwrap()
   }
 }
 
+/// When the source stone meets the target along the dimension, given the change in the rock's
+/// velocity and the time it takes. Wrong guesses of the change can give huge times, so this is
+/// computed wide enough to never overflow and the guess is rejected instead
+fn meet_time(tgt: &Stone, src: &Stone, dim: Dimension, d: i64, t: i64) -> i128 {
+  let (get_dim, get_delta) = (dim.get_dim_fn(), dim.get_delta_fn());
+  let (tgt_pos, tgt_vel) = (get_dim(tgt) as i128, get_delta(tgt) as i128);
+  let (src_pos, src_vel) = (get_dim(src) as i128, get_delta(src) as i128);
+  (tgt_pos - src_pos + (tgt_vel - d as i128) * t as i128) / (src_vel - tgt_vel)
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 struct Stone {
-  x: i64,
-  y: i64,
-  z: i64,
-  dx: i64,
-  dy: i64,
-  dz: i64,
+  pos: Point3<i64>,
+  vel: Point3<i64>,
 }
 
 fn all_factors(prime_factors: &[u64]) -> Vec<u64> {
@@ -209,59 +225,52 @@ fn all_factors(prime_factors: &[u64]) -> Vec<u64> {
 
 impl Stone {
   fn get_x(&self) -> i64 {
-    self.x
+    self.pos.x
   }
 
   fn get_y(&self) -> i64 {
-    self.y
+    self.pos.y
   }
 
   fn get_z(&self) -> i64 {
-    self.z
+    self.pos.z
   }
 
   fn get_dx(&self) -> i64 {
-    self.dx
+    self.vel.x
   }
 
   fn get_dy(&self) -> i64 {
-    self.dy
+    self.vel.y
   }
 
   fn get_dz(&self) -> i64 {
-    self.dz
+    self.vel.z
   }
 
-  fn from(input: &str) -> Stone {
-    let (pos, velocity) = input.split_once(" @ ").unwrap();
-    let mut pos = pos.split(", ");
-    let mut velocity = velocity.split(", ");
-    let x: i64 = pos.next().unwrap().trim().parse().unwrap();
-    let y: i64 = pos.next().unwrap().trim().parse().unwrap();
-    let z: i64 = pos.next().unwrap().trim().parse().unwrap();
-    let dx: i64 = velocity.next().unwrap().trim().parse().unwrap();
-    let dy: i64 = velocity.next().unwrap().trim().parse().unwrap();
-    let dz: i64 = velocity.next().unwrap().trim().parse().unwrap();
-    Stone { x, y, z, dx, dy, dz }
+  fn from(input: &str) -> Result<Stone, ParseError> {
+    let (pos, velocity) = split_once(input, input, " @ ", "'<x>, <y>, <z> @ <dx>, <dy>, <dz>'")?;
+    Ok(Stone { pos: array(input, pos, ",")?.into(), vel: array(input, velocity, ",")?.into() })
   }
 
   fn at_time(&self, t: f64) -> (f64, f64, f64) {
     (
-      self.x as f64 + self.dx as f64 * t,
-      self.y as f64 + self.dy as f64 * t,
-      self.z as f64 + self.dz as f64 * t
+      self.pos.x as f64 + self.vel.x as f64 * t,
+      self.pos.y as f64 + self.vel.y as f64 * t,
+      self.pos.z as f64 + self.vel.z as f64 * t
     )
   }
 
   fn cross_xy(&self, other: &Stone) -> Option<(f64, f64)> {
-    if other.dy * self.dx - other.dx * self.dy == 0 {
+    let det = other.vel.y * self.vel.x - other.vel.x * self.vel.y;
+    if det == 0 {
       return None;
     }
 
-    let other_t = (((other.x - self.x) * self.dy) - ((other.y - self.y) * self.dx)) as f64
-      / (other.dy * self.dx - other.dx * self.dy) as f64;
-    let self_t = (other.x - self.x) as f64 / self.dx as f64
-      + other.dx as f64 * other_t / self.dx as f64;
+    let other_t = (mul(other.pos.x - self.pos.x, self.vel.y) - mul(other.pos.y - self.pos.y, self.vel.x)) as f64
+      / det as f64;
+    let self_t = (other.pos.x - self.pos.x) as f64 / self.vel.x as f64
+      + other.vel.x as f64 * other_t / self.vel.x as f64;
 
     if other_t < 0f64 || self_t < 0f64 {
       None
@@ -272,19 +281,19 @@ impl Stone {
   }
 
   fn time_from(&self, stone: &Stone) -> i64 {
-    if self.dx == stone.dx {
-      self.x - stone.x
-    } else if self.dy == stone.dy {
-      self.y - stone.y
-    } else if self.dz == stone.dz {
-      self.z - stone.z
+    if self.vel.x == stone.vel.x {
+      self.pos.x - stone.pos.x
+    } else if self.vel.y == stone.vel.y {
+      self.pos.y - stone.pos.y
+    } else if self.vel.z == stone.vel.z {
+      self.pos.z - stone.pos.z
     } else {
       panic!("Cannot estimate time from stone {:?} to {:?}", stone, self);
     }
   }
 }
 
-pub fn generator(input: &str) -> Hail {
+pub fn generator(input: &str) -> Result<Hail, ParseError> {
   Hail::from(input)
 }
 
@@ -294,66 +303,110 @@ pub fn part1(hail: &Hail) -> i64 {
 
 pub fn part2(hail: &Hail) -> i64 {
   let s = hail.find_hit_all_stone();
-  s.x + s.y + s.z
+  sum([s.pos.x, s.pos.y, s.pos.z])
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day24::{all_factors, Hail, part2, Stone};
-
-  const INPUT: &str = "19, 13, 30 @ -2,  1, -2
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "19, 13, 30 @ -2,  1, -2
 18, 19, 22 @ -1, -1, -2
 20, 25, 34 @ -2, -2, -4
 12, 31, 28 @ -1, -2, -1
-20, 19, 15 @  1, -5, -3";
+20, 19, 15 @  1, -5, -3",
+    part1: None,
+    part2: Some("47"),
+  },
+];
+
+/// Hailstones of the size that a rock thrown from a random place hits, each at its own time. Like
+/// the puzzle the positions are around 10^14, and for each component of the velocity there are
+/// hailstones sharing it, whose other components differ. No component is zero or the rock's.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let count = size.max(12);
+  let rock: [i64; 3] = std::array::from_fn(|_| rng.gen_range(200_000_000_000_000..400_000_000_000_000));
+  let speed: [i64; 3] = std::array::from_fn(|_| rng.gen_range(-300..=300));
+  let mut velocities: Vec<[i64; 3]> = Vec::with_capacity(count);
+  while velocities.len() < count {
+    let mut vel: [i64; 3] = std::array::from_fn(|_| rng.gen_range(-500..=500));
+    // Each pair shares the x, y or z in turn
+    let i = velocities.len();
+    if i % 2 == 1 {
+      vel[i / 2 % 3] = velocities[i - 1][i / 2 % 3];
+    }
+    let shared = |other: &[i64; 3]| (0..3).filter(|&d| vel[d] == other[d]).count();
+    if (0..3).all(|d| vel[d] != 0 && vel[d] != speed[d]) && velocities.iter().all(|v| shared(v) < 2) {
+      velocities.push(vel);
+    }
+  }
+  let mut times = BTreeSet::new();
+  while times.len() < count {
+    times.insert(rng.gen_range(100_000_000_000..1_000_000_000_000_i64));
+  }
+  let mut lines: Vec<String> = times.into_iter().zip(velocities).map(|(t, vel)| {
+    let pos: [i64; 3] = std::array::from_fn(|d| rock[d] + (speed[d] - vel[d]) * t);
+    format!("{}, {}, {} @ {}, {}, {}", pos[0], pos[1], pos[2], vel[0], vel[1], vel[2])
+  }).collect();
+  lines.shuffle(rng);
+  lines.join("\n")
+}
+
+solution!(Day24, Hail, i64, i64);
+
+#[cfg(test)]
+mod tests {
+  use crate::day24::{all_factors, EXAMPLES, generator, Hail, part2, random_input, reference, Stone};
+  use crate::utils::differential::check;
+
+  const INPUT: &str = EXAMPLES[0].input;
 
   #[test]
   fn test_generator() {
-    let h = Hail::from(INPUT);
+    let h = Hail::from(INPUT).unwrap();
     assert_eq!(5, h.stones.len());
   }
 
   #[test]
   fn test_stone() {
-    Stone::from("297310270744292, 292515986537934, 398367816281800 @ -130, 46, -342");
+    Stone::from("297310270744292, 292515986537934, 398367816281800 @ -130, 46, -342").unwrap();
   }
 
   #[test]
   fn test_meets() {
-    let s1 = Stone::from("19, 13, 30 @ -2, 1, -2");
-    let s2 = Stone::from("18, 19, 22 @ -1, -1, -2");
+    let s1 = Stone::from("19, 13, 30 @ -2, 1, -2").unwrap();
+    let s2 = Stone::from("18, 19, 22 @ -1, -1, -2").unwrap();
     let (x, y) = s1.cross_xy(&s2).unwrap();
     assert_eq!(x as i32, 14);
     assert_eq!(y as i32, 15);
     assert!(x > 14.333);
     assert!(y > 15.333);
 
-    let s1 = Stone::from("19, 13, 30 @ -2, 1, -2");
-    let s2 = Stone::from("20, 25, 34 @ -2, -2, -4");
+    let s1 = Stone::from("19, 13, 30 @ -2, 1, -2").unwrap();
+    let s2 = Stone::from("20, 25, 34 @ -2, -2, -4").unwrap();
     let (x, y) = s1.cross_xy(&s2).unwrap();
     assert_eq!(x as i32, 11);
     assert_eq!(y as i32, 16);
     assert!(x > 11.6);
     assert!(y > 16.6);
 
-    let s1 = Stone::from("19, 13, 30 @ -2, 1, -2");
-    let s2 = Stone::from("20, 19, 15 @ 1, -5, -3");
+    let s1 = Stone::from("19, 13, 30 @ -2, 1, -2").unwrap();
+    let s2 = Stone::from("20, 19, 15 @ 1, -5, -3").unwrap();
     assert_eq!(None, s1.cross_xy(&s2));
 
-    let s1 = Stone::from("18, 19, 22 @ -1, -1, -2");
-    let s2 = Stone::from("20, 25, 34 @ -2, -2, -4");
+    let s1 = Stone::from("18, 19, 22 @ -1, -1, -2").unwrap();
+    let s2 = Stone::from("20, 25, 34 @ -2, -2, -4").unwrap();
     assert_eq!(None, s1.cross_xy(&s2));
   }
 
   #[test]
   fn test_part1() {
-    let h = Hail::from(INPUT);
+    let h = Hail::from(INPUT).unwrap();
     assert_eq!(2, h.count_crossings(7, 27));
   }
 
   #[test]
   fn test_part2() {
-    let h = Hail::from(INPUT);
+    let h = Hail::from(INPUT).unwrap();
     assert_eq!(part2(&h), 47);
   }
 
@@ -366,4 +419,10 @@ mod tests {
                                            262590870317 * 197,
                                            factors.iter().product()]);
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn test_reference() {
+    assert_eq!(47, reference::part2(&Hail::from(INPUT).unwrap()));
+    check(random_input, generator, 12, 0..3, part2, reference::part2);
+  }
+}
@@ -0,0 +1,61 @@
+//! Try every velocity of the rock in x and y up to a limit, throwing the rock with each one to
+//! see whether it hits every hailstone
+
+use crate::day24::{Hail, Stone};
+
+/// The largest speed of the rock in x and in y that is tried
+const LIMIT: i64 = 500;
+
+pub fn part2(hail: &Hail) -> i64 {
+  let rock = (-LIMIT..=LIMIT)
+    .flat_map(|dx| (-LIMIT..=LIMIT).map(move |dy| (dx, dy)))
+    .find_map(|(dx, dy)| throw(&hail.stones, dx, dy))
+    .unwrap_or_else(|| panic!("No rock moving at most {LIMIT} in x and y hits every hailstone"));
+  (rock[0] + rock[1] + rock[2]) as i64
+}
+
+/// Where the rock starts when thrown with the velocity in x and y, if it hits every hailstone at
+/// a whole time. Relative to the rock the hailstones all pass through its start, so two of them
+/// crossing in x and y give the start, two of the times give the velocity in z and the rest must
+/// agree.
+fn throw(stones: &[Stone], dx: i64, dy: i64) -> Option<[i128; 3]> {
+  let relative = |s: &Stone| {
+    ([s.pos.x, s.pos.y, s.pos.z].map(i128::from), [s.vel.x - dx, s.vel.y - dy, s.vel.z].map(i128::from))
+  };
+  let (a_pos, a_vel) = relative(&stones[0]);
+  // Solve a_pos + a_vel * t = b_pos + b_vel * u in x and y for the first hailstone not parallel
+  let t = stones[1..].iter().find_map(|b| {
+    let (b_pos, b_vel) = relative(b);
+    let det = b_vel[0] * a_vel[1] - a_vel[0] * b_vel[1];
+    (det != 0).then(|| (b_vel[0] * (b_pos[1] - a_pos[1]) - b_vel[1] * (b_pos[0] - a_pos[0]), det))
+  })?;
+  if t.0 % t.1 != 0 {
+    return None;
+  }
+  let start = [0, 1].map(|d| a_pos[d] + a_vel[d] * (t.0 / t.1));
+
+  let hits: Vec<([i128; 3], [i128; 3], i128)> = stones.iter()
+    .map(|s| {
+      let (pos, vel) = relative(s);
+      hit_time(pos, vel, start).map(|t| (pos, vel, t))
+    })
+    .collect::<Option<_>>()?;
+  let (a_pos, a_vel, a_t) = hits[0];
+  let &(b_pos, b_vel, b_t) = hits.iter().find(|(_, _, t)| *t != a_t)?;
+  let dz = b_pos[2] - a_pos[2] + b_vel[2] * b_t - a_vel[2] * a_t;
+  if dz % (b_t - a_t) != 0 {
+    return None;
+  }
+  let dz = dz / (b_t - a_t);
+  let z = a_pos[2] + (a_vel[2] - dz) * a_t;
+  hits.iter()
+    .all(|(pos, vel, t)| pos[2] + (vel[2] - dz) * t == z)
+    .then_some([start[0], start[1], z])
+}
+
+/// When the hailstone moving relative to the rock passes through its start in x and y
+fn hit_time(pos: [i128; 3], vel: [i128; 3], start: [i128; 2]) -> Option<i128> {
+  let d = (0..2).find(|d| vel[*d] != 0)?;
+  let t = (start[d] - pos[d]) / vel[d];
+  (t >= 0 && (0..2).all(|d| pos[d] + vel[d] * t == start[d])).then_some(t)
+}
@@ -1,44 +1,33 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::BTreeSet;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::graph::{Graph, NodeId};
+use crate::utils::intern::{Interner, Symbol};
+use crate::utils::random::{names, Random};
 
 
 #[derive(Clone)]
-pub struct WiringDiagram<'a> {
-  id_map: Vec<&'a str>,
-  links: Vec<Vec<usize>>,
+pub struct WiringDiagram {
+  names: Interner,
+  graph: Graph<Symbol, ()>,
 }
 
-impl WiringDiagram<'_> {
-  fn from(input: &str) -> WiringDiagram {
-    let mut id_map: HashMap<&str, usize> = HashMap::new();
+impl WiringDiagram {
+  fn from(input: &str) -> Result<WiringDiagram, ParseError> {
+    let mut names = Interner::new();
+    let mut graph = Graph::new();
     for line in input.lines() {
-      let (from, to) = line.split_once(": ").unwrap();
-      let i = id_map.len();
-      id_map.entry(from).or_insert(i);
+      let (from, to) = line.split_once(": ")
+        .ok_or_else(|| ParseError::at(input, line, "Expected '<component>: <components>'"))?;
+      let from = graph.node(names.intern(from));
       for to in to.split(' ') {
-        let i = id_map.len();
-        id_map.entry(to).or_insert(i);
-      }
-    }
-    let mut links: Vec<Vec<usize>> = vec![Vec::new(); id_map.len()];
-    for line in input.lines() {
-      let (from, to) = line.split_once(": ").unwrap();
-      let l1 = id_map.get(from).unwrap();
-      for to in to.split(' ') {
-        let l2 = id_map.get(to).unwrap();
-        links[*l1].push(*l2);
-        links[*l2].push(*l1);
+        let to = graph.node(names.intern(to));
+        graph.insert_undirected_edge(from, to, ());
       }
     }
 
-    let mut ids = vec![""; links.len()];
-    for (k, v) in id_map {
-      ids[v] = k;
-    }
-
-    WiringDiagram {
-      id_map: ids,
-      links,
-    }
+    Ok(WiringDiagram { names, graph })
   }
 
   fn compute_splits(&self) -> u32 {
@@ -48,98 +37,53 @@ impl WiringDiagram<'_> {
     let mut edges = Vec::new();
     for i in 0..5 {
       for j in i + 1..6 {
-        if self.links[closest_points[i].0].contains(&closest_points[j].0) {
+        if self.graph.edge(closest_points[i].0, closest_points[j].0).is_some() {
           edges.push((closest_points[i].0, closest_points[j].0));
         }
       }
     }
 
     for i in 0..edges.len() - 2 {
-      wd.disable_link(edges[i].0, edges[i].1);
+      wd.graph.remove_undirected_edge(edges[i].0, edges[i].1);
       for j in i + 1..edges.len() - 1 {
-        wd.disable_link(edges[j].0, edges[j].1);
-        for k in j + 1..edges.len() {
-          wd.disable_link(edges[k].0, edges[k].1);
-          if let Some(l) = wd.is_two_groups(edges[i].0, edges[i].1) {
+        wd.graph.remove_undirected_edge(edges[j].0, edges[j].1);
+        for &(a, b) in &edges[j + 1..] {
+          wd.graph.remove_undirected_edge(a, b);
+          if let Some(l) = wd.two_group_sizes() {
             return l;
           }
-          wd.enable_link(edges[k].0, edges[k].1);
+          wd.graph.insert_undirected_edge(a, b, ());
         }
-        wd.enable_link(edges[j].0, edges[j].1);
+        wd.graph.insert_undirected_edge(edges[j].0, edges[j].1, ());
       }
-      wd.enable_link(edges[i].0, edges[i].1);
+      wd.graph.insert_undirected_edge(edges[i].0, edges[i].1, ());
     }
-    panic!("Could not determine split for the given input!!!")
+    let names: Vec<&str> = closest_points[..6].iter().map(|(id, _)| self.name(*id)).collect();
+    panic!("Could not determine split for the given input!!! Tried the edges between {}", names.join(", "))
   }
 
-  fn is_two_groups(&self, from: usize, cannot_reach: usize) -> Option<u32> {
-    let mut pending = Vec::new();
-    pending.push(from);
-    let mut visited = vec![false; self.id_map.len()];
+  fn name(&self, id: NodeId) -> &str {
+    self.names.name(*self.graph.key(id))
+  }
 
-    while !pending.is_empty() {
-      let c = pending.pop().unwrap();
-      if visited[c] {
-        continue;
-      }
-      visited[c] = true;
-      if c == cannot_reach {
-        return None;
-      }
-      for v in &self.links[c] {
-        pending.push(*v);
-      }
+  /// The product of the group sizes when the components are in exactly two groups
+  fn two_group_sizes(&self) -> Option<u32> {
+    match self.graph.components().as_slice() {
+      [group1, group2] => Some((group1.len() * group2.len()) as u32),
+      _ => None,
     }
-    let group1_size = visited.iter().filter(|&v| *v).count() as u32;
-    Some(group1_size * (self.id_map.len() as u32 - group1_size))
   }
 
   fn find_closest_points(&self) -> Vec<(usize, u32)> {
-    let mut all_distances: Vec<(usize, u32)> = (0..self.id_map.len())
-      .map(|start| (start, self.find_distances(start).iter().sum()))
+    let mut all_distances: Vec<(usize, u32)> = (0..self.graph.len())
+      .map(|start| (start, self.graph.bfs_distances(start).iter().flatten().sum()))
       .collect();
     all_distances.sort_by_key(|(_, total_dist)| *total_dist);
     all_distances
   }
-
-  fn find_distances(&self, start: usize) -> Vec<u32> {
-    let mut distances = vec![0; self.id_map.len()];
-    let mut visited = vec![false; self.id_map.len()];
-    let mut queue = VecDeque::new();
-    let mut count = 0;
-
-    visited[start] = true;
-    count += 1;
-    queue.push_back(vec![start]);
-
-    while count < visited.len() {
-      let path = queue.pop_front().unwrap();
-      for t in &self.links[*path.last().unwrap()] {
-        if visited[*t] { continue; }
-        visited[*t] = true;
-        count += 1;
-        let mut p = path.clone();
-        p.push(*t);
-        distances[*t] = (p.len() - 1) as u32;
-        queue.push_back(p);
-      }
-    }
-
-    distances
-  }
-
-  fn enable_link(&mut self, l1: usize, l2: usize) {
-    self.links[l1].push(l2);
-    self.links[l2].push(l1);
-  }
-
-  fn disable_link(&mut self, l1: usize, l2: usize) {
-    self.links[l1].retain(|v| v != &l2);
-    self.links[l2].retain(|v| v != &l1);
-  }
 }
 
-pub fn generator(input: &str) -> WiringDiagram {
+pub fn generator(input: &str) -> Result<WiringDiagram, ParseError> {
   WiringDiagram::from(input)
 }
 
@@ -151,11 +95,10 @@ pub fn part2(_wd: &WiringDiagram) -> u32 {
   0
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day25::{generator, part1};
-
-  const INPUT: &str = "jqt: rhn xhk nvd
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "jqt: rhn xhk nvd
 rsh: frs pzl lsr
 xhk: hfx
 cmg: qnr nvd lhk bvb
@@ -167,27 +110,78 @@ ntq: jqt hfx bvb xhk
 nvd: lhk
 lsr: lhk
 rzs: qnr cmg lsr rsh
-frs: qnr lhk lsr";
+frs: qnr lhk lsr",
+    part1: Some("54"),
+    part2: None,
+  },
+];
+
+/// Two groups of about half the size of components each, where the components are wired in a chain
+/// through the group with a few more random wires each, and three wires between distinct
+/// components join the groups. The solver looks for the cut among the most central components, so
+/// the components of the cut are wired to more of their group than the others.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let count = size.max(30);
+  let names = names(rng, count, 3, "abcdefghijklmnopqrstuvwxyz");
+  let split = count / 2 + rng.gen_range(0..=count / 20);
+  let mut wires = BTreeSet::new();
+  let mut cut = Vec::new();
+  for group in [0..split, split..count] {
+    let mut chain: Vec<usize> = group.clone().collect();
+    chain.shuffle(rng);
+    for w in chain.windows(2) {
+      wires.insert((w[0].min(w[1]), w[0].max(w[1])));
+    }
+    cut.push(chain[..3].to_vec());
+    for i in group.clone() {
+      let extra = if cut[cut.len() - 1].contains(&i) { group.len() / 2 } else { 2 };
+      for &j in chain.choose_multiple(rng, extra).filter(|&&j| j != i) {
+        wires.insert((i.min(j), i.max(j)));
+      }
+    }
+  }
+  wires.extend(cut[0].iter().zip(&cut[1]).map(|(&a, &b)| (a, b)));
+
+  let mut wired = vec![Vec::new(); count];
+  for (a, b) in wires {
+    let (from, to) = if rng.gen_bool(0.5) { (a, b) } else { (b, a) };
+    wired[from].push(names[to].as_str());
+  }
+  let mut lines: Vec<String> = wired.iter().enumerate()
+    .filter(|(_, to)| !to.is_empty())
+    .map(|(from, to)| format!("{}: {}", names[from], to.join(" ")))
+    .collect();
+  lines.shuffle(rng);
+  lines.join("\n")
+}
+
+solution!(Day25, WiringDiagram, u32, u32);
+
+#[cfg(test)]
+mod tests {
+  use crate::day25::{EXAMPLES, generator, part1};
+
+  const INPUT: &str = EXAMPLES[0].input;
 
   #[test]
   fn test_generator() {
-    let wd = generator(INPUT);
-    assert_eq!(15, wd.links.len());
-    assert_eq!("jqt", wd.id_map[0]);
-    assert_eq!(4, wd.links[0].len());
-    assert_eq!("rhn", wd.id_map[1]);
-    assert_eq!(4, wd.links[1].len());
+    let wd = generator(INPUT).unwrap();
+    assert_eq!(15, wd.graph.len());
+    assert_eq!("jqt", wd.name(0));
+    assert_eq!(4, wd.graph.edges(0).len());
+    assert_eq!("rhn", wd.name(1));
+    assert_eq!(4, wd.graph.edges(1).len());
   }
 
   #[test]
   fn test_part1() {
-    let wd = generator(INPUT);
+    let wd = generator(INPUT).unwrap();
     assert_eq!(part1(&wd), 54);
   }
 
   #[test]
   fn test_shortest_paths() {
-    let wd = generator(INPUT);
+    let wd = generator(INPUT).unwrap();
     //wd.find_most_used_edges();
     wd.find_closest_points();
   }
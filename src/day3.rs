@@ -1,32 +1,23 @@
 use std::collections::HashSet;
-
-#[derive(Eq, Hash, PartialEq, Copy, Clone, Debug, PartialOrd, Ord)]
-pub struct XY {
-  x: usize,
-  y: usize,
-}
-
-impl XY {
-  fn create(x: usize, y: usize) -> XY {
-    XY {
-      x,
-      y,
-    }
-  }
-}
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::geom::Point2;
+use crate::utils::grid::Grid;
+use crate::utils::random::Random;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Number {
-  start: XY,
+  start: Point2<usize>,
   chars: usize,
   value: u32,
 }
 
 impl Number {
   fn empty() -> Number {
-    Number::create(XY::create(0, 0))
+    Number::create(Point2::new(0, 0))
   }
-  fn create(start: XY) -> Number {
+  fn create(start: Point2<usize>) -> Number {
     Number {
       start,
       chars: 0,
@@ -57,21 +48,21 @@ impl Number {
 
 pub struct Schematic {
   numbers: Vec<Number>,
-  symbols: Vec<(XY, char)>,
+  symbols: Vec<(Point2<usize>, char)>,
 }
 
 pub fn part1(schematic: &Schematic) -> u32 {
-  let symbols: HashSet<XY> = schematic.symbols.iter().map(|(xy, _)| *xy).collect();
+  let symbols: HashSet<Point2<usize>> = schematic.symbols.iter().map(|(xy, _)| *xy).collect();
   schematic.numbers.iter().filter_map(|number| {
     for x in [number.start.x as i32 - 1, (number.start.x + number.chars) as i32] {
-      if x >= 0 && symbols.contains(&XY::create(x as usize, number.start.y)) {
+      if x >= 0 && symbols.contains(&Point2::new(x as usize, number.start.y)) {
         return Some(number.value);
       }
     }
     for y in [number.start.y as i32 - 1, number.start.y as i32 + 1] {
       if y >= 0 {
         for x in (number.start.x as i32 - 1)..=(number.start.x + number.chars) as i32 {
-          if x >= 0 && symbols.contains(&XY::create(x as usize, y as usize)) {
+          if x >= 0 && symbols.contains(&Point2::new(x as usize, y as usize)) {
             return Some(number.value);
           }
         }
@@ -83,14 +74,14 @@ pub fn part1(schematic: &Schematic) -> u32 {
 
 pub fn part2(schematic: &Schematic) -> u32 {
   // Retain only gear symbol
-  let gear_symbols: HashSet<XY> = schematic.symbols.iter()
+  let gear_symbols: HashSet<Point2<usize>> = schematic.symbols.iter()
     .filter_map(|(xy, c)| if c == &'*' { Some(*xy) } else { None })
     .collect();
 
   let mut potential_gear_numbers = Vec::new();
   for number in &schematic.numbers {
     for x in [number.start.x as i32 - 1, (number.start.x + number.chars) as i32] {
-      let xy = XY::create(x as usize, number.start.y);
+      let xy = Point2::new(x as usize, number.start.y);
       if x >= 0 && gear_symbols.contains(&xy) {
         potential_gear_numbers.push((xy, number.value));
       }
@@ -98,7 +89,7 @@ pub fn part2(schematic: &Schematic) -> u32 {
     for y in [number.start.y as i32 - 1, number.start.y as i32 + 1] {
       if y >= 0 {
         for x in (number.start.x as i32 - 1)..=(number.start.x + number.chars) as i32 {
-          let xy = XY::create(x as usize, y as usize);
+          let xy = Point2::new(x as usize, y as usize);
           if x >= 0 && gear_symbols.contains(&xy) {
             potential_gear_numbers.push((xy, number.value));
           }
@@ -129,13 +120,14 @@ pub fn part2(schematic: &Schematic) -> u32 {
 }
 
 
-pub fn generator(input: &str) -> Schematic {
+pub fn generator(input: &str) -> Result<Schematic, ParseError> {
   let mut symbols = Vec::new();
   let mut numbers = Vec::new();
   let mut number: Number = Number::empty();
 
-  for (row, line) in input.lines().enumerate() {
-    for (col, c) in line.chars().enumerate() {
+  let grid: Grid<char> = input.parse()?;
+  for (row, line) in grid.rows().enumerate() {
+    for (col, &c) in line.iter().enumerate() {
       match c {
         c if c.is_ascii_digit() => {
           if number.is_empty() {
@@ -154,47 +146,83 @@ pub fn generator(input: &str) -> Schematic {
             numbers.push(number);
             number.clear();
           }
-          symbols.push((XY::create(col, row), s));
+          symbols.push((Point2::new(col, row), s));
         }
       }
     }
   }
 
-  Schematic {
+  Ok(Schematic {
     numbers,
     symbols,
+  })
+}
+
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+    part1: Some("4361"),
+    part2: Some("467835"),
+  },
+];
+
+/// A square schematic of the side with numbers of up to three digits and symbols between them
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+  let side = size.max(1);
+  let mut rows = Vec::with_capacity(side);
+  for _ in 0..side {
+    let mut row = String::with_capacity(side);
+    while row.len() < side {
+      match rng.gen_range(0..10) {
+        0..=1 => {
+          let digits = rng.gen_range(1..=3).min(side - row.len());
+          row.push(char::from(b'1' + rng.gen_range(0..9)));
+          row.extend((1..digits).map(|_| char::from(b'0' + rng.gen_range(0..10))));
+          if row.len() < side {
+            row.push('.');
+          }
+        }
+        2 => row.push(char::from(*SYMBOLS.choose(rng).unwrap())),
+        _ => row.push('.'),
+      }
+    }
+    rows.push(row);
   }
+  rows.join("\n")
 }
 
+solution!(Day3, Schematic, u32, u32);
+
 #[cfg(test)]
 mod tests {
-  use crate::day3::{generator, Number, part1, part2, XY};
+  use crate::day3::{EXAMPLES, generator, Number, part1, part2};
+  use crate::utils::geom::Point2;
 
   fn input() -> String {
-    [
-      "467..114..",
-      "...*......",
-      "..35..633.",
-      "......#...",
-      "617*......",
-      ".....+.58.",
-      "..592.....",
-      "......755.",
-      "...$.*....",
-      ".664.598..",
-    ].join("\n").to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let schematic = generator(&input());
+    let schematic = generator(&input()).unwrap();
     assert_eq!(10, schematic.numbers.len());
     assert_eq!(6, schematic.symbols.len());
-    assert_eq!(Number { start: XY::create(0, 0), chars: 3, value: 467 },
+    assert_eq!(Number { start: Point2::new(0, 0), chars: 3, value: 467 },
                schematic.numbers[0]);
-    assert_eq!(Number { start: XY::create(5, 0), chars: 3, value: 114 },
+    assert_eq!(Number { start: Point2::new(5, 0), chars: 3, value: 114 },
                schematic.numbers[1]);
-    assert_eq!(Number { start: XY::create(0, 4), chars: 3, value: 617 },
+    assert_eq!(Number { start: Point2::new(0, 4), chars: 3, value: 617 },
                schematic.numbers[4]);
 
     assert_eq!('*',
@@ -205,13 +233,13 @@ mod tests {
 
   #[test]
   fn test_part1() {
-    let schematic = generator(&input());
+    let schematic = generator(&input()).unwrap();
     assert_eq!(4361, part1(&schematic));
   }
 
   #[test]
   fn test_part2() {
-    let schematic = generator(&input());
+    let schematic = generator(&input()).unwrap();
     assert_eq!(467835, part2(&schematic));
   }
 }
\ No newline at end of file
@@ -1,14 +1,11 @@
 use std::cmp::Ordering;
 use Ordering::{Less, Equal, Greater};
-
-fn extract_sorted_numbers(input: &str) -> Vec<u32> {
-  let mut numbers: Vec<u32> = input.split_whitespace()
-    .filter_map(|tok| {
-      if tok.is_empty() { None } else { Some(tok.parse().unwrap()) }
-    }).collect();
-  numbers.sort();
-  numbers
-}
+use rand::seq::index::sample;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::parse::{numbers, split_once};
+use crate::utils::random::Random;
 
 pub struct Card {
   numbers: Vec<u32>,
@@ -16,15 +13,18 @@ pub struct Card {
 }
 
 impl Card {
-  fn create(line: &str) -> Card {
-    let (_, numbers) = line.split_once(':').unwrap();
-    let (numbers, wins) = numbers.split_once('|').unwrap();
-    let numbers = extract_sorted_numbers(numbers);
-    let wins = extract_sorted_numbers(wins);
-    Card {
-      numbers,
-      wins,
-    }
+  fn create(line: &str) -> Result<Card, ParseError> {
+    let (_, all) = split_once(line, line, ":", "'Card <id>:'")?;
+    let (mine, winning) = split_once(line, all, "|", "'|' between the numbers")?;
+    let sorted = |token: &str| -> Result<Vec<u32>, ParseError> {
+      let mut values = numbers(line, token)?;
+      values.sort();
+      Ok(values)
+    };
+    Ok(Card {
+      numbers: sorted(mine)?,
+      wins: sorted(winning)?,
+    })
   }
 
   fn winning_numbers(&self) -> u32 {
@@ -48,9 +48,10 @@ impl Card {
   }
 }
 
-pub fn generator(input: &str) -> Vec<Card> {
+pub fn generator(input: &str) -> Result<Vec<Card>, ParseError> {
   input.lines()
-    .map(Card::create).collect()
+    .map(|line| Card::create(line).map_err(|e| e.within(input, line)))
+    .collect()
 }
 
 pub fn part1(cards: &[Card]) -> u32 {
@@ -73,24 +74,47 @@ pub fn part2(cards: &[Card]) -> u32 {
   final_counts.iter().sum()
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    part1: Some("13"),
+    part2: Some("30"),
+  },
+];
+
+/// Cards with ten winning numbers and 25 numbers, where few cards win so the copies stay small
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  let list = |numbers: &[usize]| numbers.iter().map(|n| format!("{n:>2}")).collect::<Vec<_>>().join(" ");
+  (1..=size).map(|id| {
+    let wins = if rng.gen_bool(0.7) { 0 } else { rng.gen_range(1..=3) };
+    // The first ten are the winning numbers, and the wins are among the numbers after them
+    let mut winning: Vec<usize> = sample(rng, 99, 35 - wins).into_iter().map(|n| n + 1).collect();
+    let mut mine = winning.split_off(10);
+    mine.extend(&winning[..wins]);
+    mine.shuffle(rng);
+    format!("Card {id:>3}: {} | {}", list(&winning), list(&mine))
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day4, Vec<Card>, u32, u32);
+
 #[cfg(test)]
 mod tests {
-  use crate::day4::{generator, part1, part2};
+  use crate::day4::{EXAMPLES, generator, part1, part2};
 
   fn input() -> String {
-    [
-      "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
-      "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
-      "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
-      "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
-      "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
-      "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
-    ].join("\n").to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let cards = generator(&input());
+    let cards = generator(&input()).unwrap();
     assert_eq!(6, cards.len());
     assert_eq!(&17, cards[0].numbers.first().unwrap());
     assert_eq!(&86, cards[0].numbers.last().unwrap());
@@ -100,19 +124,19 @@ mod tests {
 
   #[test]
   fn test_part1() {
-    let cards = generator(&input());
+    let cards = generator(&input()).unwrap();
     assert_eq!(13, part1(&cards));
   }
 
   #[test]
   fn test_part2() {
-    let cards = generator(&input());
+    let cards = generator(&input()).unwrap();
     assert_eq!(30, part2(&cards));
   }
 
   #[test]
   fn test_bound() {
-    let cards = generator("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
+    let cards = generator("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").unwrap();
     assert_eq!(1, part2(&cards));
   }
 }
\ No newline at end of file
@@ -1,13 +1,18 @@
 use std::str::Lines;
-
-pub fn generator(input: &str) -> Almanac {
-  let mut sections = input.split("\n\n");
-  let seeds_section = sections.next().unwrap();
-  let seeds = seeds_section
-    .split_whitespace()
-    .skip(1)
-    .map(|e| e.parse().unwrap())
-    .collect();
+use rand::seq::index::sample;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::checked::cast;
+use crate::utils::interval::{Interval, IntervalMap};
+use crate::utils::parse::{blocks, exactly, numbers};
+use crate::utils::random::Random;
+
+pub fn generator(input: &str) -> Result<Almanac, ParseError> {
+  let mut sections = blocks(input);
+  let seeds_section = sections.next().unwrap_or_default();
+  let seeds = seeds_section.strip_prefix("seeds:")
+    .ok_or_else(|| ParseError::at(input, seeds_section, "Expected the 'seeds:' section"))?;
+  let seeds = numbers(input, seeds)?;
 
   let mut seed_to_soil = Map::new();
   let mut soil_to_fertilizer = Map::new();
@@ -19,19 +24,20 @@ pub fn generator(input: &str) -> Almanac {
 
   for map_section in sections {
     let mut lines = map_section.lines();
-    match lines.next().unwrap() {
-      "seed-to-soil map:" => seed_to_soil.add_entries(lines),
-      "soil-to-fertilizer map:" => soil_to_fertilizer.add_entries(lines),
-      "fertilizer-to-water map:" => fertilizer_to_water.add_entries(lines),
-      "water-to-light map:" => water_to_light.add_entries(lines),
-      "light-to-temperature map:" => light_to_temp.add_entries(lines),
-      "temperature-to-humidity map:" => temp_to_humidity.add_entries(lines),
-      "humidity-to-location map:" => humidity_to_loc.add_entries(lines),
-      header => panic!("Unexpected header {}", header),
-    }
+    let map = match lines.next().unwrap_or_default() {
+      "seed-to-soil map:" => &mut seed_to_soil,
+      "soil-to-fertilizer map:" => &mut soil_to_fertilizer,
+      "fertilizer-to-water map:" => &mut fertilizer_to_water,
+      "water-to-light map:" => &mut water_to_light,
+      "light-to-temperature map:" => &mut light_to_temp,
+      "temperature-to-humidity map:" => &mut temp_to_humidity,
+      "humidity-to-location map:" => &mut humidity_to_loc,
+      header => return Err(ParseError::at(input, header, format!("Unexpected header {}", header))),
+    };
+    map.add_entries(map_section, lines).map_err(|e| e.within(input, map_section))?;
   }
 
-  Almanac {
+  Ok(Almanac {
     seeds,
     seed_to_soil,
     soil_to_fertilizer,
@@ -40,7 +46,7 @@ pub fn generator(input: &str) -> Almanac {
     light_to_temp,
     temp_to_humidity,
     humidity_to_loc,
-  }
+  })
 }
 
 struct Entry {
@@ -50,100 +56,54 @@ struct Entry {
 }
 
 impl Entry {
-  fn create(input: &str) -> Entry {
-    let mut values = input.split_whitespace();
-    Entry {
-      dst: values.next().unwrap().parse().unwrap(),
-      src: values.next().unwrap().parse().unwrap(),
-      range: values.next().unwrap().parse().unwrap(),
-    }
+  fn create(input: &str) -> Result<Entry, ParseError> {
+    let [dst, src, range] = exactly(input, input, numbers(input, input)?)?;
+    Ok(Entry { dst, src, range })
   }
 }
 
+/// Moves the values in the source ranges of the entries, leaving every other value as it is
 struct Map {
-  entries: Vec<Entry>,
+  map: IntervalMap<u64>,
 }
 
 impl Map {
   fn new() -> Map {
     Map {
-      entries: Vec::new()
+      map: IntervalMap::new()
     }
   }
-  fn add_entries(&mut self, lines: Lines) {
+
+  /// Add the entry, or give back the source of an entry it overlaps
+  fn add(&mut self, entry: &Entry) -> Result<(), Interval<u64>> {
+    self.map.insert(Interval::from_len(entry.src as u64, entry.range as u64), entry.dst as u64)
+  }
+
+  /// Add the entries from the lines, which are a slice of the section.
+  fn add_entries(&mut self, section: &str, lines: Lines) -> Result<(), ParseError> {
     for line in lines {
-      self.entries.push(Entry::create(line));
+      let entry = Entry::create(line).map_err(|e| e.within(section, line))?;
+      self.add(&entry).map_err(|s| ParseError::at(section, line, format!(
+        "The source overlaps the entry with source {} and range {}", s.start, s.len())))?;
     }
-
-    self.entries.sort_by_key(|e| e.src);
+    Ok(())
   }
 
   fn destination(&self, src: &u32) -> u32 {
-    match self.entries.binary_search_by_key(src, |e| e.src) {
-      Ok(i) => self.entries[i].dst,
-      Err(i) if i > 0 => {
-        let prev = &self.entries[i - 1];
-        match src - prev.src {
-          i if i < prev.range => prev.dst + i,
-          _ => *src
-        }
-      }
-      Err(_) => *src,
-    }
+    cast(self.map.get(*src as u64))
   }
 
+  /// The (start, length) ranges the source ranges go to, in the order of their pieces
   fn dest_ranges(&self, src_ranges: &[(u32, u32)]) -> Vec<(u32, u32)> {
-    let mut stack: Vec<(u32, u32)> = src_ranges.to_vec();
-    let mut dest_ranges: Vec<(u32, u32)> = Vec::new();
-    while !stack.is_empty() {
-      let (mut src, mut range) = stack.pop().unwrap();
-      match self.entries.binary_search_by_key(&src, |e| e.src) {
-        Ok(i) => {
-          match range as i32 - self.entries[i].range as i32 {
-            v if v.is_negative() || v == 0 => {
-              dest_ranges.push((self.entries[i].dst, range));
-            }
-            v => {
-              dest_ranges.push((self.entries[i].dst, self.entries[i].range));
-              stack.push((self.entries[i].src + self.entries[i].range, v as u32));
-            }
-          }
-        }
-        Err(i) => {
-          if i > 0 {
-            // handle over with previous
-            let prev = &self.entries[i - 1];
-            match src - prev.src {
-              v if v < prev.range => {
-                dest_ranges.push((prev.dst + v, range.min(prev.range - v)));
-                src = prev.src + prev.range;
-                range -= range.min(prev.range - v);
-              }
-              _ => {}
-            }
-          }
-          if i < self.entries.len() && range > 0 {
-            // handle overlap with next
-            let next = &self.entries[i];
-            match next.src - src {
-              v if v < range => {
-                dest_ranges.push((src, v));
-                src += v;
-                range -= v;
-                stack.push((src, range));
-              }
-              _ => {
-                dest_ranges.push((src, range));
-              }
-            }
-          }
-          if i == self.entries.len() && range > 0 {
-            dest_ranges.push((src, range));
-          }
-        }
-      }
-    }
-    dest_ranges
+    src_ranges.iter()
+      .flat_map(|(start, len)| self.map.map_interval(Interval::from_len(*start as u64, *len as u64)))
+      .map(|i| (cast(i.start), cast(i.len())))
+      .collect()
+  }
+
+  /// The map that moves the values by this map and then by the next
+  fn then(&self, next: &Map) -> Map {
+    Map { map: self.map.then(&next.map) }
   }
 }
 
@@ -159,40 +119,22 @@ pub struct Almanac {
 }
 
 impl Almanac {
-  fn seed_to_loc(&self, seed: &u32) -> u32 {
-    self.humidity_to_loc.destination(
-      &self.temp_to_humidity.destination(
-        &self.light_to_temp.destination(
-          &self.water_to_light.destination(
-            &self.fertilizer_to_water.destination(
-              &self.soil_to_fertilizer.destination(
-                &self.seed_to_soil.destination(seed))
-            )
-          )
-        )
-      )
-    )
-  }
-
-  fn seed_ranges_to_loc_ranges(&self, seed_ranges: &[(u32, u32)]) -> Vec<(u32, u32)> {
-    self.humidity_to_loc.dest_ranges(
-      &self.temp_to_humidity.dest_ranges(
-        &self.light_to_temp.dest_ranges(
-          &self.water_to_light.dest_ranges(
-            &self.fertilizer_to_water.dest_ranges(
-              &self.soil_to_fertilizer.dest_ranges(
-                &self.seed_to_soil.dest_ranges(seed_ranges))
-            )
-          )
-        )
-      )
-    )
+  /// The seven maps composed into one from seed to location
+  fn seed_to_loc(&self) -> Map {
+    self.seed_to_soil
+      .then(&self.soil_to_fertilizer)
+      .then(&self.fertilizer_to_water)
+      .then(&self.water_to_light)
+      .then(&self.light_to_temp)
+      .then(&self.temp_to_humidity)
+      .then(&self.humidity_to_loc)
   }
 }
 
 pub fn part1(almanac: &Almanac) -> u32 {
+  let seed_to_loc = almanac.seed_to_loc();
   almanac.seeds.iter().map(|s| {
-    almanac.seed_to_loc(s)
+    seed_to_loc.destination(s)
   }).min().unwrap()
 }
 
@@ -201,19 +143,17 @@ pub fn part2(almanac: &Almanac) -> u32 {
   for i in 0..almanac.seeds.len() / 2 {
     seed_ranges.push((almanac.seeds[2 * i], almanac.seeds[(2 * i) + 1]));
   }
-  almanac.seed_ranges_to_loc_ranges(&seed_ranges)
+  almanac.seed_to_loc().dest_ranges(&seed_ranges)
     .iter()
     .min_by_key(|e| e.0)
     .unwrap()
     .0
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day5::{Entry, generator, Map, part1, part2};
-
-  fn input() -> String {
-    "seeds: 79 14 55 13
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -245,42 +185,112 @@ temperature-to-humidity map:
 
 humidity-to-location map:
 60 56 37
-56 93 4".to_string()
+56 93 4",
+    part1: Some("35"),
+    part2: Some("46"),
+  },
+];
+
+/// Up to ten seed ranges and maps of the size, whose sources split the numbers without overlaps
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  const LIMIT: u32 = 4_000_000_000;
+  const HEADERS: [&str; 7] = ["seed-to-soil", "soil-to-fertilizer", "fertilizer-to-water", "water-to-light",
+    "light-to-temperature", "temperature-to-humidity", "humidity-to-location"];
+  let seeds: Vec<String> = (0..rng.gen_range(1..=10)).map(|_| {
+    let len = rng.gen_range(1..=LIMIT / 10);
+    format!("{} {len}", rng.gen_range(0..=LIMIT - len))
+  }).collect();
+  let mut sections = vec![format!("seeds: {}", seeds.join(" "))];
+  for header in HEADERS {
+    let mut cuts: Vec<u32> = sample(rng, LIMIT as usize - 1, size.max(1)).into_iter()
+      .map(|c| c as u32 + 1)
+      .collect();
+    cuts.push(0);
+    cuts.push(LIMIT);
+    cuts.sort();
+    // Some of the pieces between the cuts are left out, so they map to themselves, but an empty
+    // section would read as a missing one
+    let kept = rng.gen_range(0..cuts.len() - 1);
+    let mut entries = Vec::new();
+    for (i, w) in cuts.windows(2).enumerate() {
+      if i == kept || rng.gen_bool(0.8) {
+        let len = w[1] - w[0];
+        entries.push(format!("{} {} {len}", rng.gen_range(0..=LIMIT - len), w[0]));
+      }
+    }
+    sections.push(format!("{header} map:\n{}", entries.join("\n")));
+  }
+  sections.join("\n\n")
+}
+
+solution!(Day5, Almanac, u32, u32);
+
+#[cfg(test)]
+mod tests {
+  use proptest::collection::{btree_set, vec};
+  use proptest::prelude::*;
+  use crate::day5::{Entry, EXAMPLES, generator, Map, part1, part2, random_input};
+  use crate::utils::random::seeded;
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let a = generator(&input());
+    let a = generator(&input()).unwrap();
     assert_eq!(a.seeds.len(), 4);
-    assert_eq!(a.seed_to_soil.entries.len(), 2);
-    assert_eq!(a.soil_to_fertilizer.entries.len(), 3);
-    assert_eq!(a.fertilizer_to_water.entries.len(), 4);
-    assert_eq!(a.water_to_light.entries.len(), 2);
-    assert_eq!(a.light_to_temp.entries.len(), 3);
-    assert_eq!(a.temp_to_humidity.entries.len(), 2);
-    assert_eq!(a.humidity_to_loc.entries.len(), 2);
+    assert_eq!(a.seed_to_soil.map.len(), 2);
+    assert_eq!(a.soil_to_fertilizer.map.len(), 3);
+    assert_eq!(a.fertilizer_to_water.map.len(), 4);
+    assert_eq!(a.water_to_light.map.len(), 2);
+    assert_eq!(a.light_to_temp.map.len(), 3);
+    assert_eq!(a.temp_to_humidity.map.len(), 2);
+    assert_eq!(a.humidity_to_loc.map.len(), 2);
+  }
+
+  #[test]
+  fn test_generator_errors() {
+    let e = generator("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n\nbogus map:\n1 2 3").err().unwrap();
+    assert_eq!((6, 1), (e.line, e.column));
+    assert_eq!("Unexpected header bogus map:", e.message);
+
+    let e = generator("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 x 48").err().unwrap();
+    assert_eq!((5, 4), (e.line, e.column));
+
+    let e = generator("seeds: 79 14 55 13\n\nseed-to-soil map:\n50 98 2\n52 50 48\n60 90 20").err().unwrap();
+    assert_eq!((6, 1), (e.line, e.column));
+    assert_eq!("The source overlaps the entry with source 50 and range 48", e.message);
+  }
+
+  #[test]
+  fn test_random_input() {
+    for size in 0..4 {
+      for seed in 0..20 {
+        let input = random_input(&mut seeded(seed), size);
+        let almanac = generator(&input).unwrap_or_else(|e| panic!("size {size} and seed {seed}: {e}"));
+        assert!(!almanac.seed_to_soil.map.is_empty() && !almanac.humidity_to_loc.map.is_empty());
+      }
+    }
   }
 
   #[test]
   fn test_part1() {
-    let a = generator(&input());
+    let a = generator(&input()).unwrap();
     assert_eq!(35, part1(&a));
   }
 
   #[test]
   fn test_part2() {
-    let a = generator(&input());
+    let a = generator(&input()).unwrap();
     assert_eq!(46, part2(&a));
   }
 
   #[test]
   fn test_dest_ranges() {
-    let m = Map {
-      entries: vec![
-        Entry { src: 5, dst: 50, range: 6 },
-        Entry { src: 15, dst: 150, range: 10 },
-      ],
-    };
+    let mut m = Map::new();
+    m.add(&Entry { src: 5, dst: 50, range: 6 }).unwrap();
+    m.add(&Entry { src: 15, dst: 150, range: 10 }).unwrap();
 
     // over and beyond the existing range
     let dest_ranges = m.dest_ranges(&[(1, 30)]);
@@ -306,4 +316,22 @@ humidity-to-location map:
     let dest_ranges = m.dest_ranges(&[(13, 3)]);
     assert_eq!(vec![(13, 2), (150, 1)], dest_ranges);
   }
-}
\ No newline at end of file
+
+  proptest! {
+    #[test]
+    fn test_dest_ranges_properties(cuts in btree_set(0..2000_u32, 2..12), dsts in vec(0..3000_u32, 12),
+                                   keep in vec(any::<bool>(), 12), ranges in vec((0..2500_u32, 1..200_u32), 1..5)) {
+      let cuts: Vec<u32> = cuts.into_iter().collect();
+      let mut m = Map::new();
+      for (i, w) in cuts.windows(2).enumerate().filter(|(i, _)| keep[*i]) {
+        m.add(&Entry { src: w[0], dst: dsts[i], range: w[1] - w[0] }).unwrap();
+      }
+      let dest_ranges = m.dest_ranges(&ranges);
+      // The pieces hold as many values as the ranges, each where the map sends it
+      prop_assert_eq!(ranges.iter().map(|(_, len)| len).sum::<u32>(),
+                      dest_ranges.iter().map(|(_, len)| len).sum::<u32>());
+      let expected = ranges.iter().flat_map(|(start, len)| *start..start + len).map(|v| m.destination(&v));
+      prop_assert!(dest_ranges.iter().flat_map(|(start, len)| *start..start + len).eq(expected));
+    }
+  }
+}
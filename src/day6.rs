@@ -1,3 +1,9 @@
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::checked::{add, mul, sub};
+use crate::utils::parse::{numbers, split_once};
+use crate::utils::random::Random;
+
 pub struct Race {
   time: u64,
   dist: u64,
@@ -5,35 +11,51 @@ pub struct Race {
 
 impl Race {
   fn min_dist(&self) -> u64 {
-    self.dist + 1
+    add(self.dist, 1)
   }
 
-  fn range(&self) -> (u64, u64) {
-    let sqrt = (((self.time * self.time) - (4 * self.min_dist())) as f64).sqrt();
-    let low = ((self.time as f64 - sqrt) / 2_f64).ceil() as u64;
-    let high = ((self.time as f64 + sqrt) / 2_f64).floor() as u64;
-    (low, high)
+  fn wins(&self, hold: u64) -> bool {
+    hold <= self.time && hold * (self.time - hold) >= self.min_dist()
   }
-}
 
-pub fn line_split_whitespace(input: &str) -> Vec<u64> {
-  let (_, v) = input.split_once(':').unwrap();
-  v.split_whitespace()
-    .filter_map(|e| if e.is_empty() { None } else { Some(e.parse().unwrap()) }).collect()
+  /// The shortest and longest holds that win, found with the integer square root so large races stay exact
+  fn range(&self) -> (u64, u64) {
+    let sqrt = sub(mul(self.time, self.time), mul(4, self.min_dist())).isqrt();
+    // The rounded down root can put the estimate a hold away from the first win
+    let mut low = (self.time - sqrt) / 2;
+    while !self.wins(low) {
+      low += 1;
+    }
+    while low > 0 && self.wins(low - 1) {
+      low -= 1;
+    }
+    // The distance is symmetric around half the time
+    (low, self.time - low)
+  }
 }
 
-pub fn generator(input: &str) -> Vec<Race> {
+pub fn generator(input: &str) -> Result<Vec<Race>, ParseError> {
   let mut lines = input.lines();
-  let times: Vec<u64> = line_split_whitespace(lines.next().unwrap());
-  let distances: Vec<u64> = line_split_whitespace(lines.next().unwrap());
-  assert_eq!(times.len(), distances.len());
+  let (Some(time_line), Some(dist_line)) = (lines.next(), lines.next()) else {
+    return Err(ParseError::at(input, input, "Expected a time and a distance line"));
+  };
+  let values = |line: &str| -> Result<Vec<u64>, ParseError> {
+    let (_, values) = split_once(input, line, ":", "'<name>: <values>'")?;
+    numbers(input, values)
+  };
+  let times = values(time_line)?;
+  let distances = values(dist_line)?;
+  if times.len() != distances.len() {
+    return Err(ParseError::at(input, dist_line,
+                              format!("Expected {} distances but found {}", times.len(), distances.len())));
+  }
 
-  times.iter().zip(distances).map(|(&t, d)| {
+  Ok(times.iter().zip(distances).map(|(&t, d)| {
     Race {
       time: t,
       dist: d,
     }
-  }).collect()
+  }).collect())
 }
 
 pub fn part1(races: &[Race]) -> u64 {
@@ -47,28 +69,59 @@ pub fn part2(races: &[Race]) -> u64 {
   let mut dist = 0_u64;
   let mut time = 0_u64;
   for race in races {
-    dist *= 10_u64.pow(race.dist.checked_ilog10().unwrap_or(0) + 1);
-    dist += race.dist;
-    time *= 10_u64.pow(race.time.checked_ilog10().unwrap_or(0) + 1);
-    time += race.time;
+    dist = add(mul(dist, 10_u64.pow(race.dist.checked_ilog10().unwrap_or(0) + 1)), race.dist);
+    time = add(mul(time, 10_u64.pow(race.time.checked_ilog10().unwrap_or(0) + 1)), race.time);
   }
   let race = Race { time, dist };
   let (l, h) = race.range();
   h - l + 1
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "Time:      7  15   30
+Distance:  9  40  200",
+    part1: Some("288"),
+    part2: Some("71503"),
+  },
+];
+
+/// Up to four races that can each be won, where joining them also gives a race that can be won
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  loop {
+    let races: Vec<(u64, u64)> = (0..size.clamp(1, 4)).map(|_| {
+      let time = rng.gen_range(7..100);
+      (time, rng.gen_range(0..(time / 2) * (time - time / 2)))
+    }).collect();
+    let joined = |value: fn(&(u64, u64)) -> u64| races.iter()
+      .map(|r| value(r).to_string())
+      .collect::<String>()
+      .parse::<u64>()
+      .unwrap();
+    let (time, dist) = (joined(|r| r.0), joined(|r| r.1));
+    if (time / 2) * (time - time / 2) > dist {
+      let line = |value: fn(&(u64, u64)) -> u64| races.iter()
+        .map(|r| format!("{:>5}", value(r)))
+        .collect::<String>();
+      return format!("Time:    {}\nDistance:{}", line(|r| r.0), line(|r| r.1));
+    }
+  }
+}
+
+solution!(Day6, Vec<Race>, u64, u64);
+
 #[cfg(test)]
 mod tests {
-  use crate::day6::{generator, part1, part2, Race};
+  use crate::day6::{EXAMPLES, generator, part1, part2, Race};
 
   fn input() -> String {
-    "Time:      7  15   30
-Distance:  9  40  200".to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let races = generator(&input());
+    let races = generator(&input()).unwrap();
     assert_eq!(3, races.len());
     assert_eq!(races[0].time, 7);
     assert_eq!(races[0].dist, 9);
@@ -82,17 +135,19 @@ Distance:  9  40  200".to_string()
     assert_eq!((2, 5), Race { time: 7, dist: 9 }.range());
     assert_eq!((4, 11), Race { time: 15, dist: 40 }.range());
     assert_eq!((11, 19), Race { time: 30, dist: 200 }.range());
+    // The discriminant is past the precision of f64 here, which gave (1000, 3_999_999_000)
+    assert_eq!((1001, 3_999_998_999), Race { time: 4_000_000_000, dist: 3_999_999_000_000 }.range());
   }
 
   #[test]
   fn test_part1() {
-    let races = generator(&input());
+    let races = generator(&input()).unwrap();
     assert_eq!(288, part1(&races));
   }
 
   #[test]
   fn test_part2() {
-    let races = generator(&input());
+    let races = generator(&input()).unwrap();
     assert_eq!(71503, part2(&races));
   }
 }
\ No newline at end of file
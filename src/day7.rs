@@ -1,8 +1,12 @@
 use std::cmp::Ordering;
 use std::cmp::Ordering::Equal;
 use std::collections::HashMap;
+use rand::seq::SliceRandom;
+use rand::Rng;
 use crate::day7::Card::{A, J, K, N, Q, T};
 use crate::day7::HandType::{FiveKind, FourKind, FullHouse, HighCard, OnePair, ThreeKind, TwoPair};
+use crate::utils::{parse_at, solution, Example, ParseError};
+use crate::utils::random::Random;
 
 #[derive(PartialOrd, PartialEq, Ord, Eq, Copy, Clone, Debug, Hash)]
 pub enum Card {
@@ -15,15 +19,15 @@ pub enum Card {
 }
 
 impl Card {
-  fn from(c: char) -> Card {
+  fn from(c: char) -> Option<Card> {
     match c {
-      'A' => A,
-      'K' => K,
-      'Q' => Q,
-      'J' => J,
-      'T' => T,
-      c if c.is_ascii_digit() => N(c.to_digit(10).unwrap() as u8),
-      _ => panic!("Unexpected card character {c}"),
+      'A' => Some(A),
+      'K' => Some(K),
+      'Q' => Some(Q),
+      'J' => Some(J),
+      'T' => Some(T),
+      c if c.is_ascii_digit() => c.to_digit(10).map(|d| N(d as u8)),
+      _ => None,
     }
   }
 }
@@ -40,7 +44,7 @@ pub enum HandType {
 }
 
 impl HandType {
-  fn determine(cards: &Vec<Card>) -> HandType {
+  fn determine(cards: &[Card]) -> HandType {
     let mut counts: HashMap<Card, u8> = HashMap::new();
     for card in cards {
       counts.entry(*card)
@@ -90,16 +94,21 @@ pub struct Hand {
 }
 
 impl Hand {
-  fn create(hand: &str) -> Hand {
-    let (hand, bid) = hand.split_once(' ').unwrap();
-    let cards = hand.chars()
-      .map(Card::from)
-      .collect();
-    Hand {
+  fn create(line: &str) -> Result<Hand, ParseError> {
+    let (hand, bid) = line.split_once(' ')
+      .ok_or_else(|| ParseError::at(line, line, "Expected '<cards> <bid>'"))?;
+    let cards: Vec<Card> = hand.char_indices()
+      .map(|(i, c)| Card::from(c)
+        .ok_or_else(|| ParseError::at(line, &hand[i..], format!("Unexpected card character {c}"))))
+      .collect::<Result<_, _>>()?;
+    if cards.len() != 5 {
+      return Err(ParseError::at(line, hand, format!("Expected 5 cards but found {}", cards.len())));
+    }
+    Ok(Hand {
       hand_type: HandType::determine(&cards),
       cards,
-      bid: bid.parse().unwrap(),
-    }
+      bid: parse_at(line, bid)?,
+    })
   }
 
   fn jokers(&self) -> u8 {
@@ -134,9 +143,10 @@ impl Ord for Hand {
   }
 }
 
-pub fn generator(input: &str) -> Vec<Hand> {
+pub fn generator(input: &str) -> Result<Vec<Hand>, ParseError> {
   input.lines()
-    .map(Hand::create).collect()
+    .map(|line| Hand::create(line).map_err(|e| e.within(input, line)))
+    .collect()
 }
 
 pub fn part1(hands: &[Hand]) -> u32 {
@@ -167,11 +177,40 @@ pub fn part2(hands: &[Hand]) -> u32 {
     .sum()
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483",
+    part1: Some("6440"),
+    part2: Some("5905"),
+  },
+];
+
+/// Hands from a few labels each, so every type of hand turns up, with bids of up to 1000
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  const LABELS: &[u8] = b"23456789TJQKA";
+  (0..size).map(|_| {
+    let count = rng.gen_range(1..=5);
+    let labels: Vec<&u8> = LABELS.choose_multiple(rng, count).collect();
+    let hand: String = (0..5).map(|_| char::from(**labels.choose(rng).unwrap())).collect();
+    format!("{hand} {}", rng.gen_range(1..=1000))
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day7, Vec<Hand>, u32, u32);
+
 #[cfg(test)]
 mod tests {
-  use std::cmp::Ordering::{Greater, Less};
+  use std::cmp::Ordering::{Equal, Greater, Less};
+  use proptest::collection::vec;
+  use proptest::prelude::*;
+  use proptest::sample::select;
   use crate::day7::Card::{A, K, N, Q};
-  use crate::day7::{generator, Hand, part1, part2};
+  use crate::day7::{EXAMPLES, generator, Hand, part1, part2};
   use crate::day7::HandType::{FiveKind, FourKind, FullHouse, HighCard, OnePair, ThreeKind, TwoPair};
 
   #[test]
@@ -182,57 +221,53 @@ mod tests {
   }
 
   fn input() -> String {
-    "32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483".to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let hands = generator(&input());
+    let hands = generator(&input()).unwrap();
     assert_eq!(5, hands.len());
   }
 
   #[test]
   fn test_hand_types() {
-    let hand = Hand::create("32T3K 765");
+    let hand = Hand::create("32T3K 765").unwrap();
     assert_eq!(OnePair, hand.hand_type);
 
-    let hand = Hand::create("KK677 28");
+    let hand = Hand::create("KK677 28").unwrap();
     assert_eq!(TwoPair, hand.hand_type);
 
-    let hand = Hand::create("QQQJA 483");
+    let hand = Hand::create("QQQJA 483").unwrap();
     assert_eq!(ThreeKind, hand.hand_type);
 
-    let hand = Hand::create("QQQAA 483");
+    let hand = Hand::create("QQQAA 483").unwrap();
     assert_eq!(FullHouse, hand.hand_type);
 
-    let hand = Hand::create("QQQAQ 483");
+    let hand = Hand::create("QQQAQ 483").unwrap();
     assert_eq!(FourKind, hand.hand_type);
 
-    let hand = Hand::create("QQQQQ 483");
+    let hand = Hand::create("QQQQQ 483").unwrap();
     assert_eq!(FiveKind, hand.hand_type);
 
-    let hand = Hand::create("T13AQ 483");
+    let hand = Hand::create("T13AQ 483").unwrap();
     assert_eq!(HighCard, hand.hand_type);
   }
 
   #[test]
   fn test_compare() {
-    let h1 = Hand::create("T55J5 684");
-    let h2 = Hand::create("QQQJA 483");
+    let h1 = Hand::create("T55J5 684").unwrap();
+    let h2 = Hand::create("QQQJA 483").unwrap();
     assert_eq!(h1.cmp(&h2), Less);
 
-    let hands = generator(&input());
+    let hands = generator(&input()).unwrap();
     let mut items = hands.clone();
     items.sort();
 
     assert_eq!(N(2).cmp(&N(3)), Less);
 
-    let h1 = Hand::create("KK677 28");
-    let h2 = Hand::create("KTJJT 220");
+    let h1 = Hand::create("KK677 28").unwrap();
+    let h2 = Hand::create("KTJJT 220").unwrap();
     assert_eq!(h1.cmp(&h2), Greater);
 
     //assert_eq!(items[0].hand_type, HighCard);
@@ -240,13 +275,35 @@ QQQJA 483".to_string()
 
   #[test]
   fn test_part1() {
-    let hands = generator(&input());
+    let hands = generator(&input()).unwrap();
     assert_eq!(6440, part1(&hands));
   }
 
   #[test]
   fn test_part2() {
-    let hands = generator(&input());
+    let hands = generator(&input()).unwrap();
     assert_eq!(5905, part2(&hands));
   }
-}
\ No newline at end of file
+
+  /// The cards of a hand, from a few labels so that the hands often share cards and types
+  fn cards() -> impl Strategy<Value = String> {
+    vec(select(vec!['2', '3', '9', 'T', 'J', 'A']), 5).prop_map(|cards| cards.into_iter().collect())
+  }
+
+  proptest! {
+    #[test]
+    fn test_hand_order_properties(cards in vec(cards(), 1..8)) {
+      let hands: Vec<Hand> = cards.iter().map(|c| Hand::create(&format!("{c} 1")).unwrap()).collect();
+      for (a, a_cards) in hands.iter().zip(&cards) {
+        for (b, b_cards) in hands.iter().zip(&cards) {
+          prop_assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{} and {}", a_cards, b_cards);
+          prop_assert_eq!(a.cmp(b) == Equal, a == b, "{} and {}", a_cards, b_cards);
+          for (c, c_cards) in hands.iter().zip(&cards) {
+            prop_assert!(a > b || b > c || a <= c, "{} <= {} <= {} but not {} <= {}",
+                         a_cards, b_cards, c_cards, a_cards, c_cards);
+          }
+        }
+      }
+    }
+  }
+}
@@ -1,170 +1,155 @@
-use std::collections::{HashMap};
 use num::integer::lcm;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::cycle::{brent, Cycle};
+use crate::utils::intern::{Interner, Symbol};
+use crate::utils::parse::split_once;
+use crate::utils::random::{names, Random};
 
-#[derive(Eq, PartialEq, Debug)]
-struct Cycle {
-  deltas: Vec<u32>,
-  to_start: u32,
-}
+pub mod reference;
 
-struct CycleItr {
-  deltas: Vec<u32>,
-  to_start: u32,
-  curr_idx: usize,
-  curr_value: u64,
+/// The steps at which a ghost stands on a node ending in Z, up to the end of its first cycle
+#[derive(Eq, PartialEq, Debug)]
+struct Ghost {
+  cycle: Cycle,
+  z_steps: Vec<u64>,
 }
 
-impl CycleItr {
-  fn from(cycle: &Cycle) -> CycleItr {
-    CycleItr {
-      deltas: cycle.deltas.clone(),
-      to_start: cycle.to_start,
-      curr_idx: 0,
-      curr_value: 0,
-    }
-  }
-}
-
-impl Iterator for CycleItr {
-  type Item = u64;
-
-  fn next(&mut self) -> Option<Self::Item> {
-    if self.curr_idx < self.deltas.len() {
-      self.curr_value += self.deltas[self.curr_idx] as u64;
-    } else {
-      self.curr_value += self.to_start as u64;
-    }
-    self.curr_idx = (self.curr_idx + 1) % self.deltas.len();
-    Some(self.curr_value)
+impl Ghost {
+  /// Every step at which the ghost stands on a node ending in Z, repeating the ones in the cycle
+  fn z_steps(&self) -> impl Iterator<Item = u64> + '_ {
+    let in_cycle: Vec<u64> = self.z_steps.iter().copied()
+      .filter(|s| *s >= self.cycle.start as u64)
+      .collect();
+    assert!(!in_cycle.is_empty(), "The ghost never returns to a node ending in Z");
+    let len = self.cycle.len as u64;
+    self.z_steps.iter().copied()
+      .chain((1..).flat_map(move |n| in_cycle.clone().into_iter().map(move |s| s + n * len)))
   }
 }
 
 pub struct Document {
   instructions: Vec<char>,
-  network: HashMap<String, (String, String)>,
+  names: Interner,
+  /// The left and right node of each node, indexed by its id
+  network: Vec<(Symbol, Symbol)>,
 }
 
 impl Document {
-  fn identify_cycle(&self, start: &String) -> Cycle {
-    let mut instructs = self.instructions.iter()
-      .enumerate().cycle();
-    let mut deltas: Vec<u32> = Vec::new();
-    let mut first: Option<(&String, usize)> = None;
-
-    let mut curr = start;
-    let mut steps = 0;
-    loop {
-      let (idx, instruct) = instructs.next().unwrap();
-      steps += 1;
+  fn next(&self, node: Symbol, instruct: char) -> Symbol {
+    match (self.network[node as usize], instruct) {
+      ((l, _), 'L') => l,
+      ((_, r), 'R') => r,
+      _ => panic!("Unexpected")
+    }
+  }
 
-      let next = match (self.network.get(curr).unwrap(), instruct) {
-        ((l, _), 'L') => {
-          l
-        }
-        ((_, r), 'R') => {
-          r
-        }
-        _ => panic!("Unexpected")
-      };
+  /// A ghost's state is its node and the index of its next instruction
+  fn step(&self, (node, idx): &(Symbol, usize)) -> (Symbol, usize) {
+    (self.next(*node, self.instructions[*idx]), (idx + 1) % self.instructions.len())
+  }
 
-      match next.ends_with('Z') {
-        true if first.is_none() => {
-          first = Some((curr, idx));
-          deltas.push(steps);
-        }
-        true if first == Some((curr, idx)) => {
-          return Cycle { to_start: steps - deltas.iter().sum::<u32>(), deltas };
-        }
-        true => {
-          deltas.push(steps - deltas.last().unwrap());
-        }
-        _ => {}
+  fn identify_cycle(&self, start: Symbol) -> Ghost {
+    let cycle = brent((start, 0), |s| self.step(s));
+    let mut state = (start, 0);
+    let mut z_steps = Vec::new();
+    for steps in 0..cycle.start + cycle.len {
+      if self.names.name(state.0).ends_with('Z') {
+        z_steps.push(steps as u64);
       }
-      curr = next;
+      state = self.step(&state);
     }
+    Ghost { cycle, z_steps }
   }
 }
 
-pub fn generator(input: &str) -> Document {
-  let (instructions, network) = input.split_once("\n\n").unwrap();
-  let network = network.lines()
+pub fn generator(input: &str) -> Result<Document, ParseError> {
+  let (instructions, network) = split_once(input, input, "\n\n",
+                                          "instructions and network separated by a blank line")?;
+  if let Some(pos) = instructions.find(|c| c != 'L' && c != 'R') {
+    return Err(ParseError::at(input, &instructions[pos..], "Expected only 'L' or 'R' instructions"));
+  }
+  let nodes: Vec<(&str, &str, &str)> = network.lines()
     .map(|line| {
-      let (node, lr) = line.split_once(" = ").unwrap();
-      let mut lr = lr.chars();
-      lr.next();
-      lr.next_back();
-      let (l, r) = lr.as_str().split_once(", ").unwrap();
-      (node.to_string(), (l.to_string(), r.to_string()))
-    }).collect();
+      let (node, lr) = split_once(input, line, " = ", "'<node> = (<left>, <right>)'")?;
+      let (l, r) = lr.strip_prefix('(')
+        .and_then(|lr| lr.strip_suffix(')'))
+        .and_then(|lr| lr.split_once(", "))
+        .ok_or_else(|| ParseError::at(input, lr, "Expected '(<left>, <right>)'"))?;
+      Ok((node, l, r))
+    }).collect::<Result<_, ParseError>>()?;
+  let mut names = Interner::new();
+  for (node, _, _) in &nodes {
+    // The ids must follow the lines so they index the network
+    if names.get(node).is_some() {
+      return Err(ParseError::at(input, node, format!("Duplicate node {node}")));
+    }
+    names.intern(node);
+  }
+  let id = |n: &str| names.get(n)
+    .ok_or_else(|| ParseError::at(input, n, format!("Unknown node {n}")));
+  let network = nodes.iter()
+    .map(|(_, l, r)| Ok((id(l)?, id(r)?)))
+    .collect::<Result<_, ParseError>>()?;
 
-  Document {
+  Ok(Document {
     instructions: instructions.chars().collect(),
+    names,
     network,
-  }
+  })
 }
 
 pub fn part1(document: &Document) -> u32 {
-  let mut curr = &"AAA".to_string();
+  let mut curr = document.names.get("AAA").unwrap();
+  let end = document.names.get("ZZZ").unwrap();
   let mut steps = 0;
   let mut instructs = document.instructions.iter().cycle();
-  while curr != "ZZZ" {
+  while curr != end {
     steps += 1;
-    match (document.network.get(curr).unwrap(), instructs.next().unwrap()) {
-      ((l, _), 'L') => curr = l,
-      ((_, r), 'R') => curr = r,
-      _ => panic!("Unexpected")
-    }
+    curr = document.next(curr, *instructs.next().unwrap());
   }
 
   steps
 }
 
 pub fn part2(document: &Document) -> u64 {
-  let cycles: Vec<Cycle> = document.network.keys()
-    .filter_map(|c| {
-      if c.ends_with('A') {
-        Some(document.identify_cycle(c))
+  let ghosts: Vec<Ghost> = document.names.iter()
+    .filter_map(|(id, name)| {
+      if name.ends_with('A') {
+        Some(document.identify_cycle(id))
       } else {
         None
       }
     }).collect();
 
-  if cycles.iter().all(|c| {
-    (c.deltas.len() == 1 || c.deltas.iter().all(|o| o == c.deltas.first().unwrap()))
-      && c.deltas.first().unwrap() == &c.to_start
-  }) {
-    cycles.iter().fold(1_u64, |a, c| {
-      lcm(a, c.to_start as u64)
+  if ghosts.iter().all(|g| g.z_steps == [g.cycle.len as u64] && g.cycle.start <= g.cycle.len) {
+    // Each ghost is on a Z node exactly at the multiples of its cycle length
+    ghosts.iter().fold(1_u64, |a, g| {
+      lcm(a, g.cycle.len as u64)
     })
   } else {
-    let mut curr_max = 0_u64;
-    let mut itrs: Vec<CycleItr> = cycles.iter().map(|c| {
-      let mut i = CycleItr::from(c);
-      i.next();
-      curr_max = curr_max.max(i.curr_value);
-      i
-    }).collect();
-
-    let mut moved = 1;
-    while moved > 0 {
-      moved = 0;
+    let mut itrs: Vec<_> = ghosts.iter().map(|g| g.z_steps().peekable()).collect();
+    loop {
+      let curr_max = itrs.iter_mut().map(|i| *i.peek().unwrap()).max().unwrap();
+      let mut all_at_max = true;
       for itr in itrs.iter_mut() {
-        if itr.curr_value < curr_max {
-          curr_max = curr_max.max(itr.next().unwrap());
-          moved += 1;
+        while *itr.peek().unwrap() < curr_max {
+          itr.next();
         }
+        all_at_max &= *itr.peek().unwrap() == curr_max;
+      }
+      if all_at_max {
+        return curr_max;
       }
     }
-    curr_max
   }
 }
 
-#[cfg(test)]
-mod tests {
-  use crate::day8::{Cycle, CycleItr, generator, part1, part2};
-
-  fn input() -> String {
-    "RL
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "RL
 
 AAA = (BBB, CCC)
 BBB = (DDD, EEE)
@@ -172,26 +157,100 @@ CCC = (ZZZ, GGG)
 DDD = (DDD, DDD)
 EEE = (EEE, EEE)
 GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)".to_string()
+ZZZ = (ZZZ, ZZZ)",
+    part1: Some("2"),
+    part2: None,
+  },
+  Example {
+    input: "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)",
+    part1: Some("6"),
+    part2: None,
+  },
+  Example {
+    input: "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)",
+    part1: None,
+    part2: Some("6"),
+  },
+];
+
+/// Up to six ghosts, one of them from AAA to ZZZ, on about the size of nodes with fewer ghosts for
+/// smaller sizes. Like the puzzle each ghost walks a loop whose length is a multiple of the
+/// instructions and ends on its Z node, which leads back to where the ghost went from its A node.
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  const PRIMES: [usize; 25] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73,
+    79, 83, 89, 97];
+  // The letters of the names that are neither the start nor the end of a ghost
+  const OTHER: &str = "BCDEFGHIJKLMNOPQRSTUVWXY";
+  let ghosts = rng.gen_range(1..=(size / 16).clamp(1, 6));
+  let loops: Vec<usize> = PRIMES.choose_multiple(rng, ghosts).copied().collect();
+  let total: usize = loops.iter().sum();
+  let len = (size / total).clamp(2, 13_000 / total);
+  let instructions: String = (0..len).map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' }).collect();
+
+  let mut ends = vec![(String::from("AAA"), String::from("ZZZ"))];
+  ends.extend(names(rng, ghosts - 1, 2, OTHER).into_iter().map(|n| (format!("{n}A"), format!("{n}Z"))));
+  let mut others = names(rng, len * total, 3, OTHER).into_iter();
+  let mut lines = Vec::new();
+  for ((start, end), loop_len) in ends.into_iter().zip(&loops) {
+    // The ghost is on path[s] after s steps, and on its Z node after the length of its loop
+    let mut path = vec![start];
+    path.extend(others.by_ref().take(len * loop_len - 1));
+    path.push(end);
+    for (s, node) in path.iter().enumerate() {
+      let next = if s + 1 < path.len() { &path[s + 1] } else { &path[1] };
+      let other = &path[rng.gen_range(1..path.len())];
+      let (left, right) = if instructions.as_bytes()[s % len] == b'L' { (next, other) } else { (other, next) };
+      lines.push(format!("{node} = ({left}, {right})"));
+    }
+  }
+  lines.shuffle(rng);
+  format!("{instructions}\n\n{}", lines.join("\n"))
+}
+
+solution!(Day8, Document, u32, u64);
+
+#[cfg(test)]
+mod tests {
+  use crate::day8::{EXAMPLES, generator, Ghost, part1, part2, random_input, reference};
+  use crate::utils::cycle::Cycle;
+  use crate::utils::differential::check;
+
+  fn input() -> String {
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let d = generator(&input());
+    let d = generator(&input()).unwrap();
     assert_eq!(2, d.instructions.len());
     assert_eq!(7, d.network.len());
+    assert_eq!("AAA", d.names.name(0));
+    assert_eq!((1, 2), d.network[0]);
   }
 
   #[test]
   fn test_part1() {
-    let d = generator(&input());
+    let d = generator(&input()).unwrap();
     assert_eq!(2, part1(&d));
 
     let d = generator("LLR
 
 AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
-ZZZ = (ZZZ, ZZZ)");
+ZZZ = (ZZZ, ZZZ)").unwrap();
     assert_eq!(6, part1(&d));
   }
 
@@ -206,9 +265,11 @@ ZZZ = (ZZZ, ZZZ)");
 22B = (22C, 22C)
 22C = (22Z, 22Z)
 22Z = (22B, 22B)
-XXX = (XXX, XXX)");
+XXX = (XXX, XXX)").unwrap();
     //assert_eq!(2, d.identify_repeat(&"11A".to_string()));
-    assert_eq!(Cycle { deltas: vec![3, 3], to_start: 3 }, d.identify_cycle(&"22A".to_string()));
+    let ghost = d.identify_cycle(d.names.get("22A").unwrap());
+    assert_eq!(Ghost { cycle: Cycle { start: 1, len: 6 }, z_steps: vec![3, 6] }, ghost);
+    assert_eq!(vec![3, 6, 9, 12], ghost.z_steps().take(4).collect::<Vec<_>>());
   }
 
   #[test]
@@ -222,16 +283,22 @@ XXX = (XXX, XXX)");
 22B = (22C, 22C)
 22C = (22Z, 22Z)
 22Z = (22B, 22B)
-XXX = (XXX, XXX)");
+XXX = (XXX, XXX)").unwrap();
     assert_eq!(6, part2(&d));
   }
 
   #[test]
   fn test_cycle() {
-    let c = Cycle { deltas: vec![19667], to_start: 19667 };
-    let mut itr = CycleItr::from(&c).peekable();
+    let g = Ghost { cycle: Cycle { start: 1, len: 19667 }, z_steps: vec![19667] };
+    let mut itr = g.z_steps();
     assert_eq!(itr.next().unwrap(), 19667);
     assert_eq!(itr.next().unwrap(), 19667 * 2);
     assert_eq!(itr.next().unwrap(), 19667 * 3);
   }
+
+  #[test]
+  fn test_reference() {
+    assert_eq!(6, reference::part2(&generator(EXAMPLES[2].input).unwrap()));
+    check(random_input, generator, 40, 0..10, part2, reference::part2);
+  }
 }
\ No newline at end of file
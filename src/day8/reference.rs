@@ -0,0 +1,22 @@
+//! Walk every ghost a step at a time until they all stand on a node ending in Z
+
+use crate::day8::Document;
+
+pub fn part2(document: &Document) -> u64 {
+  let at_end = |node| document.names.name(node).ends_with('Z');
+  let mut ghosts: Vec<_> = document.names.iter()
+    .filter(|(_, name)| name.ends_with('A'))
+    .map(|(id, _)| id)
+    .collect();
+  let mut steps = 0;
+  for instruct in document.instructions.iter().cycle() {
+    if ghosts.iter().all(|g| at_end(*g)) {
+      break;
+    }
+    for g in &mut ghosts {
+      *g = document.next(*g, *instruct);
+    }
+    steps += 1;
+  }
+  steps
+}
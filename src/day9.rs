@@ -1,6 +1,12 @@
-pub fn generator(input: &str) -> Vec<Vec<i32>> {
+use rand::Rng;
+use crate::utils::{solution, Example, ParseError};
+use crate::utils::checked::{sub, sum};
+use crate::utils::parse::numbers;
+use crate::utils::random::Random;
+
+pub fn generator(input: &str) -> Result<Vec<Vec<i32>>, ParseError> {
   input.lines()
-    .map(|line| line.split_whitespace().map(|n| n.parse().unwrap()).collect())
+    .map(|line| numbers(input, line))
     .collect()
 }
 
@@ -41,45 +47,67 @@ impl HistoryLine {
     for v in self.values.iter_mut() {
       park = *v;
       *v = next;
-      next -= park;
+      next = sub(next, park);
     }
     self.values.push(next);
   }
 
   fn next_value(&self) -> i32 {
-    self.values.iter().sum()
+    sum(self.values.iter().copied())
   }
 }
 
-fn compute(history_lines: &Vec<Vec<i32>>, reverse: bool) -> i64 {
-  history_lines.iter().map(|nums| {
+fn compute(history_lines: &[Vec<i32>], reverse: bool) -> i64 {
+  sum(history_lines.iter().map(|nums| {
     let hl = HistoryLine::from_nums(nums, reverse);
     hl.next_value() as i64
-  })
-    .sum()
+  }))
 }
 
-pub fn part1(history_lines: &Vec<Vec<i32>>) -> i64 {
+pub fn part1(history_lines: &[Vec<i32>]) -> i64 {
   compute(history_lines, false)
 }
 
-pub fn part2(history_lines: &Vec<Vec<i32>>) -> i64 {
+pub fn part2(history_lines: &[Vec<i32>]) -> i64 {
   compute(history_lines, true)
 }
 
+/// The examples from the puzzle with their answers
+pub const EXAMPLES: &[Example] = &[
+  Example {
+    input: "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45",
+    part1: Some("114"),
+    part2: Some("2"),
+  },
+];
+
+/// Lines of 21 values of polynomials of up to the fifth degree with small coefficients
+pub fn random_input(rng: &mut Random, size: usize) -> String {
+  (0..size).map(|_| {
+    let coefficients: Vec<i64> = (0..=rng.gen_range(0..=5)).map(|_| rng.gen_range(-9..=9)).collect();
+    let start = rng.gen_range(-5..=5);
+    (start..start + 21)
+      .map(|x| coefficients.iter().rev().fold(0, |v, c| v * x + c).to_string())
+      .collect::<Vec<_>>()
+      .join(" ")
+  }).collect::<Vec<_>>().join("\n")
+}
+
+solution!(Day9, Vec<Vec<i32>>, i64, i64);
+
 #[cfg(test)]
 mod tests {
-  use crate::day9::{generator, HistoryLine, part1, part2};
+  use crate::day9::{EXAMPLES, generator, HistoryLine, part1, part2};
 
   fn input() -> String {
-    "0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45".to_string()
+    EXAMPLES[0].input.to_string()
   }
 
   #[test]
   fn test_generator() {
-    let lines = generator(&input());
+    let lines = generator(&input()).unwrap();
     assert_eq!(3, lines.len());
   }
 
@@ -111,7 +139,7 @@ mod tests {
 
   #[test]
   fn test_part1() {
-    let lines = generator(&input());
+    let lines = generator(&input()).unwrap();
     assert_eq!(114, part1(&lines));
   }
 
@@ -123,7 +151,7 @@ mod tests {
 
   #[test]
   fn test_part2() {
-    let lines = generator(&input());
+    let lines = generator(&input()).unwrap();
     assert_eq!(2, part2(&lines));
   }
 }
\ No newline at end of file
@@ -0,0 +1,217 @@
+//! The history of the timings of each run, kept in timings.yml next to answers.yml.
+
+use crate::InputRun;
+
+use colored::Colorize;
+use serde::{Deserialize,Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// The number of previous runs that make up the rolling median
+const WINDOW: usize = 10;
+
+/// The timings of one day, where the parts that were not run are missing
+#[derive(Clone,Debug,Default,Deserialize,Serialize)]
+struct DayTimings {
+    generator_ns: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    part1_ns: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    part2_ns: Option<u64>,
+}
+
+impl DayTimings {
+    fn phase(&self, phase: usize) -> Option<u64> {
+        match phase {
+            0 => Some(self.generator_ns),
+            1 => self.part1_ns,
+            _ => self.part2_ns,
+        }
+    }
+}
+
+const PHASES: [&str; 3] = ["Generator", "Part 1", "Part 2"];
+
+/// The timings of one run of the binary
+#[derive(Debug,Deserialize,Serialize)]
+struct Run {
+    // the git commit, or the timestamp when it isn't available
+    key: String,
+    timestamp: String,
+    days: BTreeMap<String,DayTimings>,
+}
+
+#[derive(Default,Deserialize,Serialize)]
+pub struct History {
+    runs: Vec<Run>,
+}
+
+/// The key for this run, which is the git commit with a marker for local changes
+fn run_key(timestamp: &str) -> String {
+    let git = |args: &[&str]| Command::new("git").args(args).output().ok()
+      .filter(|o| o.status.success())
+      .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+    match git(&["rev-parse", "--short", "HEAD"]) {
+        Some(commit) if git(&["status", "--porcelain", "--untracked-files=no"])
+          .is_some_and(|s| !s.is_empty()) => format!("{}-dirty", commit),
+        Some(commit) => commit,
+        None => timestamp.to_string(),
+    }
+}
+
+/// The median of the values, which must not be empty
+fn median(values: &mut [u64]) -> u64 {
+    values.sort();
+    let n = values.len();
+    if n % 2 == 1 { values[n / 2] } else { (values[n / 2 - 1] + values[n / 2]) / 2 }
+}
+
+/// A phase that was slower than its rolling median
+#[derive(Debug,PartialEq)]
+pub struct Regression {
+    pub day: String,
+    pub phase: &'static str,
+    pub median: Duration,
+    pub current: Duration,
+}
+
+impl History {
+    fn make_filename(directory: &str) -> String {
+        Path::new(directory).join("timings.yml").to_string_lossy().to_string()
+    }
+
+    pub fn read(directory: &str) -> Self {
+        if let Ok(f) = File::open(Self::make_filename(directory)) {
+            serde_yaml::from_reader(f).expect("Could not read timings")
+        } else {
+            Self::default()
+        }
+    }
+
+    /// The timings of the days in the run that produced answers
+    fn timings(run: &InputRun) -> BTreeMap<String,DayTimings> {
+        run.outcomes.iter()
+          .filter(|o| o.is_ok())
+          .filter_map(|o| o.result())
+          .map(|r| (r.day.clone(), DayTimings {
+              generator_ns: r.generate_time.as_nanos() as u64,
+              part1_ns: r.parts.part1.then_some(r.part1.0.as_nanos() as u64),
+              part2_ns: r.parts.part2.then_some(r.part2.0.as_nanos() as u64),
+          }))
+          .collect()
+    }
+
+    /// Find the phases that are more than `percent` slower than the median of the previous runs
+    pub fn compare(&self, run: &InputRun, percent: f64) -> Vec<Regression> {
+        self.regressions(Self::timings(run), percent)
+    }
+
+    fn regressions(&self, days: BTreeMap<String,DayTimings>, percent: f64) -> Vec<Regression> {
+        let mut regressions = Vec::new();
+        for (day, current) in days {
+            for (p, phase) in PHASES.iter().enumerate() {
+                let Some(now) = current.phase(p) else { continue };
+                let mut previous: Vec<u64> = self.runs.iter().rev()
+                  .filter_map(|r| r.days.get(&day).and_then(|t| t.phase(p)))
+                  .take(WINDOW)
+                  .collect();
+                if previous.is_empty() {
+                    continue;
+                }
+                let median = median(&mut previous);
+                if now as f64 > median as f64 * (1.0 + percent / 100.0) {
+                    regressions.push(Regression {
+                        day: day.clone(),
+                        phase,
+                        median: Duration::from_nanos(median),
+                        current: Duration::from_nanos(now),
+                    });
+                }
+            }
+        }
+        regressions
+    }
+
+    /// Add the timings of the run to the history and write it back
+    pub fn append(&mut self, run: &InputRun) {
+        let days = Self::timings(run);
+        if days.is_empty() {
+            return;
+        }
+        let timestamp = chrono::Local::now().to_rfc3339();
+        self.runs.push(Run { key: run_key(&timestamp), timestamp, days });
+        let f = std::fs::OpenOptions::new()
+          .write(true)
+          .create(true)
+          .truncate(true)
+          .open(Self::make_filename(&run.directory))
+          .expect("Couldn't open file");
+        serde_yaml::to_writer(f, self).unwrap();
+    }
+}
+
+pub fn print_regressions(directory: &str, percent: f64, regressions: &[Regression]) {
+    if regressions.is_empty() {
+        println!("{}", format!("No phase in {} is more than {}% slower than the median of the last {} runs",
+                               directory, percent, WINDOW).green());
+        return;
+    }
+    println!("{}", format!("Phases in {} more than {}% slower than the median of the last {} runs",
+                           directory, percent, WINDOW).red().bold());
+    println!("{}", format!("{:<8}{:<12}{:<14}{:<14}{}", "Day", "Phase", "Median", "Now", "Change").bold());
+    for r in regressions {
+        let change = (r.current.as_secs_f64() / r.median.as_secs_f64() - 1.0) * 100.0;
+        println!("{:<8}{:<12}{:<14}{:<14}{}", r.day.replace("day", "Day "), r.phase, format!("{:.2?}", r.median),
+                 format!("{:.2?}", r.current), format!("+{:.0}%", change).red());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{median, DayTimings, History, Regression, Run, WINDOW};
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    fn timings(generator_ns: u64, part1_ns: Option<u64>) -> BTreeMap<String,DayTimings> {
+        BTreeMap::from([(String::from("day1"), DayTimings { generator_ns, part1_ns, part2_ns: None })])
+    }
+
+    fn history(generator_ns: &[u64]) -> History {
+        History {
+            runs: generator_ns.iter().enumerate().map(|(i, ns)| Run {
+                key: i.to_string(),
+                timestamp: i.to_string(),
+                days: timings(*ns, None),
+            }).collect(),
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(3, median(&mut [5, 1, 3]));
+        assert_eq!(4, median(&mut [5, 1, 3, 7]));
+        assert_eq!(9, median(&mut [9]));
+    }
+
+    #[test]
+    fn test_compare() {
+        // Only the last runs count, so the older and faster ones don't lower the median
+        let mut runs = vec![100; WINDOW];
+        runs.extend([200; WINDOW]);
+        let history = history(&runs);
+        assert_eq!(Vec::<Regression>::new(), history.regressions(timings(210, None), 10.0));
+        assert_eq!(vec![Regression {
+            day: String::from("day1"),
+            phase: "Generator",
+            median: Duration::from_nanos(200),
+            current: Duration::from_nanos(230),
+        }], history.regressions(timings(230, None), 10.0));
+
+        // Phases without a history and days that are new aren't compared
+        assert!(history.regressions(timings(200, Some(1_000_000)), 10.0).is_empty());
+        assert!(History::default().regressions(timings(1_000_000, None), 10.0).is_empty());
+    }
+}
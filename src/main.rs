@@ -1,22 +1,272 @@
-use aoc2023::{FUNCS,NAMES,utils};
+mod history;
+mod report;
+
+use aoc2023::{NAMES,REGISTRY,utils};
+use report::Format;
 
 use argh::FromArgs;
 use colored::Colorize;
 use serde::{Deserialize,Serialize};
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::fs::File;
+use std::panic;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
 
 #[derive(FromArgs)]
 /** Solution for Advent of Code (https://adventofcode.com/)*/
 struct Args {
-    /// the input directory
-    #[argh(option, short='i', default="String::from(\"input\")")]
-    input: String,
+    /// the input directory, repeat to run several (defaults to input)
+    #[argh(option, short='i')]
+    input: Vec<String>,
+
+    /// compare the answers against answers.yml and fail on any mismatch
+    #[argh(switch)]
+    check: bool,
+
+    /// write the answers back to answers.yml
+    #[argh(switch)]
+    update: bool,
+
+    /// append the timings of the run to timings.yml
+    #[argh(switch)]
+    record: bool,
+
+    /// the number of days to run in parallel, 0 for one per core (defaults to 1)
+    #[argh(option, short='j', default="1")]
+    jobs: usize,
+
+    /// run each phase this many times and report the statistics (defaults to 1)
+    #[argh(option, default="1")]
+    repeat: usize,
+
+    /// the number of untimed runs of each phase before the repeats (defaults to 0)
+    #[argh(option, default="0")]
+    warmup: usize,
+
+    /// flag the phases more than this percent slower than the median of the timing history
+    #[argh(option)]
+    compare: Option<f64>,
+
+    /// report the inputs that needed normalising, such as CRLF line endings, and fail the run
+    #[argh(switch)]
+    strict: bool,
+
+    /// read the input of a single day from this file, or stdin for -
+    #[argh(option)]
+    file: Option<String>,
+
+    /// run the days against the examples from their puzzles instead of the inputs
+    #[argh(switch)]
+    examples: bool,
+
+    /// the output format: text, json, csv or junit (defaults to text)
+    #[argh(option, default="Format::Text")]
+    format: Format,
+
+    /// the days and parts to execute, such as 1-5,12,20:2 (defaults to all)
+    #[argh(positional)]
+    days: Option<String>,
+
+    #[argh(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Gen(GenArgs),
+}
+
+#[derive(FromArgs)]
+/// Write a random input for a day to stdout, which is the same for the same seed
+#[argh(subcommand, name = "gen")]
+struct GenArgs {
+    /// the size of the input, such as the lines or the side of the grid, at least 1 (defaults to 100)
+    #[argh(option, default="100")]
+    size: usize,
+
+    /// the seed of the random numbers (defaults to 0)
+    #[argh(option, default="0")]
+    seed: u64,
 
-    /// a single day to execute (defaults to all)
+    /// the day to write an input for
     #[argh(positional)]
-    day: Option<usize>,
+    day: usize,
+}
+
+/// Print a random input for the day
+fn generate(args: &GenArgs) {
+    let Some(day) = NAMES.iter().position(|n| *n == format!("day{}", args.day)) else {
+        eprintln!("{}", format!("There is no day {}", args.day).red().bold());
+        std::process::exit(2);
+    };
+    if args.size == 0 {
+        eprintln!("{}", "The size must be at least 1".red().bold());
+        std::process::exit(2);
+    }
+    let mut rng = utils::random::seeded(args.seed);
+    println!("{}", (REGISTRY[day].random_input)(&mut rng, args.size));
+}
+
+/// How a computed answer relates to the stored one
+#[derive(Clone,Copy,Debug,Eq,PartialEq,Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Match,
+    Mismatch,
+    New,
+    Missing,
+}
+
+impl Status {
+    fn compare(expected: Option<&String>, actual: Option<&String>) -> Self {
+        match (expected, actual) {
+            (Some(e), Some(a)) if e == a => Status::Match,
+            (Some(_), Some(_)) => Status::Mismatch,
+            (None, _) => Status::New,
+            (Some(_), None) => Status::Missing,
+        }
+    }
+
+    fn pretty(&self) -> colored::ColoredString {
+        let name = format!("{:<8}", self);
+        match self {
+            Status::Match => name.green(),
+            Status::Mismatch => name.red().bold(),
+            Status::New => name.yellow(),
+            Status::Missing => name.red(),
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Status::Match => "match",
+            Status::Mismatch => "mismatch",
+            Status::New => "new",
+            Status::Missing => "missing",
+        })
+    }
+}
+
+/// The comparison of one part of one day
+struct Diff {
+    day: String,
+    part: usize,
+    status: Status,
+    expected: Option<String>,
+    actual: Option<String>,
+}
+
+thread_local! {
+    /// The message and location of the last panic, recorded by the panic hook
+    static PANIC_MESSAGE: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Record panics quietly so they can be reported with the day that caused them
+fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+          .or_else(|| payload.downcast_ref::<String>().cloned())
+          .unwrap_or_else(|| String::from("unknown panic"));
+        let message = match info.location() {
+            Some(location) => format!("{} at {}", message, location),
+            None => message,
+        };
+        PANIC_MESSAGE.with(|m| *m.borrow_mut() = message);
+    }));
+}
+
+/// The outcome of running one day against one input set
+enum Outcome {
+    Ran(Box<utils::DayResult>),
+    // the day, the parts that were selected, and the panic message
+    Panicked(String, utils::Parts, String),
+    // the day and the parts that were selected, when there was no input
+    Skipped(String, utils::Parts),
+}
+
+impl Outcome {
+    /// Run a day, catching any panic from its solver
+    fn run(day: usize, input: &str, parts: utils::Parts, repeat: utils::Repeat) -> Self {
+        match panic::catch_unwind(|| utils::run_day(&REGISTRY[day], input, parts, repeat)) {
+            Ok(result) => Outcome::Ran(Box::new(result)),
+            Err(_) => Outcome::Panicked(NAMES[day].to_string(), parts,
+                                        PANIC_MESSAGE.with(|m| m.take())),
+        }
+    }
+
+    fn day(&self) -> &str {
+        match self {
+            Outcome::Ran(r) => &r.day,
+            Outcome::Panicked(day, _, _) | Outcome::Skipped(day, _) => day,
+        }
+    }
+
+    fn parts(&self) -> utils::Parts {
+        match self {
+            Outcome::Ran(r) => r.parts,
+            Outcome::Panicked(_, parts, _) | Outcome::Skipped(_, parts) => *parts,
+        }
+    }
+
+    fn result(&self) -> Option<&utils::DayResult> {
+        match self {
+            Outcome::Ran(r) => Some(r),
+            Outcome::Panicked(_, _, _) | Outcome::Skipped(_, _) => None,
+        }
+    }
+
+    fn is_skipped(&self) -> bool {
+        matches!(self, Outcome::Skipped(_, _))
+    }
+
+    /// The answers, which are empty if the day failed
+    fn answers(&self) -> Vec<String> {
+        match self.result() {
+            Some(r) if r.is_ok() => r.get_answers(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn is_ok(&self) -> bool {
+        self.result().is_some_and(|r| r.is_ok())
+    }
+
+    /// Why the day failed to produce answers
+    fn error(&self) -> Option<String> {
+        match self {
+            Outcome::Ran(r) => r.error.as_ref().map(|e| e.to_string()),
+            Outcome::Panicked(_, _, message) => Some(format!("panicked: {}", message)),
+            Outcome::Skipped(_, _) => None,
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.result().map_or(Duration::ZERO, |r| r.generate_time + r.part1.0 + r.part2.0)
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Ran(r) => write!(f, "{}", r),
+            Outcome::Panicked(day, _, message) => {
+                writeln!(f, "{}", day.replace("day", "Day ").bold())?;
+                writeln!(f, "    {}", format!("panicked: {}", message).red().bold())
+            }
+            Outcome::Skipped(day, _) => {
+                writeln!(f, "{}", day.replace("day", "Day ").bold())?;
+                writeln!(f, "    {}", "skipped, there is no input".yellow())
+            }
+        }
+    }
 }
 
 #[derive(Default,Deserialize,Serialize)]
@@ -38,13 +288,42 @@ impl Answers {
         }
     }
 
-    fn update(&mut self, delta_list: &Vec<utils::DayResult>) {
-        for delta in delta_list {
-            let new_val = delta.get_answers();
+    /// Compare each selected part of the results against the stored answers
+    fn compare(&self, outcomes: &[Outcome]) -> Vec<Diff> {
+        let mut diffs = Vec::new();
+        for o in outcomes.iter().filter(|o| !o.is_skipped()) {
+            let stored = self.days.get(o.day());
+            let answers = o.answers();
+            for part in (0..2).filter(|p| o.parts().contains(p + 1)) {
+                let expected = stored.and_then(|s| s.get(part));
+                let actual = answers.get(part);
+                diffs.push(Diff {
+                    day: o.day().replace("day", "Day "),
+                    part: part + 1,
+                    status: Status::compare(expected, actual),
+                    expected: expected.cloned(),
+                    actual: actual.cloned(),
+                });
+            }
+        }
+        diffs
+    }
+
+    fn update(&mut self, outcomes: &[Outcome]) {
+        // Days that failed to parse or panicked keep their previous answers
+        for delta in outcomes.iter().filter_map(Outcome::result).filter(|d| d.is_ok()) {
+            // Parts that were not selected keep their previous answers too
+            let prev = self.days.get(&delta.day);
+            let new_val: Vec<String> = delta.get_answers().into_iter().enumerate()
+              .map(|(p, answer)| match prev.and_then(|prev| prev.get(p)) {
+                  Some(prev) if !delta.parts.contains(p + 1) => prev.clone(),
+                  _ => answer,
+              })
+              .collect();
             if let Some(prev) =
               self.days.insert(delta.day.to_string(), new_val.clone()) {
                 if prev != new_val {
-                    println!("{}", format!("Output for {} changed from {:?} to {:?}!",
+                    eprintln!("{}", format!("Output for {} changed from {:?} to {:?}!",
                                            delta.pretty_day(), prev, new_val).bold());
                 }
             }
@@ -62,35 +341,401 @@ impl Answers {
     }
 }
 
+fn print_diffs(diffs: &[Diff]) {
+    println!("{}", format!("{:<8}{:<6}{:<10}{:<20}{}", "Day", "Part", "Status", "Expected", "Actual").bold());
+    for d in diffs {
+        println!("{:<8}{:<6}{}  {:<20}{}", d.day, d.part, d.status.pretty(),
+                 d.expected.as_deref().unwrap_or("-"), d.actual.as_deref().unwrap_or("-"));
+    }
+}
+
+/// The results of running the selected days against one input directory
+struct InputRun {
+    // the directory or file the inputs came from
+    directory: String,
+    // whether the directory keeps answers.yml and timings.yml
+    stored: bool,
+    // the sum of the time spent in each day
+    cpu_time: Duration,
+    outcomes: Vec<Outcome>,
+    answers: Answers,
+    // the phases slower than the timing history, when comparing against it
+    regressions: Vec<history::Regression>,
+}
+
+impl InputRun {
+    fn failed(&self) -> usize {
+        self.outcomes.iter().filter(|o| !o.is_ok() && !o.is_skipped()).count()
+    }
+}
+
+/// The inputs of each day for one run, which are missing for the days without a file
+struct InputSet {
+    name: String,
+    stored: bool,
+    inputs: Vec<Option<String>>,
+    // the files that normalising changed, with what it changed
+    normalised: Vec<(String, utils::Normalised)>,
+}
+
+impl InputSet {
+    fn from_directory(directory: &str, selection: &[(usize, utils::Parts)]) -> Self {
+        let inputs = utils::read_inputs(directory, NAMES)
+          .expect("Can't read input dir");
+        for (p, _) in selection.iter().filter(|(p, _)| inputs[*p].is_none()) {
+            eprintln!("{}", format!("Skipping {}, there is no {}/{}.txt", NAMES[*p], directory, NAMES[*p]).yellow());
+        }
+        let normalised = selection.iter()
+          .filter_map(|(p, _)| match &inputs[*p] {
+              Some((_, changes)) if !changes.is_unchanged() =>
+                  Some((format!("{}/{}.txt", directory, NAMES[*p]), *changes)),
+              _ => None,
+          })
+          .collect();
+        let inputs = inputs.into_iter().map(|input| input.map(|(text, _)| text)).collect();
+        InputSet { name: directory.to_string(), stored: true, inputs, normalised }
+    }
+
+    /// Read the input of a single day from a file, or stdin for `-`
+    fn from_file(path: &str, day: usize) -> Self {
+        let text = if path == "-" {
+            std::io::read_to_string(std::io::stdin()).expect("Can't read stdin")
+        } else {
+            std::fs::read_to_string(path).expect("Can't read input file")
+        };
+        let (text, changes) = utils::normalise(&text);
+        let mut inputs = vec![None; NAMES.len()];
+        inputs[day] = Some(text);
+        let name = if path == "-" { String::from("stdin") } else { path.to_string() };
+        let normalised = if changes.is_unchanged() { Vec::new() } else { vec![(name.clone(), changes)] };
+        InputSet { name, stored: false, inputs, normalised }
+    }
+
+    /// Print what normalising changed in each file, returning whether anything changed
+    fn report_normalised(&self) -> bool {
+        for (file, changes) in &self.normalised {
+            eprintln!("{}", format!("Normalised {}: {}", file, changes).yellow());
+        }
+        !self.normalised.is_empty()
+    }
+}
+
+/// Add the timings of the runs to their history, which only happens when the run asks for it
+fn record_history(record: bool, runs: &[InputRun], histories: &mut [history::History]) {
+    if !record {
+        return;
+    }
+    for (run, history) in runs.iter().zip(histories).filter(|(r, _)| r.stored) {
+        history.append(run);
+    }
+}
+
+/// Stack size for the workers, matching the main thread for the recursive solvers
+const WORKER_STACK: usize = 8 * 1024 * 1024;
+
+/// Run the selected days of every input set on a pool of `jobs` workers.
+/// Returns the wall clock time and the runs with their outcomes in day order.
+fn run_all(sets: &[InputSet], selection: &[(usize, utils::Parts)], jobs: usize,
+           repeat: utils::Repeat) -> (Duration, Vec<InputRun>) {
+    // Each task is an input set and day with its parts
+    let tasks: Vec<(usize, usize, utils::Parts)> = (0..sets.len())
+      .flat_map(|d| selection.iter().map(move |&(p, parts)| (d, p, parts)))
+      .collect();
+    let next = AtomicUsize::new(0);
+
+    install_panic_hook();
+    let (elapsed, mut done) = utils::time(&|| thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(tasks.len()).max(1)).map(|_| {
+            thread::Builder::new().stack_size(WORKER_STACK).spawn_scoped(scope, || {
+                let mut done = Vec::new();
+                while let Some(&(d, p, parts)) = tasks.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let outcome = match &sets[d].inputs[p] {
+                        Some(input) => Outcome::run(p, input, parts, repeat),
+                        None => Outcome::Skipped(NAMES[p].to_string(), parts),
+                    };
+                    done.push((d, p, outcome));
+                }
+                done
+            }).expect("Can't start worker")
+        }).collect();
+        workers.into_iter()
+          .flat_map(|w| w.join().expect("Worker failed"))
+          .collect::<Vec<_>>()
+    }));
+    // Restore the default hook for panics outside of the solvers
+    drop(panic::take_hook());
+
+    done.sort_by_key(|(d, p, _)| (*d, *p));
+    let mut runs: Vec<InputRun> = sets.iter().map(|set| InputRun {
+        directory: set.name.clone(),
+        stored: set.stored,
+        cpu_time: Duration::ZERO,
+        outcomes: Vec::new(),
+        answers: if set.stored { Answers::read(&set.name) } else { Answers::default() },
+        regressions: Vec::new(),
+    }).collect();
+    for (d, _, outcome) in done {
+        runs[d].cpu_time += outcome.elapsed();
+        runs[d].outcomes.push(outcome);
+    }
+    (elapsed, runs)
+}
+
+/// Run the selected days against the examples from their puzzles and print how
+/// each answer compares. Returns the number of answers that were wrong.
+fn run_examples(selection: &[(usize, utils::Parts)]) -> usize {
+    println!("{}", format!("{:<8}{:<9}{:<6}{:<10}{:<20}{}", "Day", "Example", "Part", "Status", "Expected", "Actual").bold());
+    let mut wrong = 0;
+    install_panic_hook();
+    for &(p, selected) in selection {
+        for (e, example) in REGISTRY[p].examples.iter().enumerate() {
+            let parts = utils::Parts {
+                part1: selected.part1 && example.part1.is_some(),
+                part2: selected.part2 && example.part2.is_some(),
+            };
+            if !parts.part1 && !parts.part2 {
+                continue;
+            }
+            let outcome = Outcome::run(p, example.input, parts, utils::Repeat::ONCE);
+            let answers = outcome.answers();
+            for part in (1..=2).filter(|p| parts.contains(*p)) {
+                let expected = example.expected(part).map(String::from);
+                let status = Status::compare(expected.as_ref(), answers.get(part - 1));
+                if status != Status::Match {
+                    wrong += 1;
+                }
+                println!("{:<8}{:<9}{:<6}{}  {:<20}{}", NAMES[p].replace("day", "Day "), e + 1, part,
+                         status.pretty(), expected.as_deref().unwrap_or("-"),
+                         answers.get(part - 1).map_or("-", |a| a.as_str()));
+            }
+            if let Some(error) = outcome.error() {
+                println!("    {}", error.red().bold());
+            }
+        }
+    }
+    drop(panic::take_hook());
+    wrong
+}
+
+const CELL_WIDTH: usize = 24;
+
+/// Format the matrix cell for one day of one input set
+fn matrix_cell(outcome: &Outcome, answers: &Answers) -> String {
+    let status = match outcome {
+        Outcome::Panicked(_, _, _) => "panic".red().bold(),
+        Outcome::Skipped(_, _) => "skipped".dimmed(),
+        Outcome::Ran(r) if !r.is_ok() => "parse error".red().bold(),
+        _ => {
+            let diffs = answers.compare(std::slice::from_ref(outcome));
+            if diffs.iter().any(|d| d.status == Status::Mismatch) {
+                "mismatch".red().bold()
+            } else if diffs.iter().any(|d| d.status == Status::New) {
+                "new".yellow()
+            } else {
+                "ok".green()
+            }
+        }
+    };
+    let time = format!("({:.2?})", outcome.elapsed());
+    let padding = CELL_WIDTH.saturating_sub(status.chars().count() + 1 + time.chars().count());
+    format!("{} {}{}", status, time.dimmed(), " ".repeat(padding))
+}
+
+/// Print a day by input set matrix of the status and time
+fn print_matrix(runs: &[InputRun]) {
+    print!("{}", format!("{:<8}", "Day").bold());
+    for run in runs {
+        print!("{}", format!("{:<width$}", run.directory, width = CELL_WIDTH).bold());
+    }
+    println!();
+    for (d, outcome) in runs[0].outcomes.iter().enumerate() {
+        print!("{:<8}", outcome.day().replace("day", "Day "));
+        for run in runs {
+            print!("{}", matrix_cell(&run.outcomes[d], &run.answers));
+        }
+        println!();
+    }
+    print!("{:<8}", "Overall");
+    for run in runs {
+        let time = format!("({:.2?})", run.cpu_time);
+        print!("{}{}", time.dimmed(), " ".repeat(CELL_WIDTH.saturating_sub(time.chars().count())));
+    }
+    println!();
+}
+
 fn main() {
     let args: Args = argh::from_env();
-    // Did the user pick a single day to run
-    let day_filter: Option<usize> = match args.day {
-        Some(day) => {
-            let name = format!("day{}", day);
-            Some(NAMES.iter().position(|x| **x == name)
-              .expect("Requested an unimplemented day"))
-        },
-        None => None
+    if let Some(Command::Gen(gen)) = &args.command {
+        generate(gen);
+        return;
+    }
+    let directories = if args.input.is_empty() { vec![String::from("input")] } else { args.input.clone() };
+    // Did the user pick the days and parts to run
+    let selection: Vec<(usize, utils::Parts)> = match &args.days {
+        Some(spec) => utils::parse_selection(spec, NAMES).unwrap_or_else(|e| {
+            eprintln!("{}", format!("Invalid selection: {}", e).red().bold());
+            std::process::exit(2);
+        }),
+        None => (0..NAMES.len()).map(|p| (p, utils::Parts::BOTH)).collect(),
+    };
+    if args.examples {
+        let wrong = run_examples(&selection);
+        if wrong > 0 {
+            println!("{}", format!("{} example answer(s) are wrong", wrong).red().bold());
+            std::process::exit(1);
+        }
+        println!("{}", "All example answers match".green());
+        return;
+    }
+    let text = args.format == Format::Text;
+    // Read the inputs from the given file or directories
+    let sets: Vec<InputSet> = match &args.file {
+        Some(path) => {
+            if selection.len() != 1 || args.update {
+                eprintln!("{}", "--file needs a single day and can't be used with --update".red().bold());
+                std::process::exit(2);
+            }
+            vec![InputSet::from_file(path, selection[0].0)]
+        }
+        None => directories.iter().map(|dir| InputSet::from_directory(dir, &selection)).collect(),
     };
-    // Read the inputs from the given directory
-    println!("{} {}\n", "Reading from".bold(), &args.input);
-    let inputs = utils::read_inputs(&args.input, NAMES)
-      .expect("Can't read input dir");
-
-    let (elapsed, results) = utils::time(&|| {
-        FUNCS.iter().enumerate()
-          .filter(|(p, _)| day_filter.is_none() || day_filter.unwrap() == *p)
-          .map(|(p, f)| f(&inputs[p]))
-          .collect::<Vec<utils::DayResult>>()
-    });
-
-    for r in &results {
-        println!("{}", r);
-    }
-    println!("{} {}", "Overall runtime".bold(), format!("({:.2?})", elapsed).dimmed());
-
-    let mut old_answers = Answers::read(&args.input);
-    old_answers.update(&results);
-    old_answers.write(&args.input);
-}
\ No newline at end of file
+    // Only strict runs complain about inputs that had to be normalised
+    let unnormalised = args.strict && sets.iter().fold(false, |found, set| set.report_normalised() | found);
+    if text {
+        let names: Vec<&str> = sets.iter().map(|s| s.name.as_str()).collect();
+        println!("{} {}\n", "Reading from".bold(), names.join(", "));
+    }
+    let jobs = if args.jobs == 0 {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        args.jobs
+    };
+    let (elapsed, mut runs) = run_all(&sets, &selection, jobs,
+                                      utils::Repeat { runs: args.repeat.max(1), warmup: args.warmup });
+    let cpu_time: Duration = runs.iter().map(|r| r.cpu_time).sum();
+
+    // Compare against the timing history before adding this run to it
+    let mut histories: Vec<history::History> = runs.iter()
+      .map(|r| if r.stored { history::History::read(&r.directory) } else { history::History::default() })
+      .collect();
+    if let Some(percent) = args.compare {
+        for (run, history) in runs.iter_mut().zip(&histories).filter(|(r, _)| r.stored) {
+            run.regressions = history.compare(run, percent);
+        }
+    }
+
+    if let Format::Report(format) = args.format {
+        print!("{}", report::render(format, elapsed, cpu_time, &runs));
+    } else {
+        if runs.len() == 1 {
+            for o in &runs[0].outcomes {
+                println!("{}", o);
+            }
+        } else {
+            print_matrix(&runs);
+            println!();
+        }
+        println!("{} {} {} {}", "Overall runtime".bold(), format!("({:.2?})", elapsed).dimmed(),
+                 "· CPU time".bold(), format!("({:.2?})", cpu_time).dimmed());
+    }
+
+    if let Some(percent) = args.compare.filter(|_| text) {
+        for run in runs.iter().filter(|r| r.stored) {
+            println!();
+            history::print_regressions(&run.directory, percent, &run.regressions);
+        }
+    }
+    record_history(args.record, &runs, &mut histories);
+
+    if args.check {
+        let mut mismatched = false;
+        for run in &runs {
+            let mut diffs = run.answers.compare(&run.outcomes);
+            let mismatches = diffs.iter().filter(|d| d.status == Status::Mismatch).count();
+            mismatched |= mismatches > 0;
+            if !text {
+                continue;
+            }
+            println!();
+            if runs.len() > 1 {
+                // Only show the parts that need attention for each input set
+                diffs.retain(|d| d.status != Status::Match);
+                if diffs.is_empty() {
+                    println!("{} {}", run.directory.bold(), "all answers match".green());
+                    continue;
+                }
+                println!("{}", run.directory.bold());
+            }
+            print_diffs(&diffs);
+            if mismatches > 0 {
+                println!("{}", format!("{} answer(s) do not match {}", mismatches,
+                                       Answers::make_filename(&run.directory)).red().bold());
+            }
+        }
+        if mismatched {
+            std::process::exit(1);
+        }
+    }
+    for run in runs.iter_mut().filter(|r| r.stored) {
+        if args.update {
+            run.answers.update(&run.outcomes);
+            run.answers.write(&run.directory);
+        } else if text && !args.check && run.answers.compare(&run.outcomes).iter()
+          .any(|d| matches!(d.status, Status::Mismatch | Status::New)) {
+            println!("{}", format!("Answers differ from {}, rerun with --update to save them",
+                                   Answers::make_filename(&run.directory)).bold());
+        }
+    }
+
+    let failed: usize = runs.iter().map(InputRun::failed).sum();
+    if failed > 0 {
+        if text {
+            println!("{}", format!("{} day(s) failed to run", failed).red().bold());
+        }
+        std::process::exit(1);
+    }
+    if runs.iter().any(|r| !r.regressions.is_empty()) {
+        if text {
+            println!("{}", "Some phases are slower than their timing history, see above".red().bold());
+        }
+        std::process::exit(1);
+    }
+    if unnormalised {
+        if text {
+            println!("{}", "Some inputs needed normalising, see the warnings above".red().bold());
+        }
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{history, record_history, run_all, Args, InputSet};
+    use aoc2023::{REGISTRY, utils};
+    use argh::FromArgs;
+
+    #[test]
+    fn test_record_history() {
+        let directory = std::env::temp_dir().join(format!("aoc2023-record-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("day1.txt"), REGISTRY[0].examples[0].input).unwrap();
+        let timings = directory.join("timings.yml");
+        std::fs::write(&timings, "runs: []\n").unwrap();
+        let directory = directory.to_string_lossy().to_string();
+
+        let selection = [(0, utils::Parts::BOTH)];
+        let sets = [InputSet::from_directory(&directory, &selection)];
+        let (_, runs) = run_all(&sets, &selection, 1, utils::Repeat { runs: 1, warmup: 0 });
+        let read = || vec![history::History::read(&directory)];
+
+        // A default run leaves the timing history as it is
+        let args = Args::from_args(&["aoc2023"], &[]).unwrap();
+        record_history(args.record, &runs, &mut read());
+        assert_eq!("runs: []\n", std::fs::read_to_string(&timings).unwrap());
+
+        let args = Args::from_args(&["aoc2023"], &["--record"]).unwrap();
+        record_history(args.record, &runs, &mut read());
+        assert!(std::fs::read_to_string(&timings).unwrap().contains("day1"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
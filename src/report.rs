@@ -0,0 +1,282 @@
+//! Machine readable reports of a run for dashboards and CI.
+
+use crate::{InputRun, Status};
+
+use serde::Serialize;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The output format of the runner
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum Format {
+    Text,
+    Report(ReportFormat),
+}
+
+/// The machine readable formats, which are rendered once the run is over
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Junit,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Report(ReportFormat::Json)),
+            "csv" => Ok(Format::Report(ReportFormat::Csv)),
+            "junit" => Ok(Format::Report(ReportFormat::Junit)),
+            _ => Err(format!("unknown format {}, expected text, json, csv or junit", s)),
+        }
+    }
+}
+
+/// The result of one part of a day
+#[derive(Serialize)]
+struct PartRecord {
+    part: usize,
+    time_ns: u128,
+    answer: Option<String>,
+    expected: Option<String>,
+    status: Status,
+}
+
+/// The records with the total times of the run
+#[derive(Serialize)]
+struct Report<'a> {
+    wall_ns: u128,
+    cpu_ns: u128,
+    records: &'a [Record],
+}
+
+/// A phase of a day that was slower than its timing history
+#[derive(Serialize)]
+struct RegressionRecord {
+    phase: &'static str,
+    median_ns: u128,
+    time_ns: u128,
+}
+
+/// The result of one day against one input set
+#[derive(Serialize)]
+struct Record {
+    input: String,
+    day: String,
+    generator_ns: u128,
+    parts: Vec<PartRecord>,
+    error: Option<String>,
+    regressions: Vec<RegressionRecord>,
+}
+
+fn build_records(runs: &[InputRun]) -> Vec<Record> {
+    let mut records = Vec::new();
+    for run in runs {
+        for outcome in run.outcomes.iter().filter(|o| !o.is_skipped()) {
+            // Only the selected parts have a diff
+            let diffs = run.answers.compare(std::slice::from_ref(outcome));
+            let result = outcome.result();
+            let times = match result {
+                Some(r) => [r.part1.0, r.part2.0],
+                None => [Duration::ZERO; 2],
+            };
+            records.push(Record {
+                input: run.directory.clone(),
+                day: outcome.day().to_string(),
+                generator_ns: result.map_or(0, |r| r.generate_time.as_nanos()),
+                parts: diffs.iter().map(|d| PartRecord {
+                    part: d.part,
+                    time_ns: times[d.part - 1].as_nanos(),
+                    answer: d.actual.clone(),
+                    expected: d.expected.clone(),
+                    status: d.status,
+                }).collect(),
+                error: outcome.error(),
+                regressions: run.regressions.iter().filter(|r| r.day == outcome.day()).map(|r| RegressionRecord {
+                    phase: r.phase,
+                    median_ns: r.median.as_nanos(),
+                    time_ns: r.current.as_nanos(),
+                }).collect(),
+            });
+        }
+    }
+    records
+}
+
+/// Quote a CSV field if it contains a separator, quote, or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+      .replace('<', "&lt;")
+      .replace('>', "&gt;")
+      .replace('"', "&quot;")
+}
+
+fn to_json(wall: Duration, cpu: Duration, records: &[Record]) -> String {
+    let report = Report { wall_ns: wall.as_nanos(), cpu_ns: cpu.as_nanos(), records };
+    serde_json::to_string_pretty(&report).expect("Can't serialize report") + "\n"
+}
+
+fn to_csv(records: &[Record]) -> String {
+    let mut output = String::from("input,day,generator_ns,part1_ns,part1_answer,part1_expected,part1_status,\
+                                   part2_ns,part2_answer,part2_expected,part2_status,error,regressions\n");
+    for r in records {
+        let mut fields = vec![csv_field(&r.input), csv_field(&r.day), r.generator_ns.to_string()];
+        for part in 1..=2 {
+            // Parts that were not selected are left blank
+            match r.parts.iter().find(|p| p.part == part) {
+                Some(p) => {
+                    fields.push(p.time_ns.to_string());
+                    fields.push(csv_field(p.answer.as_deref().unwrap_or("")));
+                    fields.push(csv_field(p.expected.as_deref().unwrap_or("")));
+                    fields.push(p.status.to_string());
+                }
+                None => fields.extend([String::new(), String::new(), String::new(), String::new()]),
+            }
+        }
+        fields.push(csv_field(r.error.as_deref().unwrap_or("")));
+        let phases: Vec<&str> = r.regressions.iter().map(|g| g.phase).collect();
+        fields.push(csv_field(&phases.join(";")));
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+/// Each input set is a test suite and each day/part a test case
+fn to_junit(wall: Duration, records: &[Record]) -> String {
+    let mut suites = String::new();
+    let mut total = (0, 0, 0);
+    let mut inputs: Vec<&str> = records.iter().map(|r| r.input.as_str()).collect();
+    inputs.dedup();
+    for input in inputs {
+        let mut cases = String::new();
+        let (mut tests, mut failures, mut errors) = (0, 0, 0);
+        let mut elapsed = Duration::ZERO;
+        for r in records.iter().filter(|r| r.input == input) {
+            for p in &r.parts {
+                tests += 1;
+                let time = Duration::from_nanos(p.time_ns as u64);
+                elapsed += time;
+                cases.push_str(&format!("    <testcase classname=\"{}.{}\" name=\"part{}\" time=\"{:.6}\">\n",
+                                        xml_escape(input), r.day, p.part, time.as_secs_f64()));
+                // The generator runs for every part, so its regressions fail each of them
+                let slower: Vec<String> = r.regressions.iter()
+                  .filter(|g| g.phase == "Generator" || g.phase == format!("Part {}", p.part))
+                  .map(|g| format!("{} took {:.2?} against a median of {:.2?}", g.phase,
+                                   Duration::from_nanos(g.time_ns as u64), Duration::from_nanos(g.median_ns as u64)))
+                  .collect();
+                if let Some(error) = &r.error {
+                    errors += 1;
+                    cases.push_str(&format!("      <error message=\"{}\"/>\n", xml_escape(error)));
+                } else if p.status == Status::Mismatch || !slower.is_empty() {
+                    failures += 1;
+                    let mut messages = slower;
+                    if p.status == Status::Mismatch {
+                        messages.insert(0, format!("expected {} but found {}", p.expected.as_deref().unwrap_or(""),
+                                                   p.answer.as_deref().unwrap_or("")));
+                    }
+                    cases.push_str(&format!("      <failure message=\"{}\"/>\n", xml_escape(&messages.join("; "))));
+                }
+                if let Some(answer) = &p.answer {
+                    cases.push_str(&format!("      <system-out>{}</system-out>\n", xml_escape(answer)));
+                }
+                cases.push_str("    </testcase>\n");
+            }
+        }
+        suites.push_str(&format!("  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.6}\">\n",
+                                 xml_escape(input), tests, failures, errors, elapsed.as_secs_f64()));
+        suites.push_str(&cases);
+        suites.push_str("  </testsuite>\n");
+        total = (total.0 + tests, total.1 + failures, total.2 + errors);
+    }
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <testsuites name=\"aoc2023\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.6}\">\n{}</testsuites>\n",
+            total.0, total.1, total.2, wall.as_secs_f64(), suites)
+}
+
+/// Render the runs in the given machine readable format.
+/// The wall time is for the whole run, while the cpu time sums the time of each day.
+pub fn render(format: ReportFormat, wall: Duration, cpu: Duration, runs: &[InputRun]) -> String {
+    let records = build_records(runs);
+    match format {
+        ReportFormat::Json => to_json(wall, cpu, &records),
+        ReportFormat::Csv => to_csv(&records),
+        ReportFormat::Junit => to_junit(wall, &records),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Status;
+    use crate::report::{to_csv, to_json, to_junit, Format, PartRecord, Record, RegressionRecord, ReportFormat};
+    use std::time::Duration;
+
+    /// Day 1 of an input set whose name and answers need quoting, with the second part wrong
+    fn records() -> Vec<Record> {
+        vec![Record {
+            input: String::from("in, \"put\""),
+            day: String::from("day1"),
+            generator_ns: 10,
+            parts: vec![
+                PartRecord { part: 1, time_ns: 20, answer: Some(String::from("1,2")), expected: None,
+                             status: Status::New },
+                PartRecord { part: 2, time_ns: 30, answer: Some(String::from("<a & b>")),
+                             expected: Some(String::from("\"c\"")), status: Status::Mismatch },
+            ],
+            error: None,
+            regressions: vec![RegressionRecord { phase: "Part 1", median_ns: 10, time_ns: 20 }],
+        }]
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Ok(Format::Text), "text".parse());
+        assert_eq!(Ok(Format::Report(ReportFormat::Junit)), "junit".parse());
+        assert!("xml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = to_csv(&records());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(2, lines.len());
+        assert_eq!("\"in, \"\"put\"\"\",day1,10,20,\"1,2\",,new,30,<a & b>,\"\"\"c\"\"\",mismatch,,Part 1", lines[1]);
+    }
+
+    #[test]
+    fn test_junit() {
+        let junit = to_junit(Duration::from_millis(1), &records());
+        assert!(junit.contains("<testsuites name=\"aoc2023\" tests=\"2\" failures=\"2\" errors=\"0\""));
+        assert!(junit.contains("<testsuite name=\"in, &quot;put&quot;\""));
+        assert!(junit.contains("<failure message=\"Part 1 took 20.00ns against a median of 10.00ns\"/>"));
+        assert!(junit.contains("<failure message=\"expected &quot;c&quot; but found &lt;a &amp; b&gt;\"/>"));
+        assert!(junit.contains("<system-out>&lt;a &amp; b&gt;</system-out>"));
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(Duration::from_nanos(5), Duration::from_nanos(4),
+                                                                    &records())).unwrap();
+        assert_eq!(5, json["wall_ns"]);
+        assert_eq!(4, json["cpu_ns"]);
+        let record = &json["records"][0];
+        assert_eq!("in, \"put\"", record["input"]);
+        assert_eq!("day1", record["day"]);
+        assert_eq!(10, record["generator_ns"]);
+        assert_eq!(serde_json::Value::Null, record["error"]);
+        assert_eq!(serde_json::json!({"part": 2, "time_ns": 30, "answer": "<a & b>", "expected": "\"c\"",
+                                      "status": "mismatch"}), record["parts"][1]);
+        assert_eq!(serde_json::json!([{"phase": "Part 1", "median_ns": 10, "time_ns": 20}]), record["regressions"]);
+    }
+}
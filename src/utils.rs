@@ -1,17 +1,36 @@
 use colored::Colorize;
 use std::cmp::min;
+use std::collections::BTreeMap;
 use std::{fmt, fs, io};
 use std::path::Path;
+use std::str::FromStr;
 use std::time;
 
+#[cfg(feature = "alloc-stats")]
+pub mod alloc;
+pub mod checked;
+pub mod cycle;
+pub mod differential;
+pub mod geom;
+pub mod graph;
+pub mod grid;
+pub mod intern;
+pub mod interval;
+pub mod parse;
+pub mod random;
+
 /// Format the output of each line of the output.
 /// Includes the category, time, and result.
 fn pretty_print(f: &mut fmt::Formatter<'_>, line: &str,
                 duration: time::Duration,
+                memory: Option<MemStats>,
                 output: Option<&str>) -> fmt::Result {
   const DISPLAY_WIDTH: usize = 40;
 
-  let duration = format!("({:.2?})", duration);
+  let duration = match memory {
+    Some(memory) => format!("({:.2?}, {})", duration, memory),
+    None => format!("({:.2?})", duration),
+  };
   write!(f, "{} {}", line, duration.dimmed())?;
 
   match output {
@@ -43,31 +62,544 @@ pub fn time<T>(func: &dyn Fn() -> T) -> (time::Duration, T) {
   (start.elapsed(), result)
 }
 
-/// Read the data files from the in_dir into a vector of string.
-pub fn read_inputs(in_dir: &str, days: &[&str]) -> io::Result<Vec<String>> {
-  let data: Vec<io::Result<String>> = days.iter()
+/// How many times to run each phase of a day, after discarding the warm-up runs
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Repeat {
+  pub runs: usize,
+  pub warmup: usize,
+}
+
+impl Repeat {
+  pub const ONCE: Repeat = Repeat { runs: 1, warmup: 0 };
+}
+
+/// Summary statistics of repeated timings
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Stats {
+  pub min: time::Duration,
+  pub median: time::Duration,
+  pub mean: time::Duration,
+  pub stddev: time::Duration,
+}
+
+impl Stats {
+  pub fn from(samples: &[time::Duration]) -> Stats {
+    let mut sorted = samples.to_vec();
+    sorted.sort();
+    let n = sorted.len();
+    let median = if n % 2 == 1 { sorted[n / 2] } else { (sorted[n / 2 - 1] + sorted[n / 2]) / 2 };
+    let mean = sorted.iter().map(|d| d.as_secs_f64()).sum::<f64>() / n as f64;
+    let variance = sorted.iter().map(|d| (d.as_secs_f64() - mean).powi(2)).sum::<f64>() / n as f64;
+    Stats {
+      min: sorted[0],
+      median,
+      mean: time::Duration::from_secs_f64(mean),
+      stddev: time::Duration::from_secs_f64(variance.sqrt()),
+    }
+  }
+}
+
+impl fmt::Display for Stats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "min {:.2?} · median {:.2?} · mean {:.2?} · stddev {:.2?}",
+           self.min, self.median, self.mean, self.stddev)
+  }
+}
+
+/// Time the given function repeatedly, returning the statistics and the last result
+pub fn time_repeated<T>(repeat: Repeat, func: &dyn Fn() -> T) -> (Stats, T) {
+  for _ in 0..repeat.warmup {
+    func();
+  }
+  let (first, mut result) = time(func);
+  let mut samples = vec![first];
+  for _ in 1..repeat.runs {
+    let (elapsed, r) = time(func);
+    samples.push(elapsed);
+    result = r;
+  }
+  (Stats::from(&samples), result)
+}
+
+/// The heap used by a phase of a day
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemStats {
+  // the most the heap grew while the phase ran
+  pub peak_bytes: usize,
+  pub allocations: usize,
+}
+
+impl fmt::Display for MemStats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = self.peak_bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+      size /= 1024.0;
+      unit += 1;
+    }
+    if unit == 0 {
+      write!(f, "{} B peak, {} allocs", self.peak_bytes, self.allocations)
+    } else {
+      write!(f, "{:.1} {} peak, {} allocs", size, UNITS[unit], self.allocations)
+    }
+  }
+}
+
+/// Measure the heap used by a phase, when the counting allocator is enabled
+fn measure_memory<T>(executions: usize, func: impl FnOnce() -> T) -> (Option<MemStats>, T) {
+  #[cfg(feature = "alloc-stats")]
+  {
+    let (memory, result) = alloc::measure(executions, func);
+    (Some(memory), result)
+  }
+  #[cfg(not(feature = "alloc-stats"))]
+  {
+    let _ = executions;
+    (None, func())
+  }
+}
+
+/// What normalising an input changed
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Normalised {
+  pub bom: bool,
+  // the number of CRLF line endings converted to LF
+  pub crlf: usize,
+  // the number of lines that ended in spaces or tabs
+  pub trailing_whitespace: usize,
+  // the number of blank lines removed from the end
+  pub blank_lines: usize,
+}
+
+impl Normalised {
+  /// Was the input already normalised
+  pub fn is_unchanged(&self) -> bool {
+    *self == Normalised::default()
+  }
+}
+
+impl fmt::Display for Normalised {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut changes = Vec::new();
+    if self.bom {
+      changes.push(String::from("removed the byte order mark"));
+    }
+    if self.crlf > 0 {
+      changes.push(format!("converted {} CRLF line ending(s)", self.crlf));
+    }
+    if self.trailing_whitespace > 0 {
+      changes.push(format!("trimmed trailing whitespace from {} line(s)", self.trailing_whitespace));
+    }
+    if self.blank_lines > 0 {
+      changes.push(format!("removed {} trailing blank line(s)", self.blank_lines));
+    }
+    if changes.is_empty() {
+      changes.push(String::from("unchanged"));
+    }
+    write!(f, "{}", changes.join(", "))
+  }
+}
+
+/// Strip the byte order mark, convert CRLF line endings, trim the whitespace at the end
+/// of each line and remove the blank lines at the end, keeping the final newline.
+pub fn normalise(text: &str) -> (String, Normalised) {
+  let mut changes = Normalised::default();
+  let text = match text.strip_prefix('\u{feff}') {
+    Some(rest) => {
+      changes.bom = true;
+      rest
+    }
+    None => text,
+  };
+  let mut lines: Vec<&str> = text.split('\n').collect();
+  // split leaves an empty line after the final newline
+  let final_newline = text.ends_with('\n');
+  if final_newline {
+    lines.pop();
+  }
+  for line in lines.iter_mut() {
+    if let Some(stripped) = line.strip_suffix('\r') {
+      changes.crlf += 1;
+      *line = stripped;
+    }
+    let trimmed = line.trim_end_matches([' ', '\t']);
+    if trimmed.len() != line.len() {
+      changes.trailing_whitespace += 1;
+      *line = trimmed;
+    }
+  }
+  while lines.last().is_some_and(|line| line.is_empty()) {
+    lines.pop();
+    changes.blank_lines += 1;
+  }
+  let mut result = lines.join("\n");
+  if final_newline && !lines.is_empty() {
+    result.push('\n');
+  }
+  (result, changes)
+}
+
+/// Read the data files from the in_dir into a vector of normalised strings,
+/// with what normalising changed in each file.
+/// The days without a file are None, so that the others can still run.
+pub fn read_inputs(in_dir: &str, days: &[&str]) -> io::Result<Vec<Option<(String, Normalised)>>> {
+  if !Path::new(in_dir).is_dir() {
+    return Err(io::Error::new(io::ErrorKind::NotFound, format!("{in_dir} is not a directory")));
+  }
+  days.iter()
     .map(|&day| {
       let filename = format!("{in_dir}/{day}.txt");
-      fs::read_to_string(Path::new(&filename))
+      match fs::read_to_string(Path::new(&filename)) {
+        Ok(data) => Ok(Some(normalise(&data))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+      }
     })
-    .collect();
-  data.into_iter().collect()
+    .collect()
+}
+
+/// An error found while parsing a day's input.
+/// The line and column are 1-based and refer to the text given to the generator.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+  pub day: Option<String>,
+  pub line: usize,
+  pub column: usize,
+  pub message: String,
+}
+
+impl ParseError {
+  pub fn new(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+    ParseError { day: None, line, column, message: message.into() }
+  }
+
+  /// Create an error pointing at the token, which should be a slice of the text.
+  pub fn at(text: &str, token: &str, message: impl Into<String>) -> ParseError {
+    let (line, column) = locate(text, token);
+    ParseError::new(line, column, message)
+  }
+
+  /// Move an error raised while parsing the part, a slice of the text, so that
+  /// its position is relative to the whole text.
+  pub fn within(mut self, text: &str, part: &str) -> ParseError {
+    let (line, column) = locate(text, part);
+    if self.line == 1 {
+      self.column += column - 1;
+    }
+    self.line += line - 1;
+    self
+  }
+
+  /// Record the day that produced the error
+  pub fn for_day(mut self, day: &str) -> ParseError {
+    self.day = Some(day.to_string());
+    self
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(day) = &self.day {
+      write!(f, "{day} ")?;
+    }
+    write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Find the 1-based line and column of the token within the text.
+/// Tokens that are not a slice of the text are reported at the start.
+fn locate(text: &str, token: &str) -> (usize, usize) {
+  let before = (token.as_ptr() as usize).checked_sub(text.as_ptr() as usize)
+    .and_then(|offset| text.get(..offset))
+    .unwrap_or("");
+  let line = before.matches('\n').count() + 1;
+  let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+  (line, column)
+}
+
+/// Parse the token, a slice of the text, reporting its position on failure.
+pub fn parse_at<T: FromStr>(text: &str, token: &str) -> Result<T, ParseError> {
+  token.trim().parse().map_err(|_| ParseError::at(text, token, format!("Unable to parse '{token}'")))
+}
+
+/// Which parts of a day to run
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Parts {
+  pub part1: bool,
+  pub part2: bool,
+}
+
+impl Parts {
+  pub const BOTH: Parts = Parts { part1: true, part2: true };
+
+  /// Is the given part (1 or 2) selected
+  pub fn contains(&self, part: usize) -> bool {
+    match part {
+      1 => self.part1,
+      2 => self.part2,
+      _ => false,
+    }
+  }
+
+  fn union(self, other: Parts) -> Parts {
+    Parts { part1: self.part1 || other.part1, part2: self.part2 || other.part2 }
+  }
+}
+
+/// Parse a selection of days and parts such as `1-5,12,20:2`.
+/// Returns the positions in `days` with the parts to run, in day order.
+pub fn parse_selection(spec: &str, days: &[&str]) -> Result<Vec<(usize, Parts)>, String> {
+  let mut selected: BTreeMap<usize, Parts> = BTreeMap::new();
+  for item in spec.split(',').map(str::trim) {
+    let (range, parts) = match item.split_once(':') {
+      Some((range, "1")) => (range, Parts { part1: true, part2: false }),
+      Some((range, "2")) => (range, Parts { part1: false, part2: true }),
+      Some((_, part)) => return Err(format!("Unknown part {part} in {item}")),
+      None => (item, Parts::BOTH),
+    };
+    let number = |s: &str| s.trim().parse::<usize>()
+      .map_err(|_| format!("Unable to parse day '{s}' in {item}"));
+    let (first, last) = match range.split_once('-') {
+      Some((first, last)) => (number(first)?, number(last)?),
+      None => (number(range)?, number(range)?),
+    };
+    if first > last {
+      return Err(format!("Empty range {item}"));
+    }
+    for day in first..=last {
+      let position = days.iter().position(|d| *d == format!("day{day}"))
+        .ok_or_else(|| format!("Day {day} is not implemented"))?;
+      let entry = selected.entry(position).or_insert(Parts { part1: false, part2: false });
+      *entry = entry.union(parts);
+    }
+  }
+  Ok(selected.into_iter().collect())
+}
+
+/// A day's puzzle with typed input and answers
+pub trait Solution {
+  /// The parsed input, which may borrow from the text
+  type Input<'a>: 'a;
+  type Answer1: fmt::Display;
+  type Answer2: fmt::Display;
+
+  fn generator<'a>(&self, input: &'a str) -> Result<Self::Input<'a>, ParseError>;
+  fn part1(&self, input: &Self::Input<'_>) -> Self::Answer1;
+  fn part2(&self, input: &Self::Input<'_>) -> Self::Answer2;
+}
+
+/// Define the unit struct of a day with the `Solution` that calls its `generator`, `part1` and `part2`
+#[macro_export]
+macro_rules! solution {
+  ( $day:ident, $input:ty, $answer1:ty, $answer2:ty ) => {
+    #[doc = concat!("The typed solution for ", stringify!($day))]
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct $day;
+
+    impl $crate::utils::Solution for $day {
+      type Input<'a> = $input;
+      type Answer1 = $answer1;
+      type Answer2 = $answer2;
+
+      fn generator<'a>(&self, input: &'a str) -> Result<Self::Input<'a>, $crate::utils::ParseError> {
+        generator(input)
+      }
+
+      fn part1(&self, input: &Self::Input<'_>) -> Self::Answer1 {
+        part1(input)
+      }
+
+      fn part2(&self, input: &Self::Input<'_>) -> Self::Answer2 {
+        part2(input)
+      }
+    }
+  }
+}
+
+pub use solution;
+
+/// The parts of a day whose input has been generated, with the answers as strings
+pub trait Solver {
+  fn part1(&self) -> String;
+  fn part2(&self) -> String;
+}
+
+struct Generated<T, P1, P2> {
+  input: T,
+  part1: P1,
+  part2: P2,
+}
+
+impl<T, P1: Fn(&T) -> String, P2: Fn(&T) -> String> Solver for Generated<T, P1, P2> {
+  fn part1(&self) -> String {
+    (self.part1)(&self.input)
+  }
+
+  fn part2(&self) -> String {
+    (self.part2)(&self.input)
+  }
+}
+
+/// Hide the type of a day's input behind its parts
+fn erase<'a, T: 'a>(input: T, part1: impl Fn(&T) -> String + 'a,
+                        part2: impl Fn(&T) -> String + 'a) -> Box<dyn Solver + 'a> {
+  Box::new(Generated { input, part1, part2 })
+}
+
+/// The generator of a day, producing its input ready for each part
+pub type Generator = for<'a> fn(&'a str) -> Result<Box<dyn Solver + 'a>, ParseError>;
+
+/// The type erased generator of a solution, which is a `Generator`
+pub fn solver<S: Solution + Default>(data: &str) -> Result<Box<dyn Solver + '_>, ParseError> {
+  let input = S::default().generator(data)?;
+  Ok(erase(input,
+           |i| S::default().part1(i).to_string(),
+           |i| S::default().part2(i).to_string()))
+}
+
+/// An example from a day's puzzle with the answers of the parts it is for
+#[derive(Clone, Copy, Debug)]
+pub struct Example {
+  pub input: &'static str,
+  pub part1: Option<&'static str>,
+  pub part2: Option<&'static str>,
+}
+
+impl Example {
+  /// The parts that the example has an answer for
+  pub fn parts(&self) -> Parts {
+    Parts { part1: self.part1.is_some(), part2: self.part2.is_some() }
+  }
+
+  /// The expected answer of a part, counting from 1
+  pub fn expected(&self, part: usize) -> Option<&'static str> {
+    match part {
+      1 => self.part1,
+      2 => self.part2,
+      _ => None,
+    }
+  }
+}
+
+/// A day in the registry that the runner is built on
+pub struct Registered {
+  pub name: &'static str,
+  pub generator: Generator,
+  pub examples: &'static [Example],
+  pub random_input: random::InputGenerator,
+}
+
+/// Run the selected parts of a day, timing the generator and each part.
+/// When repeated, the times are the medians and the statistics are kept.
+pub fn run_day(solution: &Registered, data: &str, parts: Parts, repeat: Repeat) -> DayResult {
+  let (day, generator) = (solution.name, solution.generator);
+  let executions = repeat.runs + repeat.warmup;
+  let (generate_memory, (generate_stats, input)) =
+    measure_memory(executions, || time_repeated(repeat, &|| generator(data)));
+  let input = match input {
+    Ok(input) => input,
+    Err(e) => return DayResult::failed(day, generate_stats.median, parts, e.for_day(day)),
+  };
+  let skipped = (None, (Stats::from(&[time::Duration::ZERO]), String::new()));
+  let (part1_memory, part1) = if parts.part1 {
+    measure_memory(executions, || time_repeated(repeat, &|| input.part1()))
+  } else {
+    skipped.clone()
+  };
+  let (part2_memory, part2) = if parts.part2 {
+    measure_memory(executions, || time_repeated(repeat, &|| input.part2()))
+  } else {
+    skipped
+  };
+  let stats = (repeat.runs > 1).then_some(PhaseStats {
+    generator: generate_stats,
+    part1: part1.0,
+    part2: part2.0,
+  });
+  let memory = generate_memory.map(|generator| PhaseMemory {
+    generator,
+    part1: part1_memory,
+    part2: part2_memory,
+  });
+  DayResult {
+    day: day.to_string(),
+    generate_time: generate_stats.median,
+    parts,
+    part1: (part1.0.median, part1.1),
+    part2: (part2.0.median, part2.1),
+    stats,
+    memory,
+    error: None,
+  }
+}
+
+/// The heap used by each phase of a day, the parts are missing when they weren't run
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PhaseMemory {
+  pub generator: MemStats,
+  pub part1: Option<MemStats>,
+  pub part2: Option<MemStats>,
+}
+
+/// The timing statistics of each phase of a repeated day
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PhaseStats {
+  pub generator: Stats,
+  pub part1: Stats,
+  pub part2: Stats,
 }
 
 /// The times and results of running a day's code.
 pub struct DayResult {
   pub day: String,
   pub generate_time: time::Duration,
+  // the parts that were run, the others are left empty
+  pub parts: Parts,
   pub part1: (time::Duration, String),
   pub part2: (time::Duration, String),
+  // only kept when the phases were repeated
+  pub stats: Option<PhaseStats>,
+  // only kept when built with the alloc-stats feature
+  pub memory: Option<PhaseMemory>,
+  pub error: Option<ParseError>,
 }
 
 impl DayResult {
+  /// Build the result for a day whose input could not be parsed
+  pub fn failed(day: &str, generate_time: time::Duration, parts: Parts, error: ParseError) -> DayResult {
+    DayResult {
+      day: day.to_string(),
+      generate_time,
+      parts,
+      part1: (time::Duration::ZERO, String::new()),
+      part2: (time::Duration::ZERO, String::new()),
+      stats: None,
+      memory: None,
+      error: Some(error),
+    }
+  }
+
   /// Return the pretty name for the day
   pub fn pretty_day(&self) -> String {
     self.day.replace("day", "Day ")
   }
 
+  /// Did the day produce answers
+  pub fn is_ok(&self) -> bool {
+    self.error.is_none()
+  }
+
+  /// Print the statistics of a phase under its line, if the day was repeated
+  fn print_stats(&self, f: &mut fmt::Formatter<'_>, phase: fn(&PhaseStats) -> Stats) -> fmt::Result {
+    match &self.stats {
+      Some(stats) => writeln!(f, "    {}", phase(stats).to_string().dimmed()),
+      None => Ok(()),
+    }
+  }
+
   /// Get the answers without the times
   pub fn get_answers(&self) -> Vec<String> {
     vec![self.part1.1.to_string(), self.part2.1.to_string()]
@@ -78,9 +610,20 @@ impl fmt::Display for DayResult {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     let duration = format!("({:.2?})", self.generate_time + self.part1.0 + self.part2.0);
     writeln!(f, "{} {}", self.pretty_day().bold(), duration.dimmed())?;
-    pretty_print(f," · Generator", self.generate_time, None)?;
-    pretty_print(f, " · Part 1", self.part1.0, Some(&self.part1.1))?;
-    pretty_print(f, " · Part 2", self.part2.0, Some(&self.part2.1))
+    pretty_print(f," · Generator", self.generate_time, self.memory.map(|m| m.generator), None)?;
+    if let Some(error) = &self.error {
+      return writeln!(f, "    {}", error.to_string().red().bold());
+    }
+    self.print_stats(f, |s| s.generator)?;
+    if self.parts.part1 {
+      pretty_print(f, " · Part 1", self.part1.0, self.memory.and_then(|m| m.part1), Some(&self.part1.1))?;
+      self.print_stats(f, |s| s.part1)?;
+    }
+    if self.parts.part2 {
+      pretty_print(f, " · Part 2", self.part2.0, self.memory.and_then(|m| m.part2), Some(&self.part2.1))?;
+      self.print_stats(f, |s| s.part2)?;
+    }
+    Ok(())
   }
 }
 
@@ -90,16 +633,14 @@ macro_rules! day_list_internal {
         // Each day's code should be in src/day?.rs.
         $(pub mod $day;)*
 
-        /// Build a lambda to run each day's code
-        pub const FUNCS : &[&dyn Fn(&str) -> $crate::utils::DayResult] = &[
-            $(&|data| {
-                let (generate_time, input) = $crate::utils::time(&|| $day::generator(data));
-                let part1 = $crate::utils::time(&|| $day::part1(&input));
-                let part2 = $crate::utils::time(&|| $day::part2(&input));
-                $crate::utils::DayResult{day: stringify!($day).to_string(),
-                          generate_time,
-                          part1: (part1.0, part1.1.to_string()),
-                          part2: (part2.0, part2.1.to_string())}},)*
+        /// The registry of every day's solution
+        pub const REGISTRY : &[$crate::utils::Registered] = &[
+            $($crate::utils::Registered {
+                name: stringify!($day),
+                generator: paste::paste!{ $crate::utils::solver::<$day::[<$day:camel>]> },
+                examples: $day::EXAMPLES,
+                random_input: $day::random_input,
+            },)*
         ];
 
         /// Define the list of implemented day names.
@@ -115,4 +656,77 @@ macro_rules! day_list {
 }
 
 pub use day_list_internal;
-pub use day_list;
\ No newline at end of file
+pub use day_list;
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+  use crate::REGISTRY;
+  use crate::utils::{normalise, parse_selection, run_day, Normalised, Parts, Repeat, Stats};
+  use crate::utils::random::seeded;
+
+  const DAYS: &[&str] = &["day1", "day2", "day3", "day5"];
+
+  #[test]
+  fn test_parse_selection() {
+    let part2 = Parts { part1: false, part2: true };
+    assert_eq!(vec![(0, Parts::BOTH), (1, Parts::BOTH), (3, part2)],
+               parse_selection("1-2,5:2", DAYS).unwrap());
+    assert_eq!(vec![(2, Parts::BOTH)], parse_selection("3:1,3:2", DAYS).unwrap());
+    assert!(parse_selection("4", DAYS).is_err());
+    assert!(parse_selection("3-1", DAYS).is_err());
+    assert!(parse_selection("1:3", DAYS).is_err());
+    assert!(parse_selection("x", DAYS).is_err());
+  }
+
+  #[test]
+  fn test_stats() {
+    let ms = Duration::from_millis;
+    let stats = Stats::from(&[ms(4), ms(1), ms(3), ms(2), ms(10)]);
+    assert_eq!(ms(1), stats.min);
+    assert_eq!(ms(3), stats.median);
+    assert_eq!(ms(4), stats.mean);
+    assert_eq!(Duration::from_secs_f64(0.001 * 10f64.sqrt()), stats.stddev);
+    assert_eq!(ms(3), Stats::from(&[ms(4), ms(2)]).median);
+  }
+
+  #[test]
+  fn test_examples() {
+    for solution in REGISTRY {
+      for example in solution.examples {
+        let result = run_day(solution, example.input, example.parts(), Repeat::ONCE);
+        assert!(result.is_ok(), "{} can't parse its example", solution.name);
+        let answers = result.get_answers();
+        for part in (1..=2).filter(|p| example.parts().contains(*p)) {
+          assert_eq!(example.expected(part), Some(answers[part - 1].as_str()),
+                     "{} part {}", solution.name, part);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_random_inputs() {
+    for solution in REGISTRY {
+      let input = |size, seed| (solution.random_input)(&mut seeded(seed), size);
+      assert_eq!(input(10, 7), input(10, 7), "{} is not reproducible", solution.name);
+      for (size, seed) in [1, 10].into_iter().flat_map(|size| (0..3).map(move |seed| (size, seed))) {
+        let result = run_day(solution, &input(size, seed), Parts::BOTH, Repeat::ONCE);
+        assert!(result.is_ok(), "{} can't parse its input for size {size} and seed {seed}", solution.name);
+      }
+    }
+  }
+
+  #[test]
+  fn test_normalise() {
+    assert_eq!((String::from("a\nb\n"), Normalised::default()), normalise("a\nb\n"));
+    assert_eq!((String::from("a"), Normalised::default()), normalise("a"));
+    let (text, changes) = normalise("\u{feff}a \r\n\r\nb\r\n\r\n \n\n");
+    assert_eq!("a\n\nb\n", text);
+    assert_eq!(Normalised { bom: true, crlf: 4, trailing_whitespace: 2, blank_lines: 3 }, changes);
+    assert_eq!("removed the byte order mark, converted 4 CRLF line ending(s), \
+                trimmed trailing whitespace from 2 line(s), removed 3 trailing blank line(s)",
+               changes.to_string());
+    assert_eq!((String::new(), Normalised { blank_lines: 2, ..Normalised::default() }), normalise("\n\n"));
+  }
+}
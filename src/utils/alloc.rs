@@ -0,0 +1,78 @@
+//! A global allocator that counts the allocations and heap usage of each thread,
+//! so that the runner can report them for each phase of a day.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use crate::utils::MemStats;
+
+thread_local! {
+  // the bytes currently allocated by this thread, which can go negative when
+  // memory allocated by another thread is freed here
+  static CURRENT: Cell<i64> = const { Cell::new(0) };
+  static PEAK: Cell<i64> = const { Cell::new(0) };
+  static COUNT: Cell<usize> = const { Cell::new(0) };
+}
+
+pub struct CountingAlloc;
+
+fn record(change: i64, allocation: bool) {
+  // The thread locals have no destructors, but be safe during thread teardown
+  let _ = CURRENT.try_with(|current| {
+    let now = current.get() + change;
+    current.set(now);
+    PEAK.with(|peak| peak.set(peak.get().max(now)));
+    if allocation {
+      COUNT.with(|count| count.set(count.get() + 1));
+    }
+  });
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let ptr = System.alloc(layout);
+    if !ptr.is_null() {
+      record(layout.size() as i64, true);
+    }
+    ptr
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout);
+    record(-(layout.size() as i64), false);
+  }
+
+  unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+    let ptr = System.alloc_zeroed(layout);
+    if !ptr.is_null() {
+      record(layout.size() as i64, true);
+    }
+    ptr
+  }
+
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    let new_ptr = System.realloc(ptr, layout, new_size);
+    if !new_ptr.is_null() {
+      record(new_size as i64 - layout.size() as i64, true);
+    }
+    new_ptr
+  }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+/// Measure the peak heap growth and the number of allocations made by the function
+/// on this thread. The count is averaged over the given number of executions.
+pub fn measure<T>(executions: usize, func: impl FnOnce() -> T) -> (MemStats, T) {
+  let start = CURRENT.with(|c| c.get());
+  let start_count = COUNT.with(|c| c.get());
+  PEAK.with(|p| p.set(start));
+  let result = func();
+  let peak = PEAK.with(|p| p.get());
+  let count = COUNT.with(|c| c.get());
+  (MemStats {
+    peak_bytes: (peak - start).max(0) as usize,
+    allocations: (count - start_count) / executions.max(1),
+  }, result)
+}
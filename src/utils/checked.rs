@@ -0,0 +1,92 @@
+//! Arithmetic for the values that can outgrow their types on larger inputs. With the `checked`
+//! feature each operation checks for overflow and panics at its caller, so the run reports the day
+//! and the line of the first overflow. Without it they are the plain operators.
+
+use std::fmt::Display;
+
+use num::traits::{AsPrimitive, CheckedAdd, CheckedMul, CheckedSub, Zero};
+
+#[track_caller]
+#[inline]
+pub fn add<T: CheckedAdd + Copy + Display>(a: T, b: T) -> T {
+  if cfg!(feature = "checked") {
+    match a.checked_add(&b) {
+      Some(v) => v,
+      None => panic!("Overflow in {a} + {b}"),
+    }
+  } else {
+    a + b
+  }
+}
+
+#[track_caller]
+#[inline]
+pub fn sub<T: CheckedSub + Copy + Display>(a: T, b: T) -> T {
+  if cfg!(feature = "checked") {
+    match a.checked_sub(&b) {
+      Some(v) => v,
+      None => panic!("Overflow in {a} - {b}"),
+    }
+  } else {
+    a - b
+  }
+}
+
+#[track_caller]
+#[inline]
+pub fn mul<T: CheckedMul + Copy + Display>(a: T, b: T) -> T {
+  if cfg!(feature = "checked") {
+    match a.checked_mul(&b) {
+      Some(v) => v,
+      None => panic!("Overflow in {a} * {b}"),
+    }
+  } else {
+    a * b
+  }
+}
+
+#[track_caller]
+pub fn sum<T: CheckedAdd + Copy + Display + Zero>(values: impl IntoIterator<Item = T>) -> T {
+  let mut total = T::zero();
+  for v in values {
+    total = add(total, v);
+  }
+  total
+}
+
+/// Convert to a type that may not hold the value, which truncates without the feature
+#[track_caller]
+#[inline]
+pub fn cast<T: Copy + 'static, U: AsPrimitive<T> + TryInto<T> + Display>(value: U) -> T {
+  if cfg!(feature = "checked") {
+    match value.try_into() {
+      Ok(v) => v,
+      Err(_) => panic!("Overflow converting {value} to {}", std::any::type_name::<T>()),
+    }
+  } else {
+    value.as_()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::checked::{add, cast, mul, sub, sum};
+
+  #[test]
+  fn test_arithmetic() {
+    assert_eq!(7, add(3, 4));
+    assert_eq!(-1, sub(3, 4));
+    assert_eq!(12_u64, mul(3, 4));
+    assert_eq!(10, sum([1, 2, 3, 4]));
+    assert_eq!(200_u32, cast(200_u64));
+  }
+
+  #[test]
+  #[cfg(feature = "checked")]
+  fn test_overflow() {
+    let overflow = std::panic::catch_unwind(|| mul(u32::MAX, 2)).unwrap_err();
+    assert_eq!(Some(&"Overflow in 4294967295 * 2".to_string()), overflow.downcast_ref::<String>());
+    assert!(std::panic::catch_unwind(|| sum([i32::MAX, 1])).is_err());
+    assert!(std::panic::catch_unwind(|| cast::<u32, u64>(1 << 32)).is_err());
+  }
+}
@@ -0,0 +1,123 @@
+//! Finding where a sequence of states starts to repeat, so the state after a huge number of
+//! steps can be found from the steps before the first repeat.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The steps before a sequence of states reaches its cycle and the number of steps in the cycle
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+  pub start: usize,
+  pub len: usize,
+}
+
+impl Cycle {
+  /// The step before the first repeat that has the same state as step n
+  pub fn index(&self, n: usize) -> usize {
+    if n < self.start {
+      n
+    } else {
+      self.start + (n - self.start) % self.len
+    }
+  }
+
+  /// The state at step n, found by stepping from the initial state
+  pub fn state_at<S>(&self, initial: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let mut state = initial;
+    for _ in 0..self.index(n) {
+      state = step(&state);
+    }
+    state
+  }
+}
+
+/// Brent's algorithm, which keeps two states at a time but steps from the initial state twice
+pub fn brent<S: Clone + Eq>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+  // The hare runs ahead in powers of two until it meets the tortoise, which gives the length
+  let mut power = 1;
+  let mut len = 1;
+  let mut tortoise = initial.clone();
+  let mut hare = step(&initial);
+  while tortoise != hare {
+    if power == len {
+      tortoise = hare.clone();
+      power *= 2;
+      len = 0;
+    }
+    hare = step(&hare);
+    len += 1;
+  }
+
+  // With the hare a cycle ahead, they first meet at the start of the cycle
+  let mut tortoise = initial.clone();
+  let mut hare = initial;
+  for _ in 0..len {
+    hare = step(&hare);
+  }
+  let mut start = 0;
+  while tortoise != hare {
+    tortoise = step(&tortoise);
+    hare = step(&hare);
+    start += 1;
+  }
+  Cycle { start, len }
+}
+
+/// Remember every state until one repeats, which steps once and returns the states before the
+/// repeat so any step can be looked up by its index
+pub fn hashed<S: Clone + Eq + Hash>(initial: S, mut step: impl FnMut(&S) -> S) -> (Cycle, Vec<S>) {
+  hashed_by(initial, |s| *s = step(s), S::clone, S::clone)
+}
+
+/// Like `hashed` for a large state, which is stepped in place and only remembered by its key,
+/// returning a value of each state before the repeat
+pub fn hashed_by<S, K: Eq + Hash, V>(mut state: S, mut step: impl FnMut(&mut S), key: impl Fn(&S) -> K,
+                                     value: impl Fn(&S) -> V) -> (Cycle, Vec<V>) {
+  let mut seen = HashMap::new();
+  let mut values = Vec::new();
+  loop {
+    match seen.entry(key(&state)) {
+      Entry::Occupied(e) => {
+        let start = *e.get();
+        return (Cycle { start, len: values.len() - start }, values);
+      }
+      Entry::Vacant(e) => {
+        e.insert(values.len());
+      }
+    }
+    values.push(value(&state));
+    step(&mut state);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::cycle::{brent, hashed, hashed_by, Cycle};
+
+  /// 0, 1, 2 and then 3 to 9 over and over
+  fn step(n: &u32) -> u32 {
+    if *n == 9 { 3 } else { n + 1 }
+  }
+
+  #[test]
+  fn test_cycle() {
+    let cycle = Cycle { start: 3, len: 7 };
+    assert_eq!(cycle, brent(0, step));
+    assert_eq!(Cycle { start: 0, len: 7 }, brent(5, step));
+
+    let (hashed_cycle, states) = hashed(0, step);
+    assert_eq!(cycle, hashed_cycle);
+    assert_eq!(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9], states);
+
+    let (keyed_cycle, squares) = hashed_by(0, |n| *n = step(n), |n| n % 10, |n| n * n);
+    assert_eq!(cycle, keyed_cycle);
+    assert_eq!(vec![0, 1, 4, 9, 16, 25, 36, 49, 64, 81], squares);
+
+    assert_eq!(2, cycle.index(2));
+    assert_eq!(9, cycle.index(9));
+    assert_eq!(3, cycle.index(10));
+    assert_eq!(6, cycle.index(1_000_000_000));
+    assert_eq!(6, cycle.state_at(0, step, 1_000_000_000));
+  }
+}
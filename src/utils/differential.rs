@@ -0,0 +1,73 @@
+//! Differential testing of the days against slow but obviously correct references. Several days
+//! take shortcuts that only hold for inputs shaped like the puzzle's, so each of those has a
+//! `reference` module, and this runs both on random inputs to find where they disagree.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::utils::ParseError;
+use crate::utils::random::{seeded, InputGenerator};
+
+/// A random input on which a day and its reference gave different answers
+#[derive(Debug, Eq, PartialEq)]
+pub struct Disagreement {
+  pub size: usize,
+  pub seed: u64,
+  pub expected: String,
+  pub actual: String,
+}
+
+impl fmt::Display for Disagreement {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "size {} and seed {}: the reference gives {} but the day gives {}",
+           self.size, self.seed, self.expected, self.actual)
+  }
+}
+
+/// Parse the random input of the size from each seed and compare the day's answer with the
+/// reference's, returning every input they disagree on
+pub fn compare<T, A: PartialEq + fmt::Display>(random_input: InputGenerator,
+                                               generator: impl Fn(&str) -> Result<T, ParseError>,
+                                               size: usize, seeds: Range<u64>, answer: impl Fn(&T) -> A,
+                                               reference: impl Fn(&T) -> A) -> Vec<Disagreement> {
+  seeds.filter_map(|seed| {
+    let input = random_input(&mut seeded(seed), size);
+    let parsed = generator(&input)
+      .unwrap_or_else(|e| panic!("Can't parse the input of size {size} and seed {seed}: {e}"));
+    let (expected, actual) = (reference(&parsed), answer(&parsed));
+    (expected != actual).then(|| Disagreement {
+      size,
+      seed,
+      expected: expected.to_string(),
+      actual: actual.to_string(),
+    })
+  }).collect()
+}
+
+/// Compare the day with the reference, panicking with every disagreement
+#[track_caller]
+pub fn check<T, A: PartialEq + fmt::Display>(random_input: InputGenerator,
+                                             generator: impl Fn(&str) -> Result<T, ParseError>,
+                                             size: usize, seeds: Range<u64>, answer: impl Fn(&T) -> A,
+                                             reference: impl Fn(&T) -> A) {
+  let found = compare(random_input, generator, size, seeds, answer, reference);
+  let report: Vec<String> = found.iter().map(|d| d.to_string()).collect();
+  assert!(found.is_empty(), "{} disagreement(s) with the reference\n{}", found.len(), report.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::day1;
+  use crate::utils::differential::{check, compare, Disagreement};
+
+  #[test]
+  fn test_compare() {
+    check(day1::random_input, day1::generator, 10, 0..3, |d| day1::part1(d), |d| day1::part1(d));
+
+    let found = compare(day1::random_input, day1::generator, 10, 0..3,
+                        |d| day1::part1(d), |d| day1::part1(d) + 1);
+    assert_eq!(vec![0, 1, 2], found.iter().map(|d| d.seed).collect::<Vec<_>>());
+    let d = Disagreement { size: 10, seed: 2, expected: "5".to_string(), actual: "4".to_string() };
+    assert_eq!("size 10 and seed 2: the reference gives 5 but the day gives 4", d.to_string());
+  }
+}
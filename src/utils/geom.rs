@@ -0,0 +1,263 @@
+//! Points in two and three dimensions and the four directions on a map.
+//! On a map y grows downwards, so north is a step of (0, -1).
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use crate::utils::geom::Dir4::{East, North, South, West};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point2<T> {
+  pub x: T,
+  pub y: T,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Point3<T> {
+  pub x: T,
+  pub y: T,
+  pub z: T,
+}
+
+impl<T> Point2<T> {
+  pub const fn new(x: T, y: T) -> Point2<T> {
+    Point2 { x, y }
+  }
+}
+
+impl<T> Point3<T> {
+  pub const fn new(x: T, y: T, z: T) -> Point3<T> {
+    Point3 { x, y, z }
+  }
+}
+
+/// The distance between two values of a possibly unsigned type
+fn distance<T: Copy + PartialOrd + Sub<Output = T>>(a: T, b: T) -> T {
+  if a < b { b - a } else { a - b }
+}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>> Point2<T> {
+  /// The number of steps along the axes between the points
+  pub fn manhattan(&self, other: &Point2<T>) -> T {
+    distance(self.x, other.x) + distance(self.y, other.y)
+  }
+}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>> Point3<T> {
+  /// The number of steps along the axes between the points
+  pub fn manhattan(&self, other: &Point3<T>) -> T {
+    distance(self.x, other.x) + distance(self.y, other.y) + distance(self.z, other.z)
+  }
+}
+
+/// Implement the element wise arithmetic and the scaling of a point type
+macro_rules! point_ops {
+  ($point:ident, $($field:ident),+) => {
+    impl<T: Add<Output = T>> Add for $point<T> {
+      type Output = $point<T>;
+
+      fn add(self, other: $point<T>) -> $point<T> {
+        $point { $($field: self.$field + other.$field),+ }
+      }
+    }
+
+    impl<T: Sub<Output = T>> Sub for $point<T> {
+      type Output = $point<T>;
+
+      fn sub(self, other: $point<T>) -> $point<T> {
+        $point { $($field: self.$field - other.$field),+ }
+      }
+    }
+
+    impl<T: Copy + Mul<Output = T>> Mul<T> for $point<T> {
+      type Output = $point<T>;
+
+      fn mul(self, scale: T) -> $point<T> {
+        $point { $($field: self.$field * scale),+ }
+      }
+    }
+
+    impl<T: Neg<Output = T>> Neg for $point<T> {
+      type Output = $point<T>;
+
+      fn neg(self) -> $point<T> {
+        $point { $($field: -self.$field),+ }
+      }
+    }
+
+    impl<T: AddAssign> AddAssign for $point<T> {
+      fn add_assign(&mut self, other: $point<T>) {
+        $(self.$field += other.$field;)+
+      }
+    }
+
+    impl<T: SubAssign> SubAssign for $point<T> {
+      fn sub_assign(&mut self, other: $point<T>) {
+        $(self.$field -= other.$field;)+
+      }
+    }
+  }
+}
+
+point_ops!(Point2, x, y);
+point_ops!(Point3, x, y, z);
+
+impl<T> From<(T, T)> for Point2<T> {
+  fn from((x, y): (T, T)) -> Point2<T> {
+    Point2 { x, y }
+  }
+}
+
+impl<T> From<Point2<T>> for (T, T) {
+  fn from(p: Point2<T>) -> (T, T) {
+    (p.x, p.y)
+  }
+}
+
+impl<T> From<(T, T, T)> for Point3<T> {
+  fn from((x, y, z): (T, T, T)) -> Point3<T> {
+    Point3 { x, y, z }
+  }
+}
+
+impl<T> From<[T; 3]> for Point3<T> {
+  fn from([x, y, z]: [T; 3]) -> Point3<T> {
+    Point3 { x, y, z }
+  }
+}
+
+impl<T> From<Point3<T>> for (T, T, T) {
+  fn from(p: Point3<T>) -> (T, T, T) {
+    (p.x, p.y, p.z)
+  }
+}
+
+impl Point2<usize> {
+  /// The point with signed coordinates, for taking steps that may leave the map
+  pub fn signed(&self) -> Point2<i64> {
+    Point2::new(self.x as i64, self.y as i64)
+  }
+}
+
+impl Point2<i64> {
+  /// The point with unsigned coordinates, or None when it is left of or above the origin
+  pub fn unsigned(&self) -> Option<Point2<usize>> {
+    Some(Point2::new(self.x.try_into().ok()?, self.y.try_into().ok()?))
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "({}, {})", self.x, self.y)
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "({}, {}, {})", self.x, self.y, self.z)
+  }
+}
+
+/// The four directions in clockwise order, which is also the order of their indexes
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Dir4 {
+  North,
+  East,
+  South,
+  West,
+}
+
+impl Dir4 {
+  pub const ALL: [Dir4; 4] = [North, East, South, West];
+
+  pub fn turn_left(self) -> Dir4 {
+    Dir4::ALL[(self as usize + 3) % 4]
+  }
+
+  pub fn turn_right(self) -> Dir4 {
+    Dir4::ALL[(self as usize + 1) % 4]
+  }
+
+  pub fn reverse(self) -> Dir4 {
+    Dir4::ALL[(self as usize + 2) % 4]
+  }
+
+  /// The step of moving one cell in the direction
+  pub fn delta(self) -> Point2<i64> {
+    match self {
+      North => Point2::new(0, -1),
+      East => Point2::new(1, 0),
+      South => Point2::new(0, 1),
+      West => Point2::new(-1, 0),
+    }
+  }
+
+  pub fn is_horizontal(self) -> bool {
+    matches!(self, East | West)
+  }
+}
+
+impl TryFrom<char> for Dir4 {
+  type Error = String;
+
+  /// Parse either U, R, D and L or N, E, S and W
+  fn try_from(c: char) -> Result<Dir4, String> {
+    match c {
+      'U' | 'N' => Ok(North),
+      'R' | 'E' => Ok(East),
+      'D' | 'S' => Ok(South),
+      'L' | 'W' => Ok(West),
+      _ => Err(format!("Unexpected direction {c}")),
+    }
+  }
+}
+
+impl FromStr for Dir4 {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Dir4, String> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => Dir4::try_from(c),
+      _ => Err(format!("Unexpected direction {s}")),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::geom::{Dir4, Point2, Point3};
+  use crate::utils::geom::Dir4::{East, North, South, West};
+
+  #[test]
+  fn test_points() {
+    let p = Point2::new(3, -2);
+    assert_eq!(Point2::new(4, -4), p + Point2::new(1, -2));
+    assert_eq!(Point2::new(2, 0), p - Point2::new(1, -2));
+    assert_eq!(Point2::new(6, -4), p * 2);
+    assert_eq!(7, p.manhattan(&Point2::new(-1, 1)));
+    assert_eq!(5, Point2::new(1_usize, 4).manhattan(&Point2::new(3, 1)));
+    assert_eq!((3, -2), p.into());
+    assert_eq!(None, p.unsigned());
+    assert_eq!(Point2::new(1_i64, 4), Point2::new(1_usize, 4).signed());
+
+    let p = Point3::from([1, 2, 3]);
+    assert_eq!(Point3::new(2, 4, 6), p + p);
+    assert_eq!(6, p.manhattan(&Point3::default()));
+    assert_eq!("(1, 2, 3)", p.to_string());
+  }
+
+  #[test]
+  fn test_directions() {
+    assert_eq!(West, North.turn_left());
+    assert_eq!(East, North.turn_right());
+    assert_eq!(North, South.reverse());
+    assert_eq!(Point2::new(0, -1), North.delta());
+    assert!(Dir4::ALL.iter().all(|d| d.turn_left().turn_right() == *d));
+    assert_eq!(Ok(South), "D".parse());
+    assert_eq!(Ok(West), Dir4::try_from('W'));
+    assert!("X".parse::<Dir4>().is_err());
+    assert!("UU".parse::<Dir4>().is_err());
+  }
+}
@@ -0,0 +1,376 @@
+//! An adjacency list graph over interned nodes, and the searches the days share: breadth first
+//! distances, Dijkstra and A* over any state type, connected components and the longest path.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The index of a node in its graph, given out in the order the nodes are added
+pub type NodeId = usize;
+
+/// A graph whose nodes are keys of type K and whose edges have weights of type W
+#[derive(Clone, Debug)]
+pub struct Graph<K, W> {
+  ids: HashMap<K, NodeId>,
+  keys: Vec<K>,
+  edges: Vec<Vec<(NodeId, W)>>,
+}
+
+impl<K: Clone + Eq + Hash, W: Copy> Default for Graph<K, W> {
+  fn default() -> Self {
+    Graph { ids: HashMap::new(), keys: Vec::new(), edges: Vec::new() }
+  }
+}
+
+impl<K: Clone + Eq + Hash, W: Copy> Graph<K, W> {
+  pub fn new() -> Graph<K, W> {
+    Graph::default()
+  }
+
+  /// The id of the node, adding it when it is new
+  pub fn node(&mut self, key: K) -> NodeId {
+    match self.ids.entry(key) {
+      Entry::Occupied(e) => *e.get(),
+      Entry::Vacant(e) => {
+        let id = self.keys.len();
+        self.keys.push(e.key().clone());
+        self.edges.push(Vec::new());
+        *e.insert(id)
+      }
+    }
+  }
+
+  /// The id of the node, or None when it is not in the graph
+  pub fn id(&self, key: &K) -> Option<NodeId> {
+    self.ids.get(key).copied()
+  }
+
+  pub fn key(&self, id: NodeId) -> &K {
+    &self.keys[id]
+  }
+
+  pub fn len(&self) -> usize {
+    self.keys.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.keys.is_empty()
+  }
+
+  /// The edges leaving the node with their weights
+  pub fn edges(&self, id: NodeId) -> &[(NodeId, W)] {
+    &self.edges[id]
+  }
+
+  pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    self.edges[id].iter().map(|(to, _)| *to)
+  }
+
+  /// The weight of the edge between the nodes, if there is one
+  pub fn edge(&self, from: NodeId, to: NodeId) -> Option<W> {
+    self.edges[from].iter().find(|(n, _)| *n == to).map(|(_, w)| *w)
+  }
+
+  /// Add an edge in one direction, replacing and returning the weight of an existing one
+  pub fn insert_edge(&mut self, from: NodeId, to: NodeId, weight: W) -> Option<W> {
+    match self.edges[from].iter_mut().find(|(n, _)| *n == to) {
+      Some((_, w)) => Some(std::mem::replace(w, weight)),
+      None => {
+        self.edges[from].push((to, weight));
+        None
+      }
+    }
+  }
+
+  /// Add an edge in both directions, replacing and returning the weight of an existing one
+  pub fn insert_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: W) -> Option<W> {
+    self.insert_edge(b, a, weight);
+    self.insert_edge(a, b, weight)
+  }
+
+  /// Remove the edge in both directions
+  pub fn remove_undirected_edge(&mut self, a: NodeId, b: NodeId) {
+    self.edges[a].retain(|(n, _)| *n != b);
+    self.edges[b].retain(|(n, _)| *n != a);
+  }
+
+  /// The number of edges from the start to each node, or None when it cannot be reached
+  pub fn bfs_distances(&self, start: NodeId) -> Vec<Option<u32>> {
+    let mut distances = vec![None; self.len()];
+    distances[start] = Some(0);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((id, d)) = queue.pop_front() {
+      for n in self.neighbors(id) {
+        if distances[n].is_none() {
+          distances[n] = Some(d + 1);
+          queue.push_back((n, d + 1));
+        }
+      }
+    }
+    distances
+  }
+
+  /// The ids of the nodes in each group that is connected, following the edges both ways
+  pub fn components(&self) -> Vec<Vec<NodeId>> {
+    let mut reverse = vec![Vec::new(); self.len()];
+    for (from, edges) in self.edges.iter().enumerate() {
+      for (to, _) in edges {
+        reverse[*to].push(from);
+      }
+    }
+
+    let mut seen = vec![false; self.len()];
+    let mut components = Vec::new();
+    for root in 0..self.len() {
+      if seen[root] { continue; }
+      seen[root] = true;
+      let mut component = Vec::new();
+      let mut pending = vec![root];
+      while let Some(id) = pending.pop() {
+        component.push(id);
+        for n in self.neighbors(id).chain(reverse[id].iter().copied()) {
+          if !seen[n] {
+            seen[n] = true;
+            pending.push(n);
+          }
+        }
+      }
+      components.push(component);
+    }
+    components
+  }
+}
+
+impl<K: Clone + Eq + Hash, W: Copy + Ord + Default + Add<Output = W>> Graph<K, W> {
+  /// The heaviest path from the start to the end that visits no node twice. This searches every
+  /// path, keeping the visited nodes in a bitmask when the graph has at most 64 nodes.
+  pub fn longest_path(&self, start: NodeId, end: NodeId) -> Option<W> {
+    if self.len() > 64 {
+      let mut visited = vec![false; self.len()];
+      visited[start] = true;
+      return self.longest_path_from(start, end, &mut visited);
+    }
+    let mut longest = None;
+    let mut stack = vec![(start, W::default(), 1_u64 << start)];
+    while let Some((id, length, visited)) = stack.pop() {
+      if id == end {
+        longest = longest.max(Some(length));
+        continue;
+      }
+      for (n, w) in &self.edges[id] {
+        if visited & 1 << n == 0 {
+          stack.push((*n, length + *w, visited | 1 << n));
+        }
+      }
+    }
+    longest
+  }
+
+  /// The heaviest path from the node to the end through nodes not yet visited
+  fn longest_path_from(&self, id: NodeId, end: NodeId, visited: &mut [bool]) -> Option<W> {
+    if id == end {
+      return Some(W::default());
+    }
+    let mut longest = None;
+    for (n, w) in &self.edges[id] {
+      if !visited[*n] {
+        visited[*n] = true;
+        longest = longest.max(self.longest_path_from(*n, end, visited).map(|l| l + *w));
+        visited[*n] = false;
+      }
+    }
+    longest
+  }
+}
+
+/// The number of steps from the start to every state reached within the maximum distance,
+/// with the next states of each given by the function
+pub fn bfs<S, I>(start: S, max_distance: u32, mut next: impl FnMut(&S) -> I) -> HashMap<S, u32>
+  where S: Clone + Eq + Hash, I: IntoIterator<Item = S> {
+  let mut distances = HashMap::from([(start.clone(), 0)]);
+  let mut queue = VecDeque::from([(start, 0)]);
+  while let Some((state, d)) = queue.pop_front() {
+    if d == max_distance { continue; }
+    for n in next(&state) {
+      if let Entry::Vacant(e) = distances.entry(n.clone()) {
+        e.insert(d + 1);
+        queue.push_back((n, d + 1));
+      }
+    }
+  }
+  distances
+}
+
+/// Where a search keeps the cheapest cost found to each state
+pub trait Costs<S, C> {
+  fn get(&self, state: &S) -> Option<C>;
+  fn set(&mut self, state: &S, cost: C);
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Costs<S, C> for HashMap<S, C> {
+  fn get(&self, state: &S) -> Option<C> {
+    HashMap::get(self, state).copied()
+  }
+
+  fn set(&mut self, state: &S, cost: C) {
+    self.insert(state.clone(), cost);
+  }
+}
+
+/// The costs in a table, for states that can be numbered densely such as the cells of a grid
+pub struct IndexedCosts<C, F> {
+  costs: Vec<Option<C>>,
+  index: F,
+}
+
+impl<C: Copy, F> IndexedCosts<C, F> {
+  /// A table for the states numbered below the capacity by the index function
+  pub fn new(capacity: usize, index: F) -> IndexedCosts<C, F> {
+    IndexedCosts { costs: vec![None; capacity], index }
+  }
+}
+
+impl<S, C: Copy, F: Fn(&S) -> usize> Costs<S, C> for IndexedCosts<C, F> {
+  fn get(&self, state: &S) -> Option<C> {
+    self.costs[(self.index)(state)]
+  }
+
+  fn set(&mut self, state: &S, cost: C) {
+    self.costs[(self.index)(state)] = Some(cost);
+  }
+}
+
+/// The first goal reached from any of the starts and its cost, with the next states of each
+/// given with the cost of the move by the function, keeping the best costs in the store
+pub fn dijkstra<S, C, I>(starts: impl IntoIterator<Item = S>, next: impl FnMut(&S) -> I,
+                         is_goal: impl FnMut(&S) -> bool, best: impl Costs<S, C>) -> Option<(S, C)>
+  where C: Copy + Ord + Default + Add<Output = C>, I: IntoIterator<Item = (S, C)> {
+  astar(starts, next, |_| C::default(), is_goal, best)
+}
+
+/// Dijkstra guided by a heuristic, which must never overestimate the cost to the goal
+pub fn astar<S, C, I>(starts: impl IntoIterator<Item = S>, mut next: impl FnMut(&S) -> I,
+                      mut heuristic: impl FnMut(&S) -> C, mut is_goal: impl FnMut(&S) -> bool,
+                      mut best: impl Costs<S, C>) -> Option<(S, C)>
+  where C: Copy + Ord + Default + Add<Output = C>, I: IntoIterator<Item = (S, C)> {
+  let mut heap = BinaryHeap::new();
+  for state in starts {
+    best.set(&state, C::default());
+    heap.push(Queued { priority: heuristic(&state), cost: C::default(), state });
+  }
+
+  while let Some(Queued { cost, state, .. }) = heap.pop() {
+    if is_goal(&state) {
+      return Some((state, cost));
+    }
+    if best.get(&state).is_some_and(|b| b < cost) {
+      // A cheaper way to this state was already found
+      continue;
+    }
+    for (n, step) in next(&state) {
+      let cost = cost + step;
+      if best.get(&n).is_none_or(|b| cost < b) {
+        best.set(&n, cost);
+        heap.push(Queued { priority: cost + heuristic(&n), cost, state: n });
+      }
+    }
+  }
+  None
+}
+
+/// A state on the heap, ordered so the lowest priority is popped first
+struct Queued<S, C> {
+  priority: C,
+  cost: C,
+  state: S,
+}
+
+impl<S, C: Ord> PartialEq for Queued<S, C> {
+  fn eq(&self, other: &Self) -> bool {
+    self.priority == other.priority
+  }
+}
+
+impl<S, C: Ord> Eq for Queued<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Queued<S, C> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<S, C: Ord> Ord for Queued<S, C> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.priority.cmp(&self.priority)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use crate::utils::graph::{astar, bfs, dijkstra, Graph, IndexedCosts};
+
+  /// a - b - c - d with a shortcut from a to d, and a separate e - f
+  fn graph() -> Graph<char, u32> {
+    let mut g = Graph::new();
+    for (a, b, w) in [('a', 'b', 1), ('b', 'c', 2), ('c', 'd', 3), ('a', 'd', 10), ('e', 'f', 1)] {
+      let (a, b) = (g.node(a), g.node(b));
+      g.insert_undirected_edge(a, b, w);
+    }
+    g
+  }
+
+  #[test]
+  fn test_graph() {
+    let mut g = graph();
+    assert_eq!(6, g.len());
+    assert_eq!(Some(3), g.id(&'d'));
+    assert_eq!(&'e', g.key(4));
+    assert_eq!(Some(10), g.edge(3, 0));
+    assert_eq!(Some(10), g.insert_undirected_edge(0, 3, 9));
+    assert_eq!(2, g.edges(0).len());
+
+    assert_eq!(vec![Some(0), Some(1), Some(2), Some(1), None, None], g.bfs_distances(0));
+    let mut sizes: Vec<usize> = g.components().iter().map(|c| c.len()).collect();
+    sizes.sort();
+    assert_eq!(vec![2, 4], sizes);
+    assert_eq!(Some(9), g.longest_path(0, 3));
+    assert_eq!(None, g.longest_path(0, 4));
+
+    g.remove_undirected_edge(0, 1);
+    assert_eq!(2, g.components().len());
+    g.remove_undirected_edge(2, 3);
+    assert_eq!(3, g.components().len());
+  }
+
+  #[test]
+  fn test_longest_path_of_many_nodes() {
+    // A chain of 100 nodes with shortcuts from the start to the middle and to the end
+    let mut g: Graph<u32, u32> = Graph::new();
+    for i in 1..100 {
+      let (a, b) = (g.node(i - 1), g.node(i));
+      g.insert_undirected_edge(a, b, 1);
+    }
+    g.insert_undirected_edge(0, 50, 60);
+    g.insert_undirected_edge(0, 99, 80);
+    assert_eq!(100, g.len());
+    assert_eq!(Some(109), g.longest_path(0, 99));
+    assert_eq!(Some(129), g.longest_path(0, 50));
+  }
+
+  #[test]
+  fn test_searches() {
+    let g = graph();
+    let next = |id: &usize| g.edges(*id).to_vec();
+    assert_eq!(Some((3, 6)), dijkstra([0], next, |id| *id == 3, HashMap::new()));
+    assert_eq!(Some((3, 6)), dijkstra([0], next, |id| *id == 3, IndexedCosts::new(g.len(), |id: &usize| *id)));
+    assert_eq!(None, dijkstra([0], next, |id| *id == 4, IndexedCosts::new(g.len(), |id: &usize| *id)));
+    assert_eq!(Some((3, 6)), astar([0], next, |id| if *id == 3 { 0 } else { 1 }, |id| *id == 3, HashMap::new()));
+
+    let distances = bfs(0_i32, 3, |n| [n - 1, n + 1]);
+    assert_eq!(7, distances.len());
+    assert_eq!(Some(&3), distances.get(&-3));
+  }
+}
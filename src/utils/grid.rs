@@ -0,0 +1,258 @@
+//! A rectangular grid stored row by row in a single vector, shared by the days whose
+//! input is a map of characters.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+use crate::utils::ParseError;
+
+/// The offsets of the four neighbors of a cell: left, right, up and down
+pub const NEIGHBORS: [(i64, i64); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// The offsets of the eight neighbors of a cell, including the diagonals
+pub const NEIGHBORS8: [(i64, i64); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0),
+                                         (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// A cell that can be parsed from a single character, so the grid can implement `FromStr`
+pub trait Cell: Sized {
+  fn from_char(c: char) -> Option<Self>;
+}
+
+impl Cell for char {
+  fn from_char(c: char) -> Option<char> {
+    Some(c)
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+  /// Parse each line of the input as a row, with the parser given the (x, y) of each cell.
+  /// The name of the grid is used in the error for an empty input.
+  pub fn parse(input: &str, name: &str,
+               mut cell: impl FnMut((usize, usize), char) -> Result<T, String>) -> Result<Grid<T>, ParseError> {
+    let rows: Vec<Vec<T>> = input.lines().enumerate()
+      .map(|(y, line)| line.chars().enumerate()
+        .map(|(x, c)| cell((x, y), c).map_err(|message| ParseError::new(y + 1, x + 1, message)))
+        .collect())
+      .collect::<Result<_, _>>()?;
+    Grid::from_rows(rows, name)
+  }
+
+  /// Build the grid from rows, which must all have the same length
+  pub fn from_rows(rows: Vec<Vec<T>>, name: &str) -> Result<Grid<T>, ParseError> {
+    let width = rows.first().map(|row| row.len()).unwrap_or(0);
+    if width == 0 {
+      return Err(ParseError::new(1, 1, format!("Expected a {name}")));
+    }
+    if let Some(y) = rows.iter().position(|row| row.len() != width) {
+      return Err(ParseError::new(y + 1, 1, format!("Expected {width} columns but found {}", rows[y].len())));
+    }
+    let height = rows.len();
+    Ok(Grid { width, height, cells: rows.into_iter().flatten().collect() })
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  /// Is the position inside the grid
+  pub fn contains(&self, x: i64, y: i64) -> bool {
+    x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+  }
+
+  /// The cell at the position, or None when it is outside the grid
+  pub fn get(&self, x: i64, y: i64) -> Option<&T> {
+    self.contains(x, y).then(|| &self.cells[y as usize * self.width + x as usize])
+  }
+
+  pub fn get_mut(&mut self, x: i64, y: i64) -> Option<&mut T> {
+    if self.contains(x, y) {
+      Some(&mut self.cells[y as usize * self.width + x as usize])
+    } else {
+      None
+    }
+  }
+
+  /// The cell at the position on a grid that repeats in every direction
+  pub fn get_wrapping(&self, x: i64, y: i64) -> &T {
+    let x = x.rem_euclid(self.width as i64) as usize;
+    let y = y.rem_euclid(self.height as i64) as usize;
+    &self.cells[y * self.width + x]
+  }
+
+  /// The positions of the four neighbors of a cell that are inside the grid
+  pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.offsets(x, y, &NEIGHBORS)
+  }
+
+  /// The positions of the eight neighbors of a cell that are inside the grid
+  pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+    self.offsets(x, y, &NEIGHBORS8)
+  }
+
+  fn offsets<'a>(&'a self, x: usize, y: usize,
+                 offsets: &'static [(i64, i64)]) -> impl Iterator<Item = (usize, usize)> + 'a {
+    offsets.iter()
+      .map(move |(dx, dy)| (x as i64 + dx, y as i64 + dy))
+      .filter(|(x, y)| self.contains(*x, *y))
+      .map(|(x, y)| (x as usize, y as usize))
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    self.cells.chunks_exact(self.width)
+  }
+
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    self.cells[x..].iter().step_by(self.width)
+  }
+
+  pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+    (0..self.width).map(|x| self.column(x))
+  }
+
+  /// Every cell with its (x, y), row by row
+  pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+    self.cells.iter().enumerate().map(|(i, cell)| ((i % self.width, i / self.width), cell))
+  }
+
+  /// The (x, y) of the first cell, row by row, that matches
+  pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<(usize, usize)> {
+    self.cells.iter().position(predicate).map(|i| (i % self.width, i / self.width))
+  }
+
+  pub fn map<U>(&self, func: impl FnMut(&T) -> U) -> Grid<U> {
+    Grid { width: self.width, height: self.height, cells: self.cells.iter().map(func).collect() }
+  }
+
+  /// Build a grid from a function of each (x, y)
+  fn from_fn(width: usize, height: usize, mut func: impl FnMut(usize, usize) -> T) -> Grid<T> {
+    let cells = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)))
+      .map(|(x, y)| func(x, y))
+      .collect();
+    Grid { width, height, cells }
+  }
+}
+
+impl<T: Clone> Grid<T> {
+  /// A grid with every cell set to the value
+  pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+    Grid { width, height, cells: vec![value; width * height] }
+  }
+
+  /// The grid with the rows as columns
+  pub fn transposed(&self) -> Grid<T> {
+    Grid::from_fn(self.height, self.width, |x, y| self[(y, x)].clone())
+  }
+
+  /// The grid turned a quarter clockwise, so the left column becomes the top row
+  pub fn rotated_clockwise(&self) -> Grid<T> {
+    Grid::from_fn(self.height, self.width, |x, y| self[(y, self.height - 1 - x)].clone())
+  }
+
+  /// The grid turned a quarter anticlockwise, so the top row becomes the left column
+  pub fn rotated_anticlockwise(&self) -> Grid<T> {
+    Grid::from_fn(self.height, self.width, |x, y| self[(self.width - 1 - y, x)].clone())
+  }
+}
+
+/// Index by (x, y), panicking outside the grid
+impl<T> Index<(usize, usize)> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, (x, y): (usize, usize)) -> &T {
+    assert!(x < self.width && y < self.height, "({x}, {y}) is outside the {}x{} grid", self.width, self.height);
+    &self.cells[y * self.width + x]
+  }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+  fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+    assert!(x < self.width && y < self.height, "({x}, {y}) is outside the {}x{} grid", self.width, self.height);
+    &mut self.cells[y * self.width + x]
+  }
+}
+
+impl<T: Cell> FromStr for Grid<T> {
+  type Err = ParseError;
+
+  fn from_str(input: &str) -> Result<Grid<T>, ParseError> {
+    Grid::parse(input, "grid", |_, c| T::from_char(c).ok_or_else(|| format!("Unexpected character {c}")))
+  }
+}
+
+/// Each row on its own line
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (y, row) in self.rows().enumerate() {
+      if y > 0 {
+        writeln!(f)?;
+      }
+      for cell in row {
+        write!(f, "{cell}")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::grid::Grid;
+
+  const INPUT: &str = "abc\ndef";
+
+  #[test]
+  fn test_parse() {
+    let g: Grid<char> = INPUT.parse().unwrap();
+    assert_eq!((3, 2), (g.width(), g.height()));
+    assert_eq!('f', g[(2, 1)]);
+    assert_eq!(INPUT, g.to_string());
+
+    let e = "ab\nabc".parse::<Grid<char>>().err().unwrap();
+    assert_eq!((2, "Expected 2 columns but found 3"), (e.line, e.message.as_str()));
+    let e = Grid::parse("ab\na?", "map", |_, c| if c == '?' { Err(String::from("bad")) } else { Ok(c) })
+      .err().unwrap();
+    assert_eq!((2, 2), (e.line, e.column));
+    assert_eq!("Expected a map", Grid::parse("", "map", |_, c| Ok(c)).err().unwrap().message);
+  }
+
+  #[test]
+  fn test_access() {
+    let g: Grid<char> = INPUT.parse().unwrap();
+    assert_eq!(Some(&'a'), g.get(0, 0));
+    assert_eq!(None, g.get(-1, 0));
+    assert_eq!(None, g.get(3, 0));
+    assert_eq!(&'f', g.get_wrapping(-1, -1));
+    assert_eq!(&'b', g.get_wrapping(4, 2));
+    assert_eq!(vec![(1, 0), (0, 1)], g.neighbors(0, 0).collect::<Vec<_>>());
+    assert_eq!(5, g.neighbors8(1, 0).count());
+    assert_eq!(Some((1, 1)), g.position(|c| *c == 'e'));
+  }
+
+  #[test]
+  fn test_views() {
+    let g: Grid<char> = INPUT.parse().unwrap();
+    assert_eq!(&['d', 'e', 'f'], g.row(1));
+    assert_eq!("be", g.column(1).collect::<String>());
+    assert_eq!(vec!["ad", "be", "cf"], g.columns().map(|c| c.collect::<String>()).collect::<Vec<_>>());
+    assert_eq!("ad\nbe\ncf", g.transposed().to_string());
+    assert_eq!("da\neb\nfc", g.rotated_clockwise().to_string());
+    assert_eq!("cf\nbe\nad", g.rotated_anticlockwise().to_string());
+    assert_eq!(g, g.rotated_clockwise().rotated_anticlockwise());
+  }
+}
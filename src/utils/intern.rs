@@ -0,0 +1,71 @@
+//! A table of names with dense ids, so the puzzles with named nodes can refer to them by index
+//! and own their names instead of borrowing the input.
+
+use std::collections::HashMap;
+
+/// The id of a name, given out in the order the names are interned
+pub type Symbol = u32;
+
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+  ids: HashMap<String, Symbol>,
+  names: Vec<String>,
+}
+
+impl Interner {
+  pub fn new() -> Interner {
+    Interner::default()
+  }
+
+  /// The id of the name, adding it when it is new
+  pub fn intern(&mut self, name: &str) -> Symbol {
+    if let Some(id) = self.ids.get(name) {
+      return *id;
+    }
+    let id = self.names.len() as Symbol;
+    self.ids.insert(name.to_string(), id);
+    self.names.push(name.to_string());
+    id
+  }
+
+  /// The id of the name, or None when it has not been interned
+  pub fn get(&self, name: &str) -> Option<Symbol> {
+    self.ids.get(name).copied()
+  }
+
+  pub fn name(&self, id: Symbol) -> &str {
+    &self.names[id as usize]
+  }
+
+  pub fn len(&self) -> usize {
+    self.names.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.names.is_empty()
+  }
+
+  /// The ids with their names in the order they were interned
+  pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> {
+    self.names.iter().enumerate().map(|(id, name)| (id as Symbol, name.as_str()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::intern::Interner;
+
+  #[test]
+  fn test_interner() {
+    let mut names = Interner::new();
+    assert!(names.is_empty());
+    assert_eq!(0, names.intern("AAA"));
+    assert_eq!(1, names.intern("BBB"));
+    assert_eq!(0, names.intern("AAA"));
+    assert_eq!(2, names.len());
+    assert_eq!(Some(1), names.get("BBB"));
+    assert_eq!(None, names.get("CCC"));
+    assert_eq!("BBB", names.name(1));
+    assert_eq!(vec![(0, "AAA"), (1, "BBB")], names.iter().collect::<Vec<_>>());
+  }
+}
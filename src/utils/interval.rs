@@ -0,0 +1,386 @@
+//! Half-open intervals of integers, normalised sets of them, maps that move pieces of the number
+//! line, and boxes of intervals in several dimensions.
+
+use std::ops::{Add, Range, Sub};
+
+/// The values an interval can be made of
+pub trait Endpoint: Copy + Ord + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<T: Copy + Ord + Add<Output = T> + Sub<Output = T>> Endpoint for T {}
+
+/// The values from the start up to but not including the end
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Interval<T> {
+  pub start: T,
+  pub end: T,
+}
+
+impl<T: Endpoint> Interval<T> {
+  pub fn new(start: T, end: T) -> Interval<T> {
+    Interval { start, end }
+  }
+
+  pub fn from_len(start: T, len: T) -> Interval<T> {
+    Interval { start, end: start + len }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.start >= self.end
+  }
+
+  /// The number of values, which must not be called on an empty interval of an unsigned type
+  pub fn len(&self) -> T {
+    self.end - self.start
+  }
+
+  pub fn contains(&self, value: T) -> bool {
+    self.start <= value && value < self.end
+  }
+
+  pub fn overlaps(&self, other: &Interval<T>) -> bool {
+    self.start.max(other.start) < self.end.min(other.end)
+  }
+
+  /// The values in both intervals, or None when they do not overlap
+  pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+    let i = Interval::new(self.start.max(other.start), self.end.min(other.end));
+    (!i.is_empty()).then_some(i)
+  }
+
+  /// The values below the point and the values from it, either of which may be empty
+  pub fn split_at(&self, at: T) -> (Option<Interval<T>>, Option<Interval<T>>) {
+    let at = at.clamp(self.start, self.end.max(self.start));
+    let below = Interval::new(self.start, at);
+    let above = Interval::new(at, self.end);
+    ((!below.is_empty()).then_some(below), (!above.is_empty()).then_some(above))
+  }
+
+  pub fn range(&self) -> Range<T> {
+    self.start..self.end
+  }
+}
+
+/// A set of values kept as sorted intervals that neither overlap nor touch
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntervalSet<T> {
+  intervals: Vec<Interval<T>>,
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+  pub fn new() -> IntervalSet<T> {
+    IntervalSet { intervals: Vec::new() }
+  }
+
+  /// Sort the intervals and merge the ones that overlap or touch
+  fn normalised(mut intervals: Vec<Interval<T>>) -> IntervalSet<T> {
+    intervals.retain(|i| !i.is_empty());
+    intervals.sort();
+    let mut merged: Vec<Interval<T>> = Vec::with_capacity(intervals.len());
+    for i in intervals {
+      match merged.last_mut() {
+        Some(last) if i.start <= last.end => last.end = last.end.max(i.end),
+        _ => merged.push(i),
+      }
+    }
+    IntervalSet { intervals: merged }
+  }
+
+  pub fn insert(&mut self, interval: Interval<T>) {
+    let mut intervals = std::mem::take(&mut self.intervals);
+    intervals.push(interval);
+    *self = IntervalSet::normalised(intervals);
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.intervals.is_empty()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+    self.intervals.iter()
+  }
+
+  /// The lowest value in the set
+  pub fn min(&self) -> Option<T> {
+    self.intervals.first().map(|i| i.start)
+  }
+
+  pub fn contains(&self, value: T) -> bool {
+    let i = self.intervals.partition_point(|i| i.end <= value);
+    self.intervals.get(i).is_some_and(|i| i.contains(value))
+  }
+
+  pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+    IntervalSet::normalised(self.intervals.iter().chain(&other.intervals).copied().collect())
+  }
+
+  pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+    let intervals = self.intervals.iter()
+      .flat_map(|a| other.intervals.iter().filter_map(|b| a.intersection(b)))
+      .collect();
+    // The pieces are already sorted and apart, as the intervals of both sets are
+    IntervalSet { intervals }
+  }
+
+  /// The values in this set that are not in the other
+  pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+    let mut intervals = Vec::new();
+    for a in &self.intervals {
+      let mut start = a.start;
+      for b in other.intervals.iter().filter(|b| b.overlaps(a)) {
+        if start < b.start {
+          intervals.push(Interval::new(start, b.start));
+        }
+        start = start.max(b.end);
+      }
+      if start < a.end {
+        intervals.push(Interval::new(start, a.end));
+      }
+    }
+    IntervalSet { intervals }
+  }
+
+  /// The values below the point and the values from it
+  pub fn split_at(&self, at: T) -> (IntervalSet<T>, IntervalSet<T>) {
+    let (mut below, mut above) = (Vec::new(), Vec::new());
+    for i in &self.intervals {
+      let (b, a) = i.split_at(at);
+      below.extend(b);
+      above.extend(a);
+    }
+    (IntervalSet { intervals: below }, IntervalSet { intervals: above })
+  }
+}
+
+impl<T: Endpoint> From<Interval<T>> for IntervalSet<T> {
+  fn from(interval: Interval<T>) -> IntervalSet<T> {
+    IntervalSet::normalised(vec![interval])
+  }
+}
+
+impl<T: Endpoint> FromIterator<Interval<T>> for IntervalSet<T> {
+  fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> IntervalSet<T> {
+    IntervalSet::normalised(iter.into_iter().collect())
+  }
+}
+
+/// Moves intervals of values to new starts, leaving every other value where it is
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntervalMap<T> {
+  /// The sources sorted by their start, with where each one starts after the move
+  entries: Vec<(Interval<T>, T)>,
+}
+
+impl<T: Endpoint> IntervalMap<T> {
+  pub fn new() -> IntervalMap<T> {
+    IntervalMap { entries: Vec::new() }
+  }
+
+  /// Move the values of the source to start at the destination, or give back the source of an
+  /// entry it overlaps, leaving the map as it was
+  pub fn insert(&mut self, src: Interval<T>, dst: T) -> Result<(), Interval<T>> {
+    if src.is_empty() {
+      return Ok(());
+    }
+    let i = self.entries.partition_point(|(s, _)| s.start < src.start);
+    let overlapped = [i.checked_sub(1), Some(i)].into_iter().flatten()
+      .filter_map(|j| self.entries.get(j))
+      .find(|(s, _)| s.overlaps(&src));
+    if let Some((s, _)) = overlapped {
+      return Err(*s);
+    }
+    self.entries.insert(i, (src, dst));
+    Ok(())
+  }
+
+  /// The number of moved intervals
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn get(&self, value: T) -> T {
+    let i = self.entries.partition_point(|(s, _)| s.end <= value);
+    match self.entries.get(i) {
+      Some((src, dst)) if src.contains(value) => *dst + (value - src.start),
+      _ => value,
+    }
+  }
+
+  /// Where each piece of the interval goes, in the order of the pieces
+  pub fn map_interval(&self, interval: Interval<T>) -> Vec<Interval<T>> {
+    let mut pieces = Vec::new();
+    if interval.is_empty() {
+      return pieces;
+    }
+    let mut start = interval.start;
+    for (src, dst) in self.entries.iter().filter(|(s, _)| s.overlaps(&interval)) {
+      if start < src.start {
+        pieces.push(Interval::new(start, src.start));
+      }
+      let part = src.intersection(&interval).unwrap();
+      pieces.push(Interval::new(*dst + (part.start - src.start), *dst + (part.end - src.start)));
+      start = part.end;
+    }
+    if start < interval.end {
+      pieces.push(Interval::new(start, interval.end));
+    }
+    pieces
+  }
+
+  pub fn map_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+    set.iter().flat_map(|i| self.map_interval(*i)).collect()
+  }
+
+  /// The map that moves each value by this map and then by the next
+  pub fn then(&self, next: &IntervalMap<T>) -> IntervalMap<T> {
+    let mut entries = Vec::new();
+    for (src, dst) in &self.entries {
+      // The moved values go through the next map piece by piece
+      let mut start = src.start;
+      for piece in next.map_interval(Interval::from_len(*dst, src.len())) {
+        entries.push((Interval::from_len(start, piece.len()), piece.start));
+        start = start + piece.len();
+      }
+    }
+    // Where this map leaves the values, only the next one moves them
+    let moved: IntervalSet<T> = self.entries.iter().map(|(src, _)| *src).collect();
+    for (src, dst) in &next.entries {
+      for part in IntervalSet::from(*src).difference(&moved).iter() {
+        entries.push((*part, *dst + (part.start - src.start)));
+      }
+    }
+    entries.retain(|(src, dst)| src.start != *dst);
+    entries.sort();
+    IntervalMap { entries }
+  }
+}
+
+/// Panics when two of the sources overlap
+impl<T: Endpoint> FromIterator<(Interval<T>, T)> for IntervalMap<T> {
+  fn from_iter<I: IntoIterator<Item = (Interval<T>, T)>>(iter: I) -> IntervalMap<T> {
+    let mut map = IntervalMap::new();
+    for (src, dst) in iter {
+      map.insert(src, dst).unwrap_or_else(|_| panic!("The source overlaps another entry"));
+    }
+    map
+  }
+}
+
+/// The values inside an interval in each of N dimensions
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IntervalBox<T, const N: usize> {
+  pub dims: [Interval<T>; N],
+}
+
+impl<T: Endpoint, const N: usize> IntervalBox<T, N> {
+  pub fn new(dims: [Interval<T>; N]) -> IntervalBox<T, N> {
+    IntervalBox { dims }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.dims.iter().any(|d| d.is_empty())
+  }
+
+  pub fn overlaps(&self, other: &IntervalBox<T, N>) -> bool {
+    self.dims.iter().zip(&other.dims).all(|(a, b)| a.overlaps(b))
+  }
+
+  pub fn intersection(&self, other: &IntervalBox<T, N>) -> Option<IntervalBox<T, N>> {
+    let mut dims = self.dims;
+    for (d, o) in dims.iter_mut().zip(&other.dims) {
+      *d = d.intersection(o)?;
+    }
+    Some(IntervalBox { dims })
+  }
+
+  /// The part of the box below the point in the dimension and the part from it
+  pub fn split_at(&self, dim: usize, at: T) -> (Option<IntervalBox<T, N>>, Option<IntervalBox<T, N>>) {
+    let (below, above) = self.dims[dim].split_at(at);
+    let with = |i: Interval<T>| {
+      let mut dims = self.dims;
+      dims[dim] = i;
+      IntervalBox { dims }
+    };
+    (below.map(with), above.map(with))
+  }
+
+  /// The number of points inside the box
+  pub fn volume(&self) -> u64 where T: Into<u64> {
+    self.dims.iter()
+      .map(|d| if d.is_empty() { 0 } else { d.len().into() })
+      .product()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::interval::{Interval, IntervalBox, IntervalMap, IntervalSet};
+
+  fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+    intervals.iter().map(|(s, e)| Interval::new(*s, *e)).collect()
+  }
+
+  #[test]
+  fn test_interval() {
+    let i = Interval::new(2, 6);
+    assert_eq!(4, i.len());
+    assert!(i.contains(2) && !i.contains(6));
+    assert_eq!(Some(Interval::new(4, 6)), i.intersection(&Interval::new(4, 9)));
+    assert_eq!(None, i.intersection(&Interval::new(6, 9)));
+    assert_eq!((Some(Interval::new(2, 3)), Some(Interval::new(3, 6))), i.split_at(3));
+    assert_eq!((None, Some(i)), i.split_at(0));
+    assert_eq!((Some(i), None), i.split_at(6));
+  }
+
+  #[test]
+  fn test_set() {
+    let s = set(&[(5, 8), (1, 3), (3, 4), (7, 10)]);
+    assert_eq!(vec![Interval::new(1, 4), Interval::new(5, 10)], s.iter().copied().collect::<Vec<_>>());
+    assert!(s.contains(3) && !s.contains(4) && s.contains(9));
+    assert_eq!(Some(1), s.min());
+
+    let t = set(&[(2, 6)]);
+    assert_eq!(set(&[(1, 10)]), s.union(&t));
+    assert_eq!(set(&[(2, 4), (5, 6)]), s.intersection(&t));
+    assert_eq!(set(&[(1, 2), (6, 10)]), s.difference(&t));
+    assert_eq!((set(&[(1, 4), (5, 6)]), set(&[(6, 10)])), s.split_at(6));
+  }
+
+  #[test]
+  fn test_map() {
+    let m: IntervalMap<i32> = [(Interval::new(5, 11), 50), (Interval::new(15, 25), 150)].into_iter().collect();
+    assert_eq!((4, 52, 12, 150), (m.get(4), m.get(7), m.get(12), m.get(15)));
+    assert_eq!(vec![Interval::new(53, 56), Interval::new(11, 15), Interval::new(150, 151)],
+               m.map_interval(Interval::new(8, 16)));
+
+    let mut overlapping = m.clone();
+    assert_eq!(Err(Interval::new(5, 11)), overlapping.insert(Interval::new(0, 6), 0));
+    assert_eq!(Err(Interval::new(15, 25)), overlapping.insert(Interval::new(20, 21), 0));
+    assert_eq!(Ok(()), overlapping.insert(Interval::new(11, 15), 0));
+    assert_eq!(3, overlapping.len());
+
+    let next: IntervalMap<i32> = [(Interval::new(0, 10), 100), (Interval::new(50, 52), 0)].into_iter().collect();
+    let composed = m.then(&next);
+    for v in -5..200 {
+      assert_eq!(next.get(m.get(v)), composed.get(v), "{v}");
+    }
+    assert_eq!(set(&[(0, 2), (11, 15), (52, 56), (100, 105), (150, 151)]),
+               composed.map_set(&set(&[(0, 16)])));
+  }
+
+  #[test]
+  fn test_box() {
+    let b = IntervalBox::new([Interval::new(1, 11_u32), Interval::new(1, 11)]);
+    assert_eq!(100, b.volume());
+    let (below, above) = b.split_at(1, 5);
+    assert_eq!(40, below.unwrap().volume());
+    assert_eq!(60, above.unwrap().volume());
+    assert!(!below.unwrap().overlaps(&above.unwrap()));
+    assert_eq!(None, b.split_at(0, 1).0);
+    let c = IntervalBox::new([Interval::new(5, 20), Interval::new(0, 2)]);
+    assert_eq!(Some(IntervalBox::new([Interval::new(5, 11), Interval::new(1, 2)])), b.intersection(&c));
+  }
+}
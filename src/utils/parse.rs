@@ -0,0 +1,82 @@
+//! Helpers for the pieces the generators have in common: numbers, the sections around a
+//! separator, a fixed number of values and blocks separated by blank lines. The tokens are slices
+//! of the text, so every error points at the position of the problem within the text.
+
+use std::str::FromStr;
+
+use crate::utils::{parse_at, ParseError};
+
+/// Parse the numbers separated by whitespace
+pub fn numbers<T: FromStr>(text: &str, token: &str) -> Result<Vec<T>, ParseError> {
+  token.split_whitespace().map(|t| parse_at(text, t)).collect()
+}
+
+/// Parse the numbers separated by the separator, ignoring the whitespace around them
+pub fn numbers_by<T: FromStr>(text: &str, token: &str, separator: &str) -> Result<Vec<T>, ParseError> {
+  token.split(separator).map(|t| parse_at(text, t.trim())).collect()
+}
+
+/// The values as an array, or an error when there are not exactly N of them
+pub fn exactly<T, const N: usize>(text: &str, token: &str, values: Vec<T>) -> Result<[T; N], ParseError> {
+  let len = values.len();
+  values.try_into()
+    .map_err(|_| ParseError::at(text, token, format!("Expected {N} values but found {len}")))
+}
+
+/// Parse exactly N numbers separated by the separator
+pub fn array<T: FromStr, const N: usize>(text: &str, token: &str, separator: &str) -> Result<[T; N], ParseError> {
+  exactly(text, token, numbers_by(text, token, separator)?)
+}
+
+/// The parts before and after the first separator, or an error saying what was expected
+pub fn split_once<'a>(text: &str, token: &'a str, separator: &str,
+                      expected: &str) -> Result<(&'a str, &'a str), ParseError> {
+  token.split_once(separator)
+    .ok_or_else(|| ParseError::at(text, token, format!("Expected {expected}")))
+}
+
+/// The blocks of lines separated by blank lines
+pub fn blocks(text: &str) -> impl Iterator<Item = &str> {
+  text.split("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::parse::{array, blocks, exactly, numbers, numbers_by, split_once};
+  use crate::utils::ParseError;
+
+  #[test]
+  fn test_numbers() {
+    let text = "Time:  7 15\nDistance: 9 x";
+    assert_eq!(Ok(vec![7_u32, 15]), numbers(text, &text[5..11]));
+    let e = numbers::<u32>(text, &text[22..]).unwrap_err();
+    assert_eq!((2, 13), (e.line, e.column));
+
+    let text = "19, 13, -30 @ -2,  1, -2";
+    assert_eq!(Ok(vec![19_i64, 13, -30]), numbers_by(text, &text[..11], ","));
+    assert_eq!(Ok([-2_i64, 1, -2]), array(text, &text[14..], ","));
+    let e = array::<i64, 2>(text, &text[14..], ",").unwrap_err();
+    assert_eq!("Expected 2 values but found 3", e.message);
+    assert_eq!(15, e.column);
+  }
+
+  #[test]
+  fn test_exactly() {
+    let text = "Card 12: 41 48";
+    assert_eq!(Ok([41_u32, 48]), exactly(text, &text[9..], numbers(text, &text[9..]).unwrap()));
+    let e = exactly::<u32, 3>(text, &text[9..], vec![41, 48]).unwrap_err();
+    assert_eq!("Expected 3 values but found 2", e.message);
+    assert_eq!(10, e.column);
+  }
+
+  #[test]
+  fn test_sections() {
+    let text = "Game 1: 3 blue";
+    assert_eq!(Ok(("Game 1", "3 blue")), split_once(text, text, ": ", "'Game <id>: <reveals>'"));
+    let e: ParseError = split_once(text, &text[8..], "; ", "reveals").unwrap_err();
+    assert_eq!("Expected reveals", e.message);
+    assert_eq!(9, e.column);
+
+    assert_eq!(vec!["a\nb", "c"], blocks("a\nb\n\nc").collect::<Vec<_>>());
+  }
+}
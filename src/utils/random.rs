@@ -0,0 +1,177 @@
+//! Seeded random inputs for stress testing the days beyond the inputs in the repo. Each day has a
+//! generator that writes an input in its format from a random number generator and a size, and the
+//! same seed always gives the same input.
+
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::utils::geom::{Dir4, Point2};
+use crate::utils::geom::Dir4::{East, North, South, West};
+
+/// The random number generator of the inputs, which gives the same numbers on every platform
+pub type Random = ChaCha8Rng;
+
+/// Write a random input of about the size, which is the lines, the side of a grid or the nodes
+pub type InputGenerator = fn(&mut Random, usize) -> String;
+
+/// The random number generator for the seed
+pub fn seeded(seed: u64) -> Random {
+  Random::seed_from_u64(seed)
+}
+
+/// Distinct random names of the length from the letters
+pub fn names(rng: &mut Random, count: usize, len: usize, letters: &str) -> Vec<String> {
+  let letters: Vec<char> = letters.chars().collect();
+  assert!((count as f64) <= (letters.len() as f64).powi(len as i32), "Not enough names for {count}");
+  let mut seen = HashSet::new();
+  let mut names = Vec::with_capacity(count);
+  while names.len() < count {
+    let name: String = (0..len).map(|_| *letters.choose(rng).unwrap()).collect();
+    if seen.insert(name.clone()) {
+      names.push(name);
+    }
+  }
+  names
+}
+
+/// The rows of a grid with the character of each cell
+pub fn grid(width: usize, height: usize, mut cell: impl FnMut(usize, usize) -> char) -> String {
+  (0..height)
+    .map(|y| (0..width).map(|x| cell(x, y)).collect::<String>())
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// A random closed loop that never touches itself, as the tiles it passes through in order. The
+/// grid is made of square blocks with a ring of tiles around each, and the rings of the blocks on
+/// a random tree are joined into one loop. Returns the side of the square grid with the tiles.
+pub fn closed_loop(rng: &mut Random, blocks: usize, block: usize) -> (usize, Vec<Point2<usize>>) {
+  assert!(block >= 4, "The blocks need room to join their rings");
+  let blocks = blocks.max(1);
+  let side = blocks * block;
+  let mut connects = vec![[false; 4]; side * side];
+  let index = |x: usize, y: usize| y * side + x;
+
+  let nodes = rng.gen_range(blocks * blocks / 2..=blocks * blocks).max(1);
+  let tree = random_tree(rng, blocks, nodes);
+  for &(bx, by) in &tree.nodes {
+    let (x0, y0, x1, y1) = (bx * block, by * block, bx * block + block - 1, by * block + block - 1);
+    for x in x0..x1 {
+      connects[index(x, y0)][East as usize] = true;
+      connects[index(x + 1, y0)][West as usize] = true;
+      connects[index(x, y1)][East as usize] = true;
+      connects[index(x + 1, y1)][West as usize] = true;
+    }
+    for y in y0..y1 {
+      connects[index(x0, y)][South as usize] = true;
+      connects[index(x0, y + 1)][North as usize] = true;
+      connects[index(x1, y)][South as usize] = true;
+      connects[index(x1, y + 1)][North as usize] = true;
+    }
+  }
+  // Swap two parallel sides of the neighboring rings for two joins, which makes them one loop
+  for &((ax, ay), (bx, by)) in &tree.edges {
+    let offset = rng.gen_range(1..=block - 3);
+    let (dir, side_dir) = if ax != bx { (East, South) } else { (South, East) };
+    let (ax, ay) = (ax.min(bx), ay.min(by));
+    for (i, step) in [(0, side_dir), (1, side_dir.reverse())] {
+      let (x, y) = if dir == East {
+        (ax * block + block - 1, ay * block + offset + i)
+      } else {
+        (ax * block + offset + i, ay * block + block - 1)
+      };
+      let (nx, ny) = if dir == East { (x + 1, y) } else { (x, y + 1) };
+      connects[index(x, y)][step as usize] = false;
+      connects[index(x, y)][dir as usize] = true;
+      connects[index(nx, ny)][step as usize] = false;
+      connects[index(nx, ny)][dir.reverse() as usize] = true;
+    }
+  }
+
+  // Follow the loop from the corner of the first block
+  let (bx, by) = tree.nodes[0];
+  let start = Point2::new(bx * block, by * block);
+  let mut tiles = vec![start];
+  let mut from = North;
+  let mut curr = start;
+  loop {
+    let dir = Dir4::ALL.into_iter()
+      .find(|d| *d != from && connects[index(curr.x, curr.y)][*d as usize])
+      .unwrap();
+    curr = (curr.signed() + dir.delta()).unsigned().unwrap();
+    if curr == start {
+      break;
+    }
+    from = dir.reverse();
+    tiles.push(curr);
+  }
+  (side, tiles)
+}
+
+/// The cells of a random tree on a square of cells, with the edges between them
+struct Tree {
+  nodes: Vec<(usize, usize)>,
+  edges: Vec<((usize, usize), (usize, usize))>,
+}
+
+/// Grow a tree from a random cell by adding a random neighbor of the tree until it has the nodes
+fn random_tree(rng: &mut Random, side: usize, nodes: usize) -> Tree {
+  let start = (rng.gen_range(0..side), rng.gen_range(0..side));
+  let mut tree = Tree { nodes: vec![start], edges: Vec::new() };
+  let mut seen = HashSet::from([start]);
+  let mut frontier = Vec::new();
+  let neighbors = |(x, y): (usize, usize)| {
+    [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)]
+      .into_iter()
+      .filter(move |(x, y)| *x < side && *y < side)
+      .map(move |n| ((x, y), n))
+  };
+  frontier.extend(neighbors(start));
+  while tree.nodes.len() < nodes && !frontier.is_empty() {
+    let (from, to) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+    if seen.insert(to) {
+      tree.nodes.push(to);
+      tree.edges.push((from, to));
+      frontier.extend(neighbors(to));
+    }
+  }
+  tree
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashSet;
+
+  use rand::Rng;
+
+  use crate::utils::random::{closed_loop, grid, names, seeded};
+
+  #[test]
+  fn test_seeded() {
+    let values = |seed| seeded(seed).gen::<[u64; 4]>();
+    assert_eq!(values(42), values(42));
+    assert_ne!(values(42), values(43));
+
+    let mut rng = seeded(1);
+    let mut names = names(&mut rng, 4, 2, "ab");
+    names.sort();
+    assert_eq!(vec!["aa", "ab", "ba", "bb"], names);
+    assert_eq!("ab\nab", grid(2, 2, |x, _| if x == 0 { 'a' } else { 'b' }));
+  }
+
+  #[test]
+  fn test_closed_loop() {
+    for seed in 0..20 {
+      let (side, tiles) = closed_loop(&mut seeded(seed), 4, 4 + seed as usize % 3);
+      assert_eq!(tiles.len(), tiles.iter().collect::<HashSet<_>>().len());
+      for (i, a) in tiles.iter().enumerate() {
+        let b = tiles[(i + 1) % tiles.len()];
+        assert!(a.x < side && a.y < side);
+        assert_eq!(1, a.manhattan(&b), "Tiles {a} and {b} are not neighbors");
+      }
+    }
+  }
+}